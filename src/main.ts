@@ -62,7 +62,6 @@ async function main() {
   let dragButton = 0; // 0 left rotate, 1 middle pan, 2 right pan
 
   const ROTATE_SPEED = 0.01;
-  const PAN_SPEED = 0.002;
 
   canvas.addEventListener("contextmenu", (e) => e.preventDefault());
 
@@ -87,7 +86,9 @@ async function main() {
     if (dragButton === 0) {
       viewer.rotate(dx * ROTATE_SPEED, -dy * ROTATE_SPEED);
     } else {
-      viewer.pan(-dx * PAN_SPEED, dy * PAN_SPEED);
+      // Pan takes drawing-buffer pixels and scales them to the camera distance.
+      const dpr = window.devicePixelRatio || 1;
+      viewer.pan(-dx * dpr, dy * dpr);
     }
     viewer.draw();
   });
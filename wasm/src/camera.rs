@@ -1,4 +1,5 @@
-use crate::math::Vec3;
+use crate::math::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Bounds {
@@ -19,6 +20,182 @@ impl Bounds {
         // bounding sphere radius from AABB
         self.max.sub(self.min).length() * 0.5
     }
+
+    /// These bounds grown about their center so every side is at least `margin` long;
+    /// longer sides are unchanged. Gives flat and point-like meshes some depth.
+    pub fn padded(&self, margin: f32) -> Bounds {
+        let grow = |min: f32, max: f32| {
+            let pad = ((margin - (max - min)) * 0.5).max(0.0);
+            (min - pad, max + pad)
+        };
+        let (x0, x1) = grow(self.min.x, self.max.x);
+        let (y0, y1) = grow(self.min.y, self.max.y);
+        let (z0, z1) = grow(self.min.z, self.max.z);
+        Bounds::new(Vec3::new(x0, y0, z0), Vec3::new(x1, y1, z1))
+    }
+
+    /// Corner `i` of the box, for `i` in 0..8: bits 0, 1 and 2 pick the max side in x, y
+    /// and z.
+    pub fn corner(&self, i: usize) -> Vec3 {
+        let bit = |b: usize| ((i >> b) & 1) as f32;
+        let side = Vec3::new(bit(0), bit(1), bit(2));
+        self.min.add(self.max.sub(self.min).mul_vec(side))
+    }
+
+    /// Axis-aligned box around these bounds' corners after `m`, e.g. a model matrix.
+    pub fn transformed(&self, m: &Mat4) -> Bounds {
+        let corners = (0..8).map(|i| m.mul_point(self.corner(i)));
+        let first = m.mul_point(self.min);
+        let (min, max) = corners.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        Bounds::new(min, max)
+    }
+
+    pub fn summary(&self) -> BoundsSummary {
+        let c = self.center();
+        BoundsSummary {
+            min: [self.min.x, self.min.y, self.min.z],
+            max: [self.max.x, self.max.y, self.max.z],
+            center: [c.x, c.y, c.z],
+            radius: self.radius(),
+        }
+    }
+
+    /// Conservative AABB-vs-frustum test against planes from `Camera::frustum_planes`:
+    /// false only when the box lies entirely outside one plane.
+    #[allow(dead_code)] // for culling before draw_elements; the viewer draws one mesh today
+    pub fn intersects_frustum(&self, planes: &[[f32; 4]; 6]) -> bool {
+        planes.iter().all(|&[a, b, c, d]| {
+            // Corner furthest along the plane normal.
+            let x = if a >= 0.0 { self.max.x } else { self.min.x };
+            let y = if b >= 0.0 { self.max.y } else { self.min.y };
+            let z = if c >= 0.0 { self.max.z } else { self.min.z };
+            a * x + b * y + c * z + d >= 0.0
+        })
+    }
+}
+
+/// Serializable bounds for JS, e.g. to show a model's dimensions.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub(crate) struct BoundsSummary {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    pub center: [f32; 3],
+    /// Radius of the sphere through the box corners.
+    pub radius: f32,
+}
+
+/// Default orbit pitch limit, about 88.2 degrees: close to the poles without reaching them.
+const DEFAULT_PITCH_LIMIT: f32 = 1.54;
+
+/// Largest usable pitch magnitude. Straight up or down the view direction is parallel to
+/// world up and the look-at basis breaks down, so a limit of 90 degrees stops just short.
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 1e-4;
+
+/// How far a fit lets the user zoom: in to 1/N and out to N times the fitted distance.
+const FIT_ZOOM_RANGE: f32 = 100.0;
+
+/// `zoom_by_delta` sensitivity for browser wheel events: one ~100-unit notch of
+/// `deltaY` changes the distance by about 10%.
+pub(crate) const WHEEL_ZOOM_SENSITIVITY: f32 = 0.001;
+
+/// Fits treat every side of the bounds as at least this fraction of the longest one.
+const MIN_FIT_EXTENT: f32 = 0.01;
+
+/// Margin applied by `fit_to_bounds` (the bounding sphere fills ~87% of the view).
+pub(crate) const DEFAULT_FIT_PADDING: f32 = 1.15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProjectionMode {
+    Perspective,
+    Orthographic,
+}
+
+impl ProjectionMode {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "perspective" => Some(Self::Perspective),
+            "orthographic" | "orth" => Some(Self::Orthographic),
+            _ => None,
+        }
+    }
+}
+
+/// How pointer drags turn the camera.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RotationMode {
+    /// Yaw around world up and pitch, horizon kept level.
+    Orbit,
+    /// Free trackball rotation (`Camera::arcball_rotate`).
+    Arcball,
+}
+
+impl RotationMode {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "orbit" => Some(Self::Orbit),
+            "arcball" => Some(Self::Arcball),
+            _ => None,
+        }
+    }
+}
+
+/// Which angles `Camera::orbit` may change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OrbitMode {
+    /// Yaw and pitch.
+    Free,
+    /// Yaw only, pitch locked: a product on a turntable.
+    Turntable,
+}
+
+impl OrbitMode {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "free" => Some(Self::Free),
+            "turntable" => Some(Self::Turntable),
+            _ => None,
+        }
+    }
+}
+
+/// Canned viewing directions for `Camera::preset_direction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViewPreset {
+    Front,
+    Back,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    /// Isometric: from the front, right and top at once.
+    Iso,
+}
+
+impl ViewPreset {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "front" => Some(Self::Front),
+            "back" => Some(Self::Back),
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "iso" => Some(Self::Iso),
+            _ => None,
+        }
+    }
+}
+
+/// Serializable snapshot of the orbit parameters, for view bookmarks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CameraState {
+    pub target: [f32; 3],
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    #[serde(default)]
+    pub roll: f32,
+    pub fovy: f32,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -27,15 +204,40 @@ pub(crate) struct Camera {
     pub target: Vec3,
     /// Distance from target.
     pub distance: f32,
-    /// Yaw (around +Y), radians.
+    /// Direction the orbit treats as up: yaw turns around it and pitch tilts toward it.
+    pub world_up: Vec3,
+    /// Yaw (around `world_up`), radians.
     pub yaw: f32,
     /// Pitch (around +X in camera-local), radians.
     pub pitch: f32,
+    /// Roll around the view direction, radians (0 keeps the horizon level).
+    pub roll: f32,
     /// Vertical field-of-view (radians).
     pub fovy: f32,
     /// Near/far clip.
     pub znear: f32,
     pub zfar: f32,
+    /// Let `fit_to_*` derive `znear`/`zfar` from the framed size. Cleared by `set_clip`.
+    pub clip_auto: bool,
+    /// Perspective or parallel projection.
+    pub projection_mode: ProjectionMode,
+    /// Fraction of the remaining orbit/zoom motion kept per 60 Hz frame (0 = instant).
+    pub damping: f32,
+    /// Whether `orbit` applies pitch deltas.
+    pub orbit_mode: OrbitMode,
+    /// Range `orbit` and `arcball_rotate` keep pitch in, radians; see `set_pitch_limits`.
+    pub pitch_min: f32,
+    pub pitch_max: f32,
+    /// Range `zoom` keeps the distance in; see `set_zoom_limits`. Fits widen it.
+    pub distance_min: f32,
+    pub distance_max: f32,
+    /// Radius of the sphere the last fit framed; `clip_auto` clip planes are kept around
+    /// it. None until the first fit.
+    framed_radius: Option<f32>,
+    /// Values `orbit`/`zoom` aim for; `update` eases yaw/pitch/distance toward them.
+    goal_yaw: f32,
+    goal_pitch: f32,
+    goal_distance: f32,
 }
 
 impl Camera {
@@ -43,11 +245,166 @@ impl Camera {
         Self {
             target: Vec3::new(0.0, 0.0, 0.0),
             distance: 2.0,
+            world_up: Vec3::new(0.0, 1.0, 0.0),
             yaw: 0.0,
             pitch: 0.0,
+            roll: 0.0,
             fovy: 45_f32.to_radians(),
             znear: 0.01,
             zfar: 1000.0,
+            clip_auto: true,
+            projection_mode: ProjectionMode::Perspective,
+            damping: 0.0,
+            orbit_mode: OrbitMode::Free,
+            pitch_min: -DEFAULT_PITCH_LIMIT,
+            pitch_max: DEFAULT_PITCH_LIMIT,
+            distance_min: 0.05,
+            distance_max: 1.0e6,
+            framed_radius: None,
+            goal_yaw: 0.0,
+            goal_pitch: 0.0,
+            goal_distance: 2.0,
+        }
+    }
+
+    /// Ease yaw/pitch/distance toward the orbit/zoom goals by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        if self.damping <= 0.0 || !dt.is_finite() {
+            self.settle();
+            return;
+        }
+        let t = 1.0 - self.damping.powf(dt.max(0.0) * 60.0);
+        self.yaw += (self.goal_yaw - self.yaw) * t;
+        self.pitch += (self.goal_pitch - self.pitch) * t;
+        self.distance += (self.goal_distance - self.distance) * t;
+    }
+
+    /// Jump straight to the goals (used when damping is off).
+    fn settle(&mut self) {
+        self.yaw = self.goal_yaw;
+        self.pitch = self.goal_pitch;
+        self.distance = self.goal_distance;
+    }
+
+    /// Make the goals match the current values after a direct change.
+    fn sync_goals(&mut self) {
+        self.goal_yaw = self.yaw;
+        self.goal_pitch = self.pitch;
+        self.goal_distance = self.distance;
+    }
+
+    /// Restore the default orientation and target; distance is left for a refit.
+    pub fn reset(&mut self) {
+        let home = Self::new();
+        self.target = home.target;
+        self.yaw = home.yaw;
+        self.pitch = home.pitch;
+        self.roll = home.roll;
+        self.sync_goals();
+    }
+
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            target: [self.target.x, self.target.y, self.target.z],
+            distance: self.distance,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            roll: self.roll,
+            fovy: self.fovy,
+        }
+    }
+
+    /// Apply a snapshot from `state`. Errors on non-finite fields or a distance that isn't
+    /// positive; pitch and distance are pulled into the orbit and zoom limits and the
+    /// field of view into 10..=120 degrees, as the interactive controls would.
+    pub fn set_state(&mut self, state: CameraState) -> Result<(), String> {
+        let CameraState {
+            target,
+            distance,
+            yaw,
+            pitch,
+            roll,
+            fovy,
+        } = state;
+        let [x, y, z] = target;
+        if ![x, y, z, distance, yaw, pitch, roll, fovy]
+            .iter()
+            .all(|v| v.is_finite())
+        {
+            return Err(format!("camera state has non-finite values: {state:?}"));
+        }
+        if distance <= 0.0 {
+            return Err(format!("camera distance must be positive, got {distance}"));
+        }
+        self.target = Vec3::new(x, y, z);
+        self.distance = distance.clamp(self.distance_min, self.distance_max);
+        self.yaw = yaw;
+        self.pitch = self.clamp_pitch(pitch);
+        self.roll = roll;
+        self.fovy = fovy.clamp(10_f32.to_radians(), 120_f32.to_radians());
+        self.refresh_auto_clip();
+        self.sync_goals();
+        Ok(())
+    }
+
+    /// Place the camera at `eye` looking at `target`, as the equivalent orbit: later
+    /// orbits and zooms continue from there. Roll is cleared and pitch stays within the
+    /// orbit limits. Errors on non-finite points or an eye on the target.
+    pub fn set_look_at(&mut self, eye: Vec3, target: Vec3) -> Result<(), String> {
+        let points = [eye.x, eye.y, eye.z, target.x, target.y, target.z];
+        if !points.iter().all(|v| v.is_finite()) {
+            return Err(format!(
+                "look-at points must be finite: eye {eye:?}, target {target:?}"
+            ));
+        }
+        let offset = eye.sub(target);
+        let distance = offset.length();
+        if !(distance.is_finite() && distance > 1e-6) {
+            return Err(format!("eye {eye:?} must be away from target {target:?}"));
+        }
+        self.target = target;
+        let back = offset.div(distance);
+        let (side, up, front) = self.orbit_frame();
+        self.yaw = back.dot(side).atan2(back.dot(front));
+        self.pitch = self.clamp_pitch(back.dot(up).clamp(-1.0, 1.0).asin());
+        self.roll = 0.0;
+        self.distance = distance;
+        // Keep the next zoom from snapping back into the old range.
+        self.distance_min = self.distance_min.min(distance);
+        self.distance_max = self.distance_max.max(distance);
+        self.refresh_auto_clip();
+        self.sync_goals();
+        Ok(())
+    }
+
+    /// Turn to look at the target from along `dir` (pointing from the target toward the
+    /// eye), roll cleared; target and distance stay, so refit afterwards. Pitch stays
+    /// within the orbit limits, so looking straight down stops just short of the pole. A
+    /// zero `dir` keeps the orientation.
+    pub fn view_from_direction(&mut self, dir: Vec3) {
+        let length = dir.length();
+        if length.is_finite() && length > 1e-6 {
+            let back = dir.div(length);
+            let (side, up, front) = self.orbit_frame();
+            self.yaw = back.dot(side).atan2(back.dot(front));
+            self.pitch = self.clamp_pitch(back.dot(up).clamp(-1.0, 1.0).asin());
+            self.roll = 0.0;
+        }
+        self.sync_goals();
+    }
+
+    /// World direction from the target toward the eye for `preset`, relative to
+    /// `world_up`: with Y up, "front" looks along -Z and "right" along -X.
+    pub fn preset_direction(&self, preset: ViewPreset) -> Vec3 {
+        let (side, up, front) = self.orbit_frame();
+        match preset {
+            ViewPreset::Front => front,
+            ViewPreset::Back => front.mul(-1.0),
+            ViewPreset::Top => up,
+            ViewPreset::Bottom => up.mul(-1.0),
+            ViewPreset::Left => side.mul(-1.0),
+            ViewPreset::Right => side,
+            ViewPreset::Iso => front.add(side).add(up),
         }
     }
 
@@ -59,38 +416,262 @@ impl Camera {
         let sp = self.pitch.sin();
 
         // Forward direction from target to eye (spherical coordinates).
-        let dir = Vec3::new(cp * sy, sp, cp * cy);
+        let (side, up, front) = self.orbit_frame();
+        let dir = side.mul(cp * sy).add(up.mul(sp)).add(front.mul(cp * cy));
         self.target.add(dir.mul(self.distance))
     }
 
+    /// Axes the orbit angles are measured in: yaw 0 and pitch 0 put the eye along
+    /// `front`, positive yaw swings it toward `side`. With Y up these are +X, +Y, +Z;
+    /// with Z up the home view looks along +Y from the -Y side.
+    pub(crate) fn orbit_frame(&self) -> (Vec3, Vec3, Vec3) {
+        let up = self.world_up.normalize();
+        let mut side = Vec3::new(1.0, 0.0, 0.0);
+        side = side.sub(up.mul(up.dot(side)));
+        if side.length() <= 1e-4 {
+            // Up is the X axis; measure yaw from Y instead.
+            side = Vec3::new(0.0, 1.0, 0.0).sub(up.mul(up.y));
+        }
+        let side = side.normalize();
+        (side, up, side.cross(up))
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at(self.eye(), self.target, self.view_up())
+    }
+
+    /// Projection for the current mode; `orthographic_size` is the half-height in ortho.
+    pub fn projection_matrix(&self, aspect: f32, orthographic_size: f32) -> Mat4 {
+        match self.projection_mode {
+            ProjectionMode::Perspective => {
+                Mat4::perspective(self.fovy, aspect, self.znear, self.zfar)
+            }
+            ProjectionMode::Orthographic => {
+                let h = orthographic_size;
+                let w = h * aspect;
+                Mat4::orthographic(-w, w, -h, h, self.znear, self.zfar)
+            }
+        }
+    }
+
+    /// World-space clip planes (left, right, bottom, top, near, far) of the current
+    /// view-projection, as `[a, b, c, d]` with normals pointing inward and normalized, so
+    /// `a*x + b*y + c*z + d` is the signed distance of a point from the plane.
+    #[allow(dead_code)] // input to Bounds::intersects_frustum, which nothing calls yet
+    pub fn frustum_planes(&self, aspect: f32, orthographic_size: f32) -> [[f32; 4]; 6] {
+        let m = self
+            .projection_matrix(aspect, orthographic_size)
+            .mul(self.view_matrix())
+            .m;
+        let row = |i: usize| [m[i], m[4 + i], m[8 + i], m[12 + i]];
+        let w = row(3);
+        let mut planes = [[0.0; 4]; 6];
+        for (axis, pair) in planes.chunks_exact_mut(2).enumerate() {
+            let r = row(axis);
+            for (plane, sign) in pair.iter_mut().zip([1.0, -1.0]) {
+                let p: [f32; 4] = std::array::from_fn(|k| w[k] + sign * r[k]);
+                let len = Vec3::new(p[0], p[1], p[2]).length().max(1e-12);
+                *plane = p.map(|v| v / len);
+            }
+        }
+        planes
+    }
+
     pub fn view_up(&self) -> Vec3 {
-        Vec3::new(0.0, 1.0, 0.0)
+        let world_up = self.world_up.normalize();
+        if self.roll == 0.0 {
+            return world_up;
+        }
+
+        // Rotate the level camera up vector around the forward axis.
+        let forward = self.target.sub(self.eye()).normalize();
+        let right = forward.cross(world_up).normalize();
+        let up = right.cross(forward);
+        up.mul(self.roll.cos()).add(right.mul(self.roll.sin()))
+    }
+
+    /// Set the vertical field of view in degrees, clamped to 10..=120. Non-finite input
+    /// is ignored. The distance is unchanged, so the framing widens or narrows.
+    pub fn set_fov(&mut self, degrees: f32) {
+        if degrees.is_finite() {
+            self.fovy = degrees.clamp(10.0, 120.0).to_radians();
+        }
+    }
+
+    /// Pin the clip planes; later fits keep them until `clip_auto` is turned back on.
+    /// Requires `0 < znear < zfar`.
+    pub fn set_clip(&mut self, znear: f32, zfar: f32) -> Result<(), String> {
+        if !(znear > 0.0 && znear.is_finite()) {
+            return Err(format!("znear must be positive, got {znear}"));
+        }
+        if !(zfar > znear && zfar.is_finite()) {
+            return Err(format!(
+                "zfar ({zfar}) must be greater than znear ({znear})"
+            ));
+        }
+        self.znear = znear;
+        self.zfar = zfar;
+        self.clip_auto = false;
+        Ok(())
+    }
+
+    pub fn roll(&mut self, delta_roll: f32) {
+        self.roll += delta_roll;
+    }
+
+    /// Limit orbit pitch to `min..=max` radians and pull the camera into that range.
+    /// Limits up to +/-90 degrees are accepted; at the poles the pitch stops a hair short
+    /// so the view stays defined. Errors unless `min < max`.
+    pub fn set_pitch_limits(&mut self, min: f32, max: f32) -> Result<(), String> {
+        if !(min.is_finite() && max.is_finite() && min < max) {
+            return Err(format!("pitch min ({min}) must be less than max ({max})"));
+        }
+        self.pitch_min = min.clamp(-MAX_PITCH, MAX_PITCH);
+        self.pitch_max = max.clamp(-MAX_PITCH, MAX_PITCH);
+        self.pitch = self.clamp_pitch(self.pitch);
+        self.goal_pitch = self.clamp_pitch(self.goal_pitch);
+        Ok(())
+    }
+
+    fn clamp_pitch(&self, pitch: f32) -> f32 {
+        pitch.clamp(self.pitch_min, self.pitch_max)
     }
 
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
-        self.yaw += delta_yaw;
-        self.pitch = (self.pitch + delta_pitch).clamp(-1.54, 1.54); // ~ +/- 88.2 deg
+        self.goal_yaw += delta_yaw;
+        let delta_pitch = match self.orbit_mode {
+            OrbitMode::Free => delta_pitch,
+            OrbitMode::Turntable => 0.0,
+        };
+        self.goal_pitch = self.clamp_pitch(self.goal_pitch + delta_pitch);
+        if self.damping <= 0.0 {
+            self.settle();
+        }
+    }
+
+    /// Trackball rotation for a drag between two points in arcball coordinates (see
+    /// `arcball_point`): the model turns so the surface under the cursor follows it.
+    /// Pitch stays within the orbit limits, so the camera cannot pass over a pole.
+    pub fn arcball_rotate(&mut self, prev_ndc: (f32, f32), curr_ndc: (f32, f32)) {
+        let from = arcball_point(prev_ndc.0, prev_ndc.1);
+        let to = arcball_point(curr_ndc.0, curr_ndc.1);
+        let axis_vs = from.cross(to);
+        if axis_vs.length() <= 1e-7 {
+            return;
+        }
+        let angle = from.dot(to).clamp(-1.0, 1.0).acos();
+
+        // The view-space axis in world space, from the camera basis.
+        let back = self.eye().sub(self.target).normalize();
+        let up = self.view_up().normalize();
+        let right = up.cross(back);
+        let axis = right
+            .mul(axis_vs.x)
+            .add(up.mul(axis_vs.y))
+            .add(back.mul(axis_vs.z));
+        // Turning the model one way is turning the camera the other way around the target.
+        let q = Quat::from_axis_angle(axis, -angle);
+        self.set_orientation(q.rotate(back), q.rotate(up));
+    }
+
+    /// Point the camera along `-back` with `up` as its up vector, as yaw/pitch/roll.
+    fn set_orientation(&mut self, back: Vec3, up: Vec3) {
+        let (side, world_up, front) = self.orbit_frame();
+        let yaw = back.dot(side).atan2(back.dot(front));
+        // Stay on the same turn as before so a later `update` doesn't spin around.
+        let turn = std::f32::consts::TAU;
+        self.yaw += (yaw - self.yaw + turn / 2.0).rem_euclid(turn) - turn / 2.0;
+        self.pitch = self.clamp_pitch(back.dot(world_up).clamp(-1.0, 1.0).asin());
+
+        let forward = back.mul(-1.0);
+        let right = forward.cross(world_up).normalize();
+        let level_up = right.cross(forward);
+        self.roll = up.dot(right).atan2(up.dot(level_up));
+        self.sync_goals();
+    }
+
+    /// Keep the zoom distance within `min..=max` and pull the camera into that range.
+    /// The next fit may widen the range again to suit the model. Errors unless
+    /// `0 < min < max`.
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) -> Result<(), String> {
+        if !(min > 0.0 && min < max && max.is_finite()) {
+            return Err(format!(
+                "zoom limits need 0 < min < max, got {min} and {max}"
+            ));
+        }
+        self.distance_min = min;
+        self.distance_max = max;
+        self.distance = self.distance.clamp(min, max);
+        self.goal_distance = self.goal_distance.clamp(min, max);
+        Ok(())
     }
 
     pub fn zoom(&mut self, factor: f32) {
         // factor > 1 zooms out, < 1 zooms in
-        self.distance = (self.distance * factor).clamp(0.05, 1.0e6);
+        self.goal_distance =
+            (self.goal_distance * factor).clamp(self.distance_min, self.distance_max);
+        if self.damping <= 0.0 {
+            self.settle();
+        }
+    }
+
+    /// Zoom by a signed scroll amount. Equal steps scale the distance by equal ratios
+    /// (`exp(-wheel_delta * sensitivity)`), so zooming feels as fast close up as far out
+    /// and opposite deltas cancel. Positive deltas zoom in; browsers report scrolling
+    /// down as positive `deltaY`, so pass `-deltaY` for the usual scroll-down-zooms-out.
+    pub fn zoom_by_delta(&mut self, wheel_delta: f32, sensitivity: f32) {
+        self.zoom(wheel_zoom_factor(wheel_delta, sensitivity));
+    }
+
+    /// Zoom so the point under `(ndc_x, ndc_y)` on the target plane stays put.
+    pub fn zoom_at(&mut self, factor: f32, ndc_x: f32, ndc_y: f32, aspect: f32) {
+        let old_distance = self.goal_distance;
+        self.zoom(factor);
+
+        // The cursor ray hits the target plane at `offset * distance` from the target;
+        // moving the target along that offset by the distance change keeps it fixed.
+        let tan_half_fovy = (self.fovy * 0.5).tan();
+        let (cam_right, cam_up) = self.view_basis();
+        let offset = cam_right
+            .mul(ndc_x * tan_half_fovy * aspect)
+            .add(cam_up.mul(ndc_y * tan_half_fovy));
+        self.target = self
+            .target
+            .add(offset.mul(old_distance - self.goal_distance));
     }
 
     pub fn pan(&mut self, right: f32, up: f32) {
         // Pan in view plane: move target by camera right/up vectors.
-        let eye = self.eye();
-        let forward = self.target.sub(eye).normalize();
-        let world_up = self.view_up();
-        let cam_right = forward.cross(world_up).normalize();
-        let cam_up = cam_right.cross(forward).normalize();
-
+        let (cam_right, cam_up) = self.view_basis();
         self.target = self.target.add(cam_right.mul(right)).add(cam_up.mul(up));
     }
 
+    /// Camera right and up vectors in world space.
+    fn view_basis(&self) -> (Vec3, Vec3) {
+        let forward = self.target.sub(self.eye()).normalize();
+        let cam_right = forward.cross(self.view_up()).normalize();
+        let cam_up = cam_right.cross(forward).normalize();
+        (cam_right, cam_up)
+    }
+
+    #[allow(dead_code)] // default-padding shorthand; the viewer always passes its padding
     pub fn fit_to_bounds(&mut self, bounds: Bounds, aspect: f32) {
-        self.target = bounds.center();
-        let r = bounds.radius().max(1e-4);
+        self.fit_to_bounds_with_padding(bounds, aspect, DEFAULT_FIT_PADDING);
+    }
+
+    /// Frame `bounds`; `padding` scales the fitted distance (1.0 = sphere touches the edges).
+    /// Flat sides are padded to `MIN_FIT_EXTENT` of the longest (1 mm for a single point).
+    pub fn fit_to_bounds_with_padding(&mut self, bounds: Bounds, aspect: f32, padding: f32) {
+        let size = bounds.max.sub(bounds.min);
+        let longest = size.x.max(size.y).max(size.z);
+        let bounds = bounds.padded((longest * MIN_FIT_EXTENT).max(1e-3));
+        self.fit_to_sphere(bounds.center(), bounds.radius(), aspect, padding);
+    }
+
+    /// Frame a bounding sphere; tighter than `fit_to_bounds` for round shapes.
+    pub fn fit_to_sphere(&mut self, center: Vec3, radius: f32, aspect: f32, padding: f32) {
+        self.target = center;
+        let r = radius.max(1e-4);
 
         // Distance so that bounding sphere fits vertically; adjust for aspect.
         let tan_half_fovy = (self.fovy * 0.5).tan();
@@ -103,10 +684,509 @@ impl Camera {
             dist = dist.max(dist_x);
         }
 
-        // Add a little padding.
-        self.distance = dist * 1.15;
-        self.znear = (self.distance - r * 2.5).max(0.001);
-        self.zfar = (self.distance + r * 2.5).max(self.znear + 1.0);
+        self.distance = dist * padding.max(1e-3);
+        // Tiny and huge models need room beyond the default limits.
+        self.distance_min = self.distance_min.min(self.distance / FIT_ZOOM_RANGE);
+        self.distance_max = self.distance_max.max(self.distance * FIT_ZOOM_RANGE);
+        self.framed_radius = Some(r);
+        self.refresh_auto_clip();
+        self.sync_goals();
+    }
+
+    /// With `clip_auto`, put the clip planes around the last framed sphere at the current
+    /// distance, so the model stays between them after the camera is placed directly.
+    fn refresh_auto_clip(&mut self) {
+        if let (true, Some(r)) = (self.clip_auto, self.framed_radius) {
+            self.znear = (self.distance - r * 2.5).max(0.001);
+            self.zfar = (self.distance + r * 2.5).max(self.znear + 1.0);
+        }
+    }
+}
+
+/// `zoom` factor for a scroll of `wheel_delta`; see `Camera::zoom_by_delta`.
+pub(crate) fn wheel_zoom_factor(wheel_delta: f32, sensitivity: f32) -> f32 {
+    let factor = (-wheel_delta * sensitivity).exp();
+    if factor.is_finite() && factor > 0.0 {
+        factor
+    } else {
+        1.0
+    }
+}
+
+/// Shoemake's arcball: lift a point (unit circle = viewport's inscribed circle, y up)
+/// onto the unit hemisphere facing the viewer. Points outside the circle land on its rim.
+pub(crate) fn arcball_point(x: f32, y: f32) -> Vec3 {
+    let d2 = x * x + y * y;
+    if d2 <= 1.0 {
+        Vec3::new(x, y, (1.0 - d2).sqrt())
+    } else {
+        let d = d2.sqrt();
+        Vec3::new(x / d, y / d, 0.0)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec3_approx(a: Vec3, b: Vec3, eps: f32) {
+        assert!(
+            (a.x - b.x).abs() <= eps && (a.y - b.y).abs() <= eps && (a.z - b.z).abs() <= eps,
+            "Vec3 mismatch: left={a:?} right={b:?} eps={eps}"
+        );
+    }
+
+    #[test]
+    fn frustum_keeps_visible_boxes_and_culls_behind_camera() {
+        let mut cam = Camera::new();
+        cam.distance = 5.0;
+        cam.sync_goals();
+        let unit = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        // The camera sits on +Z looking at the origin, so +Z beyond the eye is behind it.
+        let behind = Bounds::new(Vec3::new(-1.0, -1.0, 20.0), Vec3::new(1.0, 1.0, 22.0));
+        let beside = Bounds::new(Vec3::new(50.0, -1.0, -1.0), Vec3::new(52.0, 1.0, 1.0));
+        let beyond_far = Bounds::new(Vec3::new(-1.0, -1.0, -2000.0), Vec3::new(1.0, 1.0, -1999.0));
+        for mode in [ProjectionMode::Perspective, ProjectionMode::Orthographic] {
+            cam.projection_mode = mode;
+            let planes = cam.frustum_planes(1.5, 2.0);
+            assert!(unit.intersects_frustum(&planes), "{mode:?}");
+            assert!(!behind.intersects_frustum(&planes), "{mode:?}");
+            assert!(!beside.intersects_frustum(&planes), "{mode:?}");
+            assert!(!beyond_far.intersects_frustum(&planes), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn arcball_lifts_points_onto_the_sphere() {
+        assert_vec3_approx(arcball_point(0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let inside = arcball_point(0.3, -0.4);
+        assert_vec3_approx(inside, Vec3::new(0.3, -0.4, 0.75_f32.sqrt()), 1e-6);
+        assert!((inside.length() - 1.0).abs() < 1e-6);
+        // Outside the circle: projected onto the rim, same direction, z = 0.
+        assert_vec3_approx(arcball_point(3.0, 4.0), Vec3::new(0.6, 0.8, 0.0), 1e-6);
+        assert_vec3_approx(arcball_point(1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn horizontal_arcball_drag_turns_around_up() {
+        let mut cam = Camera::new();
+        cam.arcball_rotate((0.0, 0.0), (0.5, 0.0));
+        let angle = 0.5_f32.asin();
+        // Dragging right swings the camera left, so the front follows the cursor.
+        assert!((cam.yaw + angle).abs() < 1e-5, "yaw {}", cam.yaw);
+        assert!(cam.pitch.abs() < 1e-5);
+        assert!(cam.roll.abs() < 1e-5);
+
+        cam.arcball_rotate((0.0, 0.0), (0.0, 0.0));
+        assert!((cam.yaw + angle).abs() < 1e-5);
+    }
+
+    #[test]
+    fn manual_clip_survives_refit() {
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let mut cam = Camera::new();
+        assert!(cam.set_clip(0.0, 10.0).is_err());
+        assert!(cam.set_clip(5.0, 5.0).is_err());
+        assert!(cam.set_clip(f32::NAN, 10.0).is_err());
+        assert!(cam.clip_auto);
+
+        cam.set_clip(0.5, 5000.0).unwrap();
+        cam.fit_to_bounds(bounds, 1.0);
+        assert_eq!((cam.znear, cam.zfar), (0.5, 5000.0));
+
+        cam.clip_auto = true;
+        cam.fit_to_bounds(bounds, 1.0);
+        assert!(cam.zfar < 5000.0);
+    }
+
+    #[test]
+    fn set_fov_drives_projection_focal_term() {
+        let mut cam = Camera::new();
+        let aspect = 1.5;
+        for (degrees, expected) in [(60.0, 60.0), (90.0, 90.0), (5.0, 10.0), (170.0, 120.0)] {
+            cam.set_fov(degrees);
+            let f = 1.0 / (expected as f32 * 0.5).to_radians().tan();
+            let proj = cam.projection_matrix(aspect, 1.0);
+            assert!((proj.m[0] - f / aspect).abs() <= 1e-5, "fov {degrees}");
+            assert!((proj.m[5] - f).abs() <= 1e-5);
+        }
+        cam.set_fov(f32::NAN);
+        assert!((cam.fovy - 120_f32.to_radians()).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn smaller_padding_moves_closer() {
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let r = bounds.radius();
+        let mut loose = Camera::new();
+        loose.fit_to_bounds(bounds, 1.0);
+        let mut tight = Camera::new();
+        tight.fit_to_bounds_with_padding(bounds, 1.0, 0.9);
+
+        assert!(tight.distance < loose.distance);
+        let mut same = Camera::new();
+        same.fit_to_bounds_with_padding(bounds, 1.0, 1.15);
+        assert_eq!(same.distance, loose.distance);
+
+        for cam in [loose, tight] {
+            assert!(cam.znear > 0.0 && cam.znear < cam.distance - r);
+            assert!(cam.zfar > cam.distance + r);
+        }
+    }
+
+    #[test]
+    fn damped_update_moves_partway() {
+        let mut cam = Camera::new();
+        cam.damping = 0.8;
+        cam.orbit(1.0, 0.5);
+        cam.zoom(2.0);
+        // Goals are set but nothing moves until update.
+        assert_eq!(cam.yaw, 0.0);
+        assert_eq!(cam.distance, 2.0);
+
+        cam.update(1.0 / 60.0);
+        assert!(cam.yaw > 0.0 && cam.yaw < 1.0, "yaw={}", cam.yaw);
+        assert!(cam.pitch > 0.0 && cam.pitch < 0.5, "pitch={}", cam.pitch);
+        assert!(
+            cam.distance > 2.0 && cam.distance < 4.0,
+            "distance={}",
+            cam.distance
+        );
+
+        for _ in 0..600 {
+            cam.update(1.0 / 60.0);
+        }
+        assert!((cam.yaw - 1.0).abs() <= 1e-4);
+        assert!((cam.distance - 4.0).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn top_preset_looks_down_on_the_center() {
+        let bounds = Bounds::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(3.0, 4.0, 5.0));
+        let mut cam = Camera::new();
+        cam.orbit(0.7, 0.3);
+        cam.roll(0.2);
+        let top = cam.preset_direction(ViewPreset::from_str("top").unwrap());
+        cam.view_from_direction(top);
+        cam.fit_to_bounds(bounds, 1.5);
+
+        assert_eq!(cam.pitch, DEFAULT_PITCH_LIMIT);
+        assert_eq!(cam.roll, 0.0);
+        let center = bounds.center();
+        let offset = cam.eye().sub(center);
+        assert_vec3_approx(cam.target, center, 1e-6);
+        assert!(offset.normalize().y > 0.99, "eye {:?}", cam.eye());
+
+        let front = cam.preset_direction(ViewPreset::Front);
+        cam.view_from_direction(front);
+        assert!(cam.yaw.abs() < 1e-6 && cam.pitch.abs() < 1e-6);
+        let iso = cam.preset_direction(ViewPreset::Iso);
+        cam.view_from_direction(iso);
+        assert!((cam.pitch - (1.0 / 3.0_f32.sqrt()).asin()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orbit_pitch_clamps_to_limits() {
+        let mut cam = Camera::new();
+        cam.orbit(0.0, 10.0);
+        assert_eq!(cam.pitch, 1.54);
+        cam.orbit(0.0, -20.0);
+        assert_eq!(cam.pitch, -1.54);
+
+        cam.set_pitch_limits(-0.2, 0.6).unwrap();
+        // The current pitch is pulled into the new range right away.
+        assert_eq!(cam.pitch, -0.2);
+        cam.orbit(0.0, 5.0);
+        assert_eq!(cam.pitch, 0.6);
+
+        cam.set_pitch_limits(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2)
+            .unwrap();
+        cam.orbit(0.0, 5.0);
+        assert_eq!(cam.pitch, MAX_PITCH);
+        assert!(cam.view_matrix().m.iter().all(|v| v.is_finite()));
+
+        assert!(cam.set_pitch_limits(0.5, 0.5).is_err());
+        assert!(cam.set_pitch_limits(0.5, -0.5).is_err());
+        assert!(cam.set_pitch_limits(f32::NAN, 0.5).is_err());
+    }
+
+    #[test]
+    fn zoom_respects_limits_and_fits_widen_them() {
+        let mut cam = Camera::new();
+        cam.set_zoom_limits(1.0, 3.0).unwrap();
+        cam.zoom(10.0);
+        assert_eq!(cam.distance, 3.0);
+        cam.zoom(0.01);
+        assert_eq!(cam.distance, 1.0);
+        assert!(cam.set_zoom_limits(0.0, 1.0).is_err());
+        assert!(cam.set_zoom_limits(2.0, 1.0).is_err());
+
+        // A 1 mm part: the default 0.05 floor would stop well short of it.
+        let tiny = Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1e-3, 1e-3, 1e-3));
+        let mut cam = Camera::new();
+        cam.fit_to_bounds(tiny, 1.0);
+        let fitted = cam.distance;
+        cam.zoom(0.1);
+        assert!((cam.distance - fitted * 0.1).abs() <= 1e-9);
+
+        // A city-sized scene can still be backed away from.
+        let huge = Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1e6, 1e6, 1e6));
+        cam.fit_to_bounds(huge, 1.0);
+        let fitted = cam.distance;
+        cam.zoom(10.0);
+        assert_eq!(cam.distance, fitted * 10.0);
+    }
+
+    #[test]
+    fn wheel_deltas_zoom_symmetrically() {
+        let mut cam = Camera::new();
+        let start = cam.distance;
+        cam.zoom_by_delta(100.0, WHEEL_ZOOM_SENSITIVITY);
+        let zoomed_in = cam.distance;
+        assert!(zoomed_in < start);
+        cam.zoom_by_delta(-100.0, WHEEL_ZOOM_SENSITIVITY);
+        assert!((cam.distance - start).abs() <= 1e-6);
+        cam.zoom_by_delta(-100.0, WHEEL_ZOOM_SENSITIVITY);
+        // Out by the same ratio as in.
+        assert!((cam.distance / start - start / zoomed_in).abs() <= 1e-5);
+        assert_eq!(wheel_zoom_factor(f32::NAN, 1.0), 1.0);
+    }
+
+    #[test]
+    fn flat_and_point_bounds_still_frame() {
+        let plane = crate::geometry::plane(2.0, 2.0, 1);
+        let mut cam = Camera::new();
+        cam.fit_to_bounds(plane.bounds, 1.0);
+        assert!(cam.distance > 0.0);
+        assert!(cam.znear > 0.001 && cam.znear < cam.distance);
+
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        cam.fit_to_bounds(Bounds::new(origin, origin), 1.0);
+        assert!(cam.distance > 0.0 && cam.zfar > cam.znear);
+        assert!(cam.znear >= 0.001);
+
+        let padded = plane.bounds.padded(0.5);
+        assert_eq!((padded.min.y, padded.max.y), (-0.25, 0.25));
+        assert_eq!((padded.min.x, padded.max.x), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn turntable_orbit_ignores_pitch() {
+        let mut cam = Camera::new();
+        cam.orbit(0.0, 0.3);
+        cam.orbit_mode = OrbitMode::Turntable;
+        cam.orbit(0.5, -0.8);
+        assert!((cam.yaw - 0.5).abs() <= 1e-6);
+        assert_eq!(cam.pitch, 0.3);
+
+        cam.orbit_mode = OrbitMode::Free;
+        cam.orbit(0.0, -0.8);
+        assert!((cam.pitch + 0.5).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn zero_damping_is_instant() {
+        let mut cam = Camera::new();
+        cam.orbit(1.0, 0.5);
+        cam.zoom(2.0);
+        assert_eq!(cam.yaw, 1.0);
+        assert_eq!(cam.pitch, 0.5);
+        assert_eq!(cam.distance, 4.0);
+    }
+
+    #[test]
+    fn state_round_trip_is_noop() {
+        let mut cam = Camera::new();
+        cam.orbit(0.8, -0.6);
+        cam.pan(0.25, 0.5);
+        cam.roll(0.1);
+        cam.zoom(3.0);
+
+        let saved = cam.state();
+        cam.set_state(saved).unwrap();
+        assert_eq!(cam.state(), saved);
+
+        let mut other = Camera::new();
+        other.set_state(saved).unwrap();
+        assert_eq!(other.state(), saved);
+        assert_vec3_approx(other.eye(), cam.eye(), 0.0);
+    }
+
+    #[test]
+    fn set_state_rejects_broken_snapshots_and_clamps_the_rest() {
+        let mut cam = Camera::new();
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        cam.fit_to_bounds(bounds, 1.0);
+        let before = cam.state();
+        for broken in [
+            CameraState {
+                distance: f32::NAN,
+                ..before
+            },
+            CameraState {
+                distance: 0.0,
+                ..before
+            },
+            CameraState {
+                target: [0.0, f32::INFINITY, 0.0],
+                ..before
+            },
+            CameraState {
+                pitch: f32::NAN,
+                ..before
+            },
+        ] {
+            assert!(cam.set_state(broken).is_err(), "{broken:?}");
+            assert_eq!(cam.state(), before);
+        }
+
+        let far = before.distance * 10.0;
+        cam.set_state(CameraState {
+            pitch: 3.0,
+            distance: far,
+            ..before
+        })
+        .unwrap();
+        assert_eq!(cam.pitch, DEFAULT_PITCH_LIMIT);
+        // The clip planes follow the new distance so the model isn't cut away.
+        assert!(cam.znear < far - bounds.radius() && cam.zfar > far + bounds.radius());
+    }
+
+    #[test]
+    fn look_at_reproduces_the_eye() {
+        let mut cam = Camera::new();
+        let eye = Vec3::new(3.0, 2.0, -4.0);
+        let target = Vec3::new(0.5, -1.0, 1.0);
+        cam.set_look_at(eye, target).unwrap();
+        assert_vec3_approx(cam.eye(), eye, 1e-4);
+        assert_vec3_approx(cam.target, target, 0.0);
+
+        // Orbiting carries on from the placed view without a jump.
+        cam.orbit(0.0, 0.0);
+        assert_vec3_approx(cam.eye(), eye, 1e-4);
+
+        cam.world_up = Vec3::new(0.0, 0.0, 1.0);
+        cam.set_look_at(eye, target).unwrap();
+        assert_vec3_approx(cam.eye(), eye, 1e-4);
+    }
+
+    #[test]
+    fn look_at_rejects_degenerate_points_and_refits_the_clip_planes() {
+        let mut cam = Camera::new();
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        cam.fit_to_bounds(bounds, 1.0);
+        let before = cam.state();
+        let nan = Vec3::new(f32::NAN, 0.0, 0.0);
+        assert!(cam.set_look_at(nan, Vec3::new(0.0, 0.0, 0.0)).is_err());
+        assert!(cam
+            .set_look_at(Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 2.0, 3.0))
+            .is_err());
+        assert_eq!(cam.state(), before);
+
+        // A far eye moves the clip planes out with it instead of cutting the model away.
+        let far = Vec3::new(0.0, 0.0, 500.0);
+        cam.set_look_at(far, Vec3::new(0.0, 0.0, 0.0)).unwrap();
+        assert!(cam.znear < 500.0 - bounds.radius() && cam.zfar > 500.0 + bounds.radius());
+    }
+
+    #[test]
+    fn reset_then_fit_returns_home() {
+        let bounds = Bounds::new(Vec3::new(1.0, 0.0, -2.0), Vec3::new(3.0, 2.0, 0.0));
+        let mut cam = Camera::new();
+        cam.fit_to_bounds(bounds, 1.0);
+        let fitted_distance = cam.distance;
+
+        cam.orbit(1.2, 0.4);
+        cam.pan(0.5, -0.3);
+        cam.roll(0.2);
+        cam.reset();
+        cam.fit_to_bounds(bounds, 1.0);
+
+        assert_eq!(cam.yaw, 0.0);
+        assert_eq!(cam.pitch, 0.0);
+        assert_eq!(cam.roll, 0.0);
+        assert_vec3_approx(cam.target, bounds.center(), 1e-6);
+        assert_eq!(cam.distance, fitted_distance);
+    }
+
+    #[test]
+    fn zoom_at_center_matches_zoom() {
+        let mut a = Camera::new();
+        a.orbit(0.5, -0.2);
+        a.target = Vec3::new(1.0, 2.0, 3.0);
+        let mut b = a;
+
+        a.zoom(0.5);
+        b.zoom_at(0.5, 0.0, 0.0, 1.5);
+        assert_vec3_approx(a.target, b.target, 1e-6);
+        assert_eq!(a.distance, b.distance);
+    }
+
+    #[test]
+    fn zoom_at_keeps_cursor_point_fixed() {
+        let mut cam = Camera::new();
+        cam.orbit(0.3, 0.1);
+        let aspect = 1.5;
+        let (ndc_x, ndc_y) = (0.6, -0.4);
+        let tan_half = (cam.fovy * 0.5).tan();
+
+        let world_under_cursor = |c: &Camera| {
+            let (right, up) = c.view_basis();
+            c.target
+                .add(right.mul(ndc_x * tan_half * aspect * c.distance))
+                .add(up.mul(ndc_y * tan_half * c.distance))
+        };
+
+        let before = world_under_cursor(&cam);
+        cam.zoom_at(0.5, ndc_x, ndc_y, aspect);
+        // The old point now sits on the new cursor ray (the target plane moved along it).
+        let after_eye = cam.eye();
+        let ray = world_under_cursor(&cam).sub(after_eye).normalize();
+        let to_point = before.sub(after_eye).normalize();
+        assert_vec3_approx(ray, to_point, 1e-5);
+    }
+
+    #[test]
+    fn zero_roll_keeps_world_up() {
+        let mut cam = Camera::new();
+        cam.orbit(0.7, 0.3);
+        assert_vec3_approx(cam.view_up(), Vec3::new(0.0, 1.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn z_up_framing_shows_the_top_face_up() {
+        let cube = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+        let mut cam = Camera::new();
+        cam.world_up = Vec3::new(0.0, 0.0, 1.0);
+        cam.fit_to_bounds(cube, 1.0);
+        cam.orbit(0.6, 0.4);
+        assert_vec3_approx(cam.view_up(), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        // The +Z face normal points up the screen and, pitched down at it, toward the eye.
+        let [_, y, z, _] = cam.view_matrix().mul_vec4([0.0, 0.0, 1.0, 0.0]);
+        assert!((y - 0.4_f32.cos()).abs() <= 1e-5);
+        assert!((z - 0.4_f32.sin()).abs() <= 1e-5);
+
+        // Yaw turns around +Z, keeping the eye's height; the home view looks along +Y.
+        let height = cam.eye().z;
+        cam.orbit(1.3, 0.0);
+        assert!((cam.eye().z - height).abs() <= 1e-5);
+        cam.orbit(-1.9, -0.4);
+        let forward = cam.target.sub(cam.eye()).normalize();
+        assert_vec3_approx(forward, Vec3::new(0.0, 1.0, 0.0), 1e-5);
+    }
+
+    #[test]
+    fn half_turn_roll_flips_up() {
+        let mut cam = Camera::new();
+        cam.roll(std::f32::consts::PI);
+        assert_vec3_approx(cam.view_up(), Vec3::new(0.0, -1.0, 0.0), 1e-5);
+
+        // A quarter turn keeps up perpendicular to the view direction.
+        cam.roll = std::f32::consts::FRAC_PI_2;
+        cam.orbit(0.4, 0.2);
+        let forward = cam.target.sub(cam.eye()).normalize();
+        assert!(cam.view_up().dot(forward).abs() <= 1e-5);
+        assert!((cam.view_up().length() - 1.0).abs() <= 1e-5);
+    }
+}
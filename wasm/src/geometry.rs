@@ -1,13 +1,376 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+use serde::Serialize;
+
 use crate::camera::Bounds;
 use crate::math::Vec3;
 
+mod glb;
+mod obj;
+mod ply;
+mod stl;
+
+pub(crate) use glb::parse_glb;
+pub(crate) use obj::parse_obj;
+pub(crate) use ply::parse_ply;
+pub(crate) use stl::parse_stl_binary;
+
 /// Simple mesh: positions (x,y,z) and optional triangle indices.
 #[derive(Debug, Clone)]
 pub(crate) struct Mesh {
     pub positions: Vec<f32>,
     pub normals: Vec<f32>,
-    pub indices: Vec<u16>,
+    /// Texture coordinates (u,v) per vertex.
+    pub uvs: Vec<f32>,
+    /// Tangent (x,y,z) plus handedness w per vertex, for normal mapping.
+    pub tangents: Vec<f32>,
+    /// Optional RGB color per vertex, multiplied into the base color. Empty when absent.
+    pub colors: Vec<f32>,
+    pub indices: IndexBuffer,
+    /// The same triangles as one `TRIANGLE_STRIP` (see `to_triangle_strip`), for grid
+    /// primitives whose rows strip naturally; empty otherwise. Anything that reorders the
+    /// triangles must clear it.
+    pub strip: Vec<u16>,
     pub bounds: Bounds,
+    pub topology: Topology,
+}
+
+/// What the vertices describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Topology {
+    /// Triangles, indexed or (with empty `indices`) as a flat list.
+    Triangles,
+    /// Unconnected points drawn with `POINTS` (e.g. scanned point clouds).
+    Points,
+    /// Line segments, two consecutive vertices each (grids, gizmos). Drawn unlit.
+    Lines,
+    /// One connected polyline through the vertices in order (`LINE_STRIP`).
+    LineStrip,
+}
+
+/// Size of a mesh as uploaded, from `Mesh::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct MeshStats {
+    pub vertices: u32,
+    /// 0 for point clouds and lines.
+    pub triangles: u32,
+    /// Vertex attributes, indices and the triangle strip, as the GPU buffers hold them.
+    pub bytes: u64,
+}
+
+/// How vertex normals are derived when building a mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum NormalMode {
+    /// Average adjacent face normals per shared vertex.
+    Smooth,
+    /// One normal per triangle; vertices are split so faces stay faceted.
+    Flat,
+    /// Smooth across edges whose faces meet at less than this angle (radians), hard
+    /// above it. See `compute_normals_with_crease`.
+    Crease(f32),
+}
+
+impl NormalMode {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "smooth" => Some(Self::Smooth),
+            "flat" => Some(Self::Flat),
+            _ => None,
+        }
+    }
+}
+
+/// Which way a mesh's triangles turn, seen from outside; see `detect_winding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Winding {
+    /// Counter-clockwise from outside, as the renderer culls for.
+    CounterClockwise,
+    /// Clockwise from outside: the mesh is inside-out.
+    Clockwise,
+    /// The triangles enclose no clear volume: flat, or mixed so the turns cancel out.
+    Ambiguous,
+}
+
+/// How `Mesh::with_generated_uvs` lays texture coordinates onto a mesh that has none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UvProjection {
+    /// One projection along the thinnest side of the bounds, like a decal.
+    Planar,
+    /// Six planar projections, picked per triangle (see `box_project_uvs`).
+    Box,
+}
+
+impl UvProjection {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "planar" => Some(Self::Planar),
+            "box" => Some(Self::Box),
+            _ => None,
+        }
+    }
+}
+
+impl Mesh {
+    /// Return this mesh shaded with `mode`.
+    /// Flat de-indexes so every triangle owns three vertices sharing its face normal.
+    pub(crate) fn with_normal_mode(&self, mode: NormalMode) -> Mesh {
+        match (mode, self.topology) {
+            (NormalMode::Flat, Topology::Triangles) => flat_shaded(self),
+            (NormalMode::Crease(angle), Topology::Triangles) => crease_shaded(self, angle),
+            _ => self.clone(),
+        }
+    }
+
+    /// Triangle corner indices as `usize`; a mesh without indices is read as a triangle list.
+    /// Point clouds and line lists have no triangles.
+    pub(crate) fn corner_indices(&self) -> Vec<usize> {
+        if self.topology != Topology::Triangles {
+            return Vec::new();
+        }
+        let corners: Vec<usize> = match &self.indices {
+            IndexBuffer::U16(ix) => ix.iter().map(|&i| i as usize).collect(),
+            IndexBuffer::U32(ix) => ix.iter().map(|&i| i as usize).collect(),
+        };
+        if corners.is_empty() {
+            (0..self.positions.len() / 3).collect()
+        } else {
+            corners
+        }
+    }
+
+    /// Whether any texture coordinate is set; loaders fill in zeros when a file has none.
+    pub(crate) fn has_uvs(&self) -> bool {
+        self.uvs.iter().any(|&c| c != 0.0)
+    }
+
+    /// Return this mesh with texture coordinates from `projection`, replacing any it had.
+    /// Box projection splits the vertices whose triangles land on different sides.
+    pub(crate) fn with_generated_uvs(&self, projection: UvProjection) -> Mesh {
+        if self.topology != Topology::Triangles {
+            return self.clone();
+        }
+        if projection == UvProjection::Planar {
+            let size = self.bounds.max.sub(self.bounds.min);
+            let sides = [size.x, size.y, size.z];
+            let axis = (0..3)
+                .min_by(|&a, &b| sides[a].total_cmp(&sides[b]))
+                .unwrap();
+            let mut mesh = self.clone();
+            mesh.uvs = self
+                .positions
+                .chunks_exact(3)
+                .flat_map(|p| project_uv(&self.bounds, p, axis, true))
+                .collect();
+            mesh.recompute_tangents();
+            return mesh;
+        }
+
+        let corners = self.corner_indices();
+        let gather = |data: &[f32]| -> Vec<f32> {
+            corners
+                .iter()
+                .flat_map(|&i| data[i * 3..i * 3 + 3].iter().copied())
+                .collect()
+        };
+        let uvs = box_project_uvs(&gather(&self.positions), &gather(&self.normals));
+
+        // Corners of one vertex that project to the same UV keep sharing a vertex.
+        let mut split: HashMap<(usize, [u32; 2]), u32> = HashMap::new();
+        let mut sources = Vec::new();
+        let mut out_uvs = Vec::new();
+        let mut indices = Vec::with_capacity(corners.len());
+        for (&i, uv) in corners.iter().zip(uvs.chunks_exact(2)) {
+            let index = *split
+                .entry((i, [uv[0].to_bits(), uv[1].to_bits()]))
+                .or_insert_with(|| {
+                    sources.push(i);
+                    out_uvs.extend_from_slice(uv);
+                    (sources.len() - 1) as u32
+                });
+            indices.push(index);
+        }
+
+        let (mut positions, mut normals, mut colors) = (Vec::new(), Vec::new(), Vec::new());
+        for &i in &sources {
+            positions.extend_from_slice(&self.positions[i * 3..i * 3 + 3]);
+            normals.extend_from_slice(&self.normals[i * 3..i * 3 + 3]);
+            if !self.colors.is_empty() {
+                colors.extend_from_slice(&self.colors[i * 3..i * 3 + 3]);
+            }
+        }
+        let tangents = compute_tangents(&positions, &normals, &out_uvs, &indices);
+        Mesh {
+            positions,
+            normals,
+            uvs: out_uvs,
+            tangents,
+            colors,
+            indices: IndexBuffer::from_u32(indices, sources.len()),
+            strip: Vec::new(),
+            bounds: self.bounds,
+            topology: Topology::Triangles,
+        }
+    }
+
+    /// Reverse every triangle so front and back faces swap; normals follow on recompute.
+    pub(crate) fn flip_winding(&mut self) {
+        if self.topology != Topology::Triangles {
+            return;
+        }
+        if self.indices.is_empty() {
+            let sequential = (0..(self.positions.len() / 3) as u32).collect();
+            self.indices = IndexBuffer::from_u32(sequential, self.positions.len() / 3);
+        }
+        match &mut self.indices {
+            IndexBuffer::U16(ix) => flip_winding(ix),
+            IndexBuffer::U32(ix) => flip_winding(ix),
+        }
+        self.strip.clear();
+    }
+
+    /// Flip triangles that disagree with their neighbours (see `make_consistent_winding`),
+    /// then turn the whole mesh over if it comes out inside-out. Rebuilds the normals when
+    /// anything flipped. Meshes without indices share no edges and are left alone.
+    pub(crate) fn fix_winding(&mut self) {
+        if self.topology != Topology::Triangles || self.indices.is_empty() {
+            return;
+        }
+        let (flipped, winding) = match &mut self.indices {
+            IndexBuffer::U16(ix) => (
+                make_consistent_winding(ix, &self.positions),
+                detect_winding(&self.positions, ix),
+            ),
+            IndexBuffer::U32(ix) => (
+                make_consistent_winding(ix, &self.positions),
+                detect_winding(&self.positions, ix),
+            ),
+        };
+        if winding == Winding::Clockwise {
+            self.flip_winding();
+        } else if flipped == 0 {
+            return;
+        }
+        self.strip.clear();
+        self.recompute_normals();
+    }
+
+    /// Vertex, triangle and byte counts of the buffers this mesh uploads.
+    pub(crate) fn stats(&self) -> MeshStats {
+        let vertices = self.positions.len() / 3;
+        let triangles = match self.topology {
+            Topology::Triangles if self.indices.is_empty() => vertices / 3,
+            Topology::Triangles => self.indices.len() / 3,
+            _ => 0,
+        };
+        let floats = [
+            &self.positions,
+            &self.normals,
+            &self.uvs,
+            &self.tangents,
+            &self.colors,
+        ]
+        .iter()
+        .map(|v| v.len())
+        .sum::<usize>();
+        let index_bytes = match &self.indices {
+            IndexBuffer::U16(ix) => ix.len() * 2,
+            IndexBuffer::U32(ix) => ix.len() * 4,
+        };
+        MeshStats {
+            vertices: vertices as u32,
+            triangles: triangles as u32,
+            bytes: (floats * 4 + index_bytes + self.strip.len() * 2) as u64,
+        }
+    }
+
+    /// Rebuild smooth normals and tangents from the current positions and winding.
+    pub(crate) fn recompute_normals(&mut self) {
+        self.normals = match &self.indices {
+            IndexBuffer::U16(ix) => compute_normals(&self.positions, ix),
+            IndexBuffer::U32(ix) => compute_normals(&self.positions, ix),
+        };
+        self.recompute_tangents();
+    }
+
+    /// Rebuild tangents from the current normals and UVs.
+    pub(crate) fn recompute_tangents(&mut self) {
+        self.tangents = match &self.indices {
+            IndexBuffer::U16(ix) => compute_tangents(&self.positions, &self.normals, &self.uvs, ix),
+            IndexBuffer::U32(ix) => compute_tangents(&self.positions, &self.normals, &self.uvs, ix),
+        };
+    }
+
+    /// Attach per-vertex RGB colors (components in [0,1]); an empty list removes them.
+    pub(crate) fn set_colors(&mut self, colors: Vec<f32>) -> Result<(), String> {
+        if !colors.is_empty() && colors.len() != self.positions.len() {
+            return Err(format!(
+                "expected {} color components, got {}",
+                self.positions.len(),
+                colors.len()
+            ));
+        }
+        self.colors = colors;
+        Ok(())
+    }
+
+    /// Vertex pairs joined by a line: consecutive pairs for `Lines`, each vertex and the
+    /// next for `LineStrip`. Other topologies have no line segments.
+    pub(crate) fn segments(&self) -> Vec<[usize; 2]> {
+        let vertex_count = self.positions.len() / 3;
+        match self.topology {
+            Topology::Lines => (0..vertex_count / 2).map(|i| [i * 2, i * 2 + 1]).collect(),
+            Topology::LineStrip => (1..vertex_count).map(|i| [i - 1, i]).collect(),
+            Topology::Triangles | Topology::Points => Vec::new(),
+        }
+    }
+
+    /// Position of vertex `i`.
+    pub(crate) fn position(&self, i: usize) -> Vec3 {
+        let b = i * 3;
+        Vec3::new(
+            self.positions[b],
+            self.positions[b + 1],
+            self.positions[b + 2],
+        )
+    }
+}
+
+/// Triangle indices, 16-bit when every vertex is addressable that way.
+/// WebGL1 needs `OES_element_index_uint` to draw the 32-bit variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum IndexBuffer {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl IndexBuffer {
+    /// Pick the narrowest index type able to address `vertex_count` vertices.
+    /// An empty list (non-indexed drawing) is always 16-bit since nothing is uploaded.
+    pub(crate) fn from_u32(indices: Vec<u32>, vertex_count: usize) -> Self {
+        if indices.is_empty() || vertex_count <= u16::MAX as usize + 1 {
+            Self::U16(indices.into_iter().map(|i| i as u16).collect())
+        } else {
+            Self::U32(indices)
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::U16(v) => v.len(),
+            Self::U32(v) => v.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<u16>> for IndexBuffer {
+    fn from(indices: Vec<u16>) -> Self {
+        Self::U16(indices)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -15,8 +378,16 @@ pub(crate) enum Primitive {
     Triangle,
     Cube,
     Cylinder,
+    /// Cylinder without caps.
+    Tube,
     Sphere,
     Torus,
+    Plane,
+    Cone,
+    Capsule,
+    Icosphere,
+    Klein,
+    Mobius,
 }
 
 impl Primitive {
@@ -25,8 +396,15 @@ impl Primitive {
             "triangle" => Some(Self::Triangle),
             "cube" => Some(Self::Cube),
             "cylinder" => Some(Self::Cylinder),
+            "tube" => Some(Self::Tube),
             "sphere" => Some(Self::Sphere),
             "torus" => Some(Self::Torus),
+            "plane" => Some(Self::Plane),
+            "cone" => Some(Self::Cone),
+            "capsule" => Some(Self::Capsule),
+            "icosphere" => Some(Self::Icosphere),
+            "klein" => Some(Self::Klein),
+            "mobius" => Some(Self::Mobius),
             _ => None,
         }
     }
@@ -37,107 +415,248 @@ pub(crate) fn make_primitive(p: Primitive) -> Mesh {
         Primitive::Triangle => triangle(),
         Primitive::Cube => cube(),
         Primitive::Cylinder => cylinder(0.5, 1.0, 32),
+        Primitive::Tube => cylinder_with_caps(0.5, 1.0, 32, false),
         Primitive::Sphere => sphere(0.5, 32, 16),
         Primitive::Torus => torus(0.6, 0.2, 32, 16),
+        Primitive::Plane => plane(2.0, 2.0, 8),
+        Primitive::Cone => cone(0.5, 1.0, 32),
+        Primitive::Capsule => capsule(0.3, 0.6, 32, 8),
+        Primitive::Icosphere => icosphere(0.5, 3),
+        Primitive::Klein => klein_bottle(96, 48),
+        Primitive::Mobius => mobius_strip(96, 8),
+    }
+}
+
+/// Largest per-axis segment count of a grid-built mesh: 256 x 256 vertices is as many as
+/// u16 indices address.
+const MAX_GRID_SEGMENTS: u32 = 255;
+
+/// Like `make_primitive` with caller-chosen segment counts for the round primitives:
+/// around the axis (`major`) and along it or around the tube (`minor`). The cylinder and
+/// tube only use `major`. Counts are clamped to each shape's minimum and to
+/// `MAX_GRID_SEGMENTS`. Other primitives take `major` as their `make_primitive_with_detail`
+/// level.
+pub(crate) fn make_primitive_detailed(p: Primitive, major: u32, minor: u32) -> Mesh {
+    let major = major.min(MAX_GRID_SEGMENTS);
+    let minor = minor.min(MAX_GRID_SEGMENTS);
+    match p {
+        Primitive::Cylinder => cylinder(0.5, 1.0, major),
+        Primitive::Tube => cylinder_with_caps(0.5, 1.0, major, false),
+        Primitive::Sphere => sphere(0.5, major, minor),
+        Primitive::Torus => torus(0.6, 0.2, major, minor),
+        _ => make_primitive_with_detail(p, major),
+    }
+}
+
+/// Like `make_primitive` with a tessellation level: `detail` is the per-face grid size for
+/// the cube. Other primitives don't take one yet and ignore it.
+pub(crate) fn make_primitive_with_detail(p: Primitive, detail: u32) -> Mesh {
+    match p {
+        Primitive::Cube => cube_subdivided(detail),
+        _ => make_primitive(p),
     }
 }
 
 pub(crate) fn triangle() -> Mesh {
     let positions = vec![-0.5, -0.5, 0.0, 0.5, -0.5, 0.0, 0.0, 0.5, 0.0];
-    mesh_from_positions_indices(positions, vec![])
+    let uvs = vec![0.0, 0.0, 1.0, 0.0, 0.5, 1.0];
+    mesh_from_positions_indices(positions, Vec::new().into(), uvs)
 }
 
+/// Faces of the unit cube centered at the origin: corner origin, then the u and v edge
+/// directions (u x v = outward normal).
+const CUBE_FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+    ([0.5, -0.5, -0.5], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]), // back (-z)
+    ([-0.5, -0.5, 0.5], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),  // front (+z)
+    ([-0.5, -0.5, -0.5], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]), // left (-x)
+    ([0.5, -0.5, 0.5], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),  // right (+x)
+    ([-0.5, -0.5, -0.5], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]), // bottom (-y)
+    ([-0.5, 0.5, 0.5], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),  // top (+y)
+];
+
 pub(crate) fn cube() -> Mesh {
     // Unit cube centered at origin, size 1.0.
-    let p = [
-        (-0.5f32, -0.5f32, -0.5f32), // 0
-        (0.5f32, -0.5f32, -0.5f32),  // 1
-        (0.5f32, 0.5f32, -0.5f32),   // 2
-        (-0.5f32, 0.5f32, -0.5f32),  // 3
-        (-0.5f32, -0.5f32, 0.5f32),  // 4
-        (0.5f32, -0.5f32, 0.5f32),   // 5
-        (0.5f32, 0.5f32, 0.5f32),    // 6
-        (-0.5f32, 0.5f32, 0.5f32),   // 7
-    ];
-    let mut positions = Vec::with_capacity(8 * 3);
-    for (x, y, z) in p {
-        positions.extend_from_slice(&[x, y, z]);
-    }
-
-    // 12 triangles (two per face), CCW winding.
-    let indices: Vec<u16> = vec![
-        // back (-z)
-        0, 1, 2, 0, 2, 3, //
-        // front (+z)
-        4, 6, 5, 4, 7, 6, //
-        // left (-x)
-        0, 3, 7, 0, 7, 4, //
-        // right (+x)
-        1, 5, 6, 1, 6, 2, //
-        // bottom (-y)
-        0, 4, 5, 0, 5, 1, //
-        // top (+y)
-        3, 2, 6, 3, 6, 7, //
-    ];
+    // Four vertices per face so each face gets its own [0,1] UV square (box mapping).
+    let mut positions = Vec::with_capacity(24 * 3);
+    let mut uvs = Vec::with_capacity(24 * 2);
+    let mut indices: Vec<u16> = Vec::with_capacity(36);
+    for (face, (o, du, dv)) in CUBE_FACES.iter().enumerate() {
+        for (fu, fv) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            for k in 0..3 {
+                positions.push(o[k] + du[k] * fu + dv[k] * fv);
+            }
+            uvs.extend_from_slice(&[fu, fv]);
+        }
+        // Two triangles per face, CCW winding seen from outside.
+        let b = (face * 4) as u16;
+        indices.extend_from_slice(&[b, b + 1, b + 2, b, b + 2, b + 3]);
+    }
+
+    mesh_from_positions_indices(positions, indices.into(), uvs)
+}
+
+/// The unit cube with each face split into a `segments` x `segments` grid, for smoother
+/// lighting and deformation. Grid vertices are shared within a face but not across its
+/// edges, so faces keep flat normals and their own UV square. Clamped to 1..=100
+/// segments, which keeps the vertex count addressable with u16 indices.
+pub(crate) fn cube_subdivided(segments: u32) -> Mesh {
+    let n = segments.clamp(1, 100) as usize;
+    let per_face = (n + 1) * (n + 1);
+    let mut positions = Vec::with_capacity(6 * per_face * 3);
+    let mut uvs = Vec::with_capacity(6 * per_face * 2);
+    let mut indices: Vec<u16> = Vec::with_capacity(6 * n * n * 6);
+    for (face, (o, du, dv)) in CUBE_FACES.iter().enumerate() {
+        for iv in 0..=n {
+            let fv = iv as f32 / n as f32;
+            for iu in 0..=n {
+                let fu = iu as f32 / n as f32;
+                for k in 0..3 {
+                    positions.push(o[k] + du[k] * fu + dv[k] * fv);
+                }
+                uvs.extend_from_slice(&[fu, fv]);
+            }
+        }
+        let base = (face * per_face) as u16;
+        let stride = (n + 1) as u16;
+        for iv in 0..n {
+            for iu in 0..n {
+                let a = base + iv as u16 * stride + iu as u16;
+                let b = a + 1;
+                let c = a + stride;
+                let d = c + 1;
+                // Same winding as `cube`: CCW seen from outside.
+                indices.extend_from_slice(&[a, b, d, a, d, c]);
+            }
+        }
+    }
 
-    mesh_from_positions_indices(positions, indices)
+    mesh_from_positions_indices(positions, indices.into(), uvs)
 }
 
 pub(crate) fn cylinder(radius: f32, height: f32, segments: u32) -> Mesh {
+    cylinder_with_caps(radius, height, segments, true)
+}
+
+/// `cylinder`, or with `capped` false an open tube: just the side quads, no cap fans or
+/// cap vertices.
+pub(crate) fn cylinder_with_caps(radius: f32, height: f32, segments: u32, capped: bool) -> Mesh {
     let segments = segments.max(3) as usize;
     let half_h = height * 0.5;
 
     // Layout:
-    // - ring vertices: 2 per segment (bottom/top)
-    // - cap centers: 2 vertices
-    let mut positions = Vec::with_capacity((segments * 2 + 2) * 3);
+    // - side ring vertices: 2 per segment (bottom/top), plus a duplicated seam column
+    // - cap rings (if capped): 1 per segment each, followed by the cap center
+    // Caps don't share the side vertices so they keep flat normals and their own UVs.
+    let side_count = (segments + 1) * 2;
+    let cap_count = if capped { segments + 1 } else { 0 };
+    let mut positions = Vec::with_capacity((side_count + cap_count * 2) * 3);
+    let mut uvs = Vec::with_capacity((side_count + cap_count * 2) * 2);
 
-    for i in 0..segments {
-        let t = (i as f32) * std::f32::consts::TAU / (segments as f32);
-        let (s, c) = t.sin_cos();
+    // Cylindrical mapping: u wraps around, v runs bottom to top.
+    for i in 0..=segments {
+        let fu = i as f32 / (segments as f32);
+        let (s, c) = (fu * std::f32::consts::TAU).sin_cos();
         let x = c * radius;
         let z = s * radius;
         // bottom
         positions.extend_from_slice(&[x, -half_h, z]);
+        uvs.extend_from_slice(&[fu, 0.0]);
         // top
         positions.extend_from_slice(&[x, half_h, z]);
+        uvs.extend_from_slice(&[fu, 1.0]);
     }
 
-    let bottom_center_idx = (segments * 2) as u16;
-    positions.extend_from_slice(&[0.0, -half_h, 0.0]);
-    let top_center_idx = (segments * 2 + 1) as u16;
-    positions.extend_from_slice(&[0.0, half_h, 0.0]);
+    // Caps use a planar disc mapping.
+    let cap_heights: &[f32] = if capped { &[-half_h, half_h] } else { &[] };
+    for &y in cap_heights {
+        for i in 0..segments {
+            let t = (i as f32) * std::f32::consts::TAU / (segments as f32);
+            let (s, c) = t.sin_cos();
+            positions.extend_from_slice(&[c * radius, y, s * radius]);
+            uvs.extend_from_slice(&[c * 0.5 + 0.5, s * 0.5 + 0.5]);
+        }
+        positions.extend_from_slice(&[0.0, y, 0.0]);
+        uvs.extend_from_slice(&[0.5, 0.5]);
+    }
 
     let mut indices: Vec<u16> = Vec::new();
 
     // sides (two triangles per quad)
     for i in 0..segments {
-        let j = (i + 1) % segments;
         let b0 = (i * 2) as u16;
         let t0 = (i * 2 + 1) as u16;
-        let b1 = (j * 2) as u16;
-        let t1 = (j * 2 + 1) as u16;
+        let b1 = (i * 2 + 2) as u16;
+        let t1 = (i * 2 + 3) as u16;
 
-        indices.extend_from_slice(&[b0, b1, t1, b0, t1, t0]);
+        indices.extend_from_slice(&[b0, t1, b1, b0, t0, t1]);
+    }
+    // An open tube stops at the side quads.
+    if !capped {
+        return mesh_from_positions_indices(positions, indices.into(), uvs);
     }
 
     // bottom cap (fan) - winding so outside faces outwards
+    let bottom = side_count;
+    let bottom_center_idx = (bottom + segments) as u16;
     for i in 0..segments {
         let j = (i + 1) % segments;
-        let b0 = (i * 2) as u16;
-        let b1 = (j * 2) as u16;
-        indices.extend_from_slice(&[bottom_center_idx, b1, b0]);
+        let b0 = (bottom + i) as u16;
+        let b1 = (bottom + j) as u16;
+        indices.extend_from_slice(&[bottom_center_idx, b0, b1]);
     }
 
     // top cap (fan)
+    let top = side_count + cap_count;
+    let top_center_idx = (top + segments) as u16;
     for i in 0..segments {
         let j = (i + 1) % segments;
-        let t0 = (i * 2 + 1) as u16;
-        let t1 = (j * 2 + 1) as u16;
-        indices.extend_from_slice(&[top_center_idx, t0, t1]);
+        let t0 = (top + i) as u16;
+        let t1 = (top + j) as u16;
+        indices.extend_from_slice(&[top_center_idx, t1, t0]);
+    }
+
+    mesh_from_positions_indices(positions, indices.into(), uvs)
+}
+
+pub(crate) fn cone(radius: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(3) as usize;
+    let half_h = height * 0.5;
+
+    // Layout:
+    // - base ring vertices: 1 per segment
+    // - apex, then base center
+    let mut positions = Vec::with_capacity((segments + 2) * 3);
+    // Planar projection seen from above: the apex lands in the middle, no seam.
+    let mut uvs = Vec::with_capacity((segments + 2) * 2);
+
+    for i in 0..segments {
+        let t = (i as f32) * std::f32::consts::TAU / (segments as f32);
+        let (s, c) = t.sin_cos();
+        positions.extend_from_slice(&[c * radius, -half_h, s * radius]);
+        uvs.extend_from_slice(&[c * 0.5 + 0.5, s * 0.5 + 0.5]);
+    }
+
+    let apex_idx = segments as u16;
+    positions.extend_from_slice(&[0.0, half_h, 0.0]);
+    let base_center_idx = (segments + 1) as u16;
+    positions.extend_from_slice(&[0.0, -half_h, 0.0]);
+    uvs.extend_from_slice(&[0.5, 0.5, 0.5, 0.5]);
+
+    let mut indices: Vec<u16> = Vec::with_capacity(segments * 6);
+
+    // sides: one triangle per segment up to the apex
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        indices.extend_from_slice(&[i as u16, apex_idx, j as u16]);
+    }
+
+    // base cap (fan) - same winding as the cylinder's bottom cap
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        indices.extend_from_slice(&[base_center_idx, i as u16, j as u16]);
     }
 
-    mesh_from_positions_indices(positions, indices)
+    mesh_from_positions_indices(positions, indices.into(), uvs)
 }
 
 pub(crate) fn sphere(radius: f32, segments_u: u32, segments_v: u32) -> Mesh {
@@ -146,6 +665,8 @@ pub(crate) fn sphere(radius: f32, segments_u: u32, segments_v: u32) -> Mesh {
     let v = segments_v.max(2) as usize;
 
     let mut positions: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 3);
+    // Spherical (equirectangular) mapping straight from the parameterization.
+    let mut uvs: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 2);
     for iy in 0..=v {
         let fy = iy as f32 / (v as f32);
         let theta = fy * std::f32::consts::PI; // 0..pi
@@ -159,6 +680,7 @@ pub(crate) fn sphere(radius: f32, segments_u: u32, segments_v: u32) -> Mesh {
             let y = ct * radius;
             let z = sp * st * radius;
             positions.extend_from_slice(&[x, y, z]);
+            uvs.extend_from_slice(&[fx, 1.0 - fy]);
         }
     }
 
@@ -170,12 +692,159 @@ pub(crate) fn sphere(radius: f32, segments_u: u32, segments_v: u32) -> Mesh {
             let b = a + 1;
             let c = a + stride;
             let d = c + 1;
-            // two triangles per quad
-            indices.extend_from_slice(&[a, c, d, a, d, b]);
+            // two triangles per quad, CCW seen from outside; the quads touching a pole
+            // have a zero-length edge there and keep only their real triangle
+            if iy + 1 < v {
+                indices.extend_from_slice(&[a, d, c]);
+            }
+            if iy > 0 {
+                indices.extend_from_slice(&[a, b, d]);
+            }
+        }
+    }
+
+    let mut mesh = grid_mesh(positions, indices, uvs);
+    // Each pole vertex touches one fan triangle, whose tilted normal would shade the
+    // tip unevenly; the surface normal there is straight along the axis.
+    let ring = (u + 1) * 3;
+    let south = mesh.normals.len() - ring;
+    for (start, y) in [(0, 1.0), (south, -1.0)] {
+        for n in mesh.normals[start..start + ring].chunks_exact_mut(3) {
+            n.copy_from_slice(&[0.0, y, 0.0]);
+        }
+    }
+    mesh.recompute_tangents();
+    mesh
+}
+
+pub(crate) fn capsule(radius: f32, cylinder_height: f32, segments_u: u32, segments_v: u32) -> Mesh {
+    // u: around +Y, v: rings per hemisphere (pole to equator)
+    let u = segments_u.max(3) as usize;
+    let v = segments_v.max(1) as usize;
+    let half_h = cylinder_height.max(0.0) * 0.5;
+
+    // Rows run from the top pole to the bottom pole. Each hemisphere's equator
+    // ring doubles as the cylinder's end ring, so the joints are welded.
+    let rows = 2 * (v + 1);
+    let mut positions: Vec<f32> = Vec::with_capacity(rows * (u + 1) * 3);
+    let mut uvs: Vec<f32> = Vec::with_capacity(rows * (u + 1) * 2);
+    for row in 0..rows {
+        let (fy, offset) = if row <= v {
+            (row as f32 / (v as f32), half_h)
+        } else {
+            ((row - 1) as f32 / (v as f32), -half_h)
+        };
+        let theta = fy * std::f32::consts::FRAC_PI_2; // 0..pi
+        let (st, ct) = theta.sin_cos();
+        for ix in 0..=u {
+            let fx = ix as f32 / (u as f32);
+            let phi = fx * std::f32::consts::TAU;
+            let (sp, cp) = phi.sin_cos();
+            positions.extend_from_slice(&[
+                cp * st * radius,
+                ct * radius + offset,
+                sp * st * radius,
+            ]);
+            uvs.extend_from_slice(&[fx, 1.0 - row as f32 / (rows - 1) as f32]);
+        }
+    }
+
+    let stride = (u + 1) as u16;
+    let mut indices: Vec<u16> = Vec::with_capacity((rows - 1) * u * 6);
+    for iy in 0..rows - 1 {
+        for ix in 0..u {
+            let a = (iy as u16) * stride + (ix as u16);
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+            indices.extend_from_slice(&[a, d, c, a, b, d]);
+        }
+    }
+
+    mesh_from_positions_indices(positions, indices.into(), uvs)
+}
+
+pub(crate) fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+    // Icosahedron from three orthogonal golden rectangles, then midpoint subdivision.
+    // Clamped so the vertex count stays addressable with u16 indices.
+    let t = (1.0 + 5f32.sqrt()) * 0.5;
+    let mut verts: Vec<Vec3> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[u16; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions.min(6) {
+        // Shared edges are keyed by their sorted endpoints so each midpoint is created once.
+        let mut midpoints: HashMap<(u16, u16), u16> = HashMap::new();
+        let mut midpoint = |a: u16, b: u16, verts: &mut Vec<Vec3>| -> u16 {
+            let key = (a.min(b), a.max(b));
+            *midpoints.entry(key).or_insert_with(|| {
+                let m = verts[a as usize]
+                    .add(verts[b as usize])
+                    .mul(0.5)
+                    .normalize();
+                verts.push(m);
+                (verts.len() - 1) as u16
+            })
+        };
+
+        let mut next = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut verts);
+            let bc = midpoint(b, c, &mut verts);
+            let ca = midpoint(c, a, &mut verts);
+            next.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
         }
+        faces = next;
     }
 
-    mesh_from_positions_indices(positions, indices)
+    let mut positions = Vec::with_capacity(verts.len() * 3);
+    // Spherical mapping from the direction; triangles straddling the u seam stretch.
+    let mut uvs = Vec::with_capacity(verts.len() * 2);
+    for v in &verts {
+        positions.extend_from_slice(&[v.x * radius, v.y * radius, v.z * radius]);
+        let u = 0.5 + v.z.atan2(v.x) / std::f32::consts::TAU;
+        uvs.extend_from_slice(&[u, 1.0 - v.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI]);
+    }
+    let indices: Vec<u16> = faces.into_iter().flatten().collect();
+
+    mesh_from_positions_indices(positions, indices.into(), uvs)
 }
 
 pub(crate) fn torus(major_radius: f32, minor_radius: f32, segments_u: u32, segments_v: u32) -> Mesh {
@@ -184,6 +853,8 @@ pub(crate) fn torus(major_radius: f32, minor_radius: f32, segments_u: u32, segme
     let v = segments_v.max(3) as usize;
 
     let mut positions: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 3);
+    // Toroidal mapping: u around the hole, v around the tube.
+    let mut uvs: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 2);
     for iu in 0..=u {
         let fu = iu as f32 / (u as f32);
         let theta = fu * std::f32::consts::TAU;
@@ -199,6 +870,7 @@ pub(crate) fn torus(major_radius: f32, minor_radius: f32, segments_u: u32, segme
             let y = minor_radius * sp;
             let z = st * r;
             positions.extend_from_slice(&[x, y, z]);
+            uvs.extend_from_slice(&[fu, fv]);
         }
     }
 
@@ -210,88 +882,1133 @@ pub(crate) fn torus(major_radius: f32, minor_radius: f32, segments_u: u32, segme
             let b = a + 1;
             let c = a + stride;
             let d = c + 1;
-            indices.extend_from_slice(&[a, c, d, a, d, b]);
+            indices.extend_from_slice(&[a, d, c, a, b, d]);
         }
     }
 
-    mesh_from_positions_indices(positions, indices)
+    grid_mesh(positions, indices, uvs)
 }
 
-fn mesh_from_positions_indices(positions: Vec<f32>, indices: Vec<u16>) -> Mesh {
-    let bounds = compute_bounds(&positions);
-    let normals = compute_normals(&positions, &indices);
-    Mesh {
-        positions,
-        normals,
-        indices,
-        bounds,
-    }
-}
+pub(crate) fn plane(width: f32, depth: f32, segments: u32) -> Mesh {
+    // Subdivided quad in the XZ plane, centered at origin, facing +Y.
+    let n = segments.max(1) as usize;
 
-fn compute_bounds(positions: &[f32]) -> Bounds {
-    if positions.len() < 3 {
-        return Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+    let mut positions: Vec<f32> = Vec::with_capacity((n + 1) * (n + 1) * 3);
+    let mut uvs: Vec<f32> = Vec::with_capacity((n + 1) * (n + 1) * 2);
+    for iz in 0..=n {
+        let fz = iz as f32 / n as f32;
+        let z = (fz - 0.5) * depth;
+        for ix in 0..=n {
+            let fx = ix as f32 / n as f32;
+            let x = (fx - 0.5) * width;
+            positions.extend_from_slice(&[x, 0.0, z]);
+            uvs.extend_from_slice(&[fx, 1.0 - fz]);
+        }
     }
-    let mut min = Vec3::new(positions[0], positions[1], positions[2]);
-    let mut max = Vec3::new(positions[0], positions[1], positions[2]);
-    for v in positions.chunks_exact(3) {
-        let x = v[0];
-        let y = v[1];
-        let z = v[2];
-        min.x = min.x.min(x);
-        min.y = min.y.min(y);
-        min.z = min.z.min(z);
-        max.x = max.x.max(x);
-        max.y = max.y.max(y);
-        max.z = max.z.max(z);
+
+    let stride = (n + 1) as u16;
+    let mut indices: Vec<u16> = Vec::with_capacity(n * n * 6);
+    for iz in 0..n {
+        for ix in 0..n {
+            let a = (iz as u16) * stride + (ix as u16);
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+            // CCW seen from above so the face normal points +Y
+            indices.extend_from_slice(&[a, c, b, b, c, d]);
+        }
     }
-    Bounds::new(min, max)
+
+    grid_mesh(positions, indices, uvs)
 }
 
-/// Compute per-vertex normals by averaging adjacent triangle normals.
-/// Handles both indexed and non-indexed geometry.
-fn compute_normals(positions: &[f32], indices: &[u16]) -> Vec<f32> {
-    let mut normals = vec![0.0f32; positions.len()];
+/// Surface sampled from `f(u, v)` over the unit square on a `u_segments` x `v_segments`
+/// grid, with `(u, v)` as texture coordinates. Triangles wind so front faces point along
+/// `df/du x df/dv`. Closed surfaces get a duplicated seam row/column, like `sphere`.
+/// Each count is clamped to 1..=`MAX_GRID_SEGMENTS`.
+pub(crate) fn parametric(u_segments: u32, v_segments: u32, f: impl Fn(f32, f32) -> Vec3) -> Mesh {
+    let u = u_segments.clamp(1, MAX_GRID_SEGMENTS) as usize;
+    let v = v_segments.clamp(1, MAX_GRID_SEGMENTS) as usize;
 
-    if indices.is_empty() {
-        // Non-indexed: assume triangles laid out sequentially.
-        for (tri_idx, tri) in positions.chunks_exact(9).enumerate() {
-            let a = Vec3::new(tri[0], tri[1], tri[2]);
-            let b = Vec3::new(tri[3], tri[4], tri[5]);
-            let c = Vec3::new(tri[6], tri[7], tri[8]);
-            let n = b.sub(a).cross(c.sub(a)).normalize();
-            let base = tri_idx * 9;
-            for v in 0..3 {
-                let dst = base + v * 3;
-                normals[dst] += n.x;
-                normals[dst + 1] += n.y;
-                normals[dst + 2] += n.z;
-            }
+    let mut positions: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 3);
+    let mut uvs: Vec<f32> = Vec::with_capacity((u + 1) * (v + 1) * 2);
+    for iu in 0..=u {
+        let fu = iu as f32 / u as f32;
+        for iv in 0..=v {
+            let fv = iv as f32 / v as f32;
+            let p = f(fu, fv);
+            positions.extend_from_slice(&[p.x, p.y, p.z]);
+            uvs.extend_from_slice(&[fu, fv]);
         }
-    } else {
-        // Indexed: accumulate face normals for each referenced vertex.
-        for idx in indices.chunks_exact(3) {
-            let ia = idx[0] as usize * 3;
-            let ib = idx[1] as usize * 3;
-            let ic = idx[2] as usize * 3;
-            if ic + 2 >= positions.len() {
-                continue;
-            }
-            let a = Vec3::new(positions[ia], positions[ia + 1], positions[ia + 2]);
-            let b = Vec3::new(positions[ib], positions[ib + 1], positions[ib + 2]);
-            let c = Vec3::new(positions[ic], positions[ic + 1], positions[ic + 2]);
-            let n = b.sub(a).cross(c.sub(a)).normalize();
-            for &i in &[ia, ib, ic] {
-                normals[i] += n.x;
-                normals[i + 1] += n.y;
-                normals[i + 2] += n.z;
-            }
+    }
+
+    let stride = (v + 1) as u16;
+    let mut indices: Vec<u16> = Vec::with_capacity(u * v * 6);
+    for iu in 0..u {
+        for iv in 0..v {
+            let a = (iu as u16) * stride + (iv as u16);
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+            indices.extend_from_slice(&[a, c, d, a, d, b]);
         }
     }
 
-    // Normalize the accumulated normals; default to +Z when degenerate.
-    for n in normals.chunks_exact_mut(3) {
-        let v = Vec3::new(n[0], n[1], n[2]).normalize();
+    mesh_from_positions_indices(positions, indices.into(), uvs)
+}
+
+/// Figure-8 immersion of a Klein bottle around +Y, about 1.6 units across. Being
+/// one-sided, part of it shows back faces from any viewpoint.
+pub(crate) fn klein_bottle(segments_u: u32, segments_v: u32) -> Mesh {
+    const R: f32 = 2.0;
+    const SCALE: f32 = 0.25;
+    parametric(segments_u, segments_v, |u, v| {
+        let (u, v) = (u * std::f32::consts::TAU, v * std::f32::consts::TAU);
+        let (sh, ch) = (u * 0.5).sin_cos();
+        let r = R + ch * v.sin() - sh * (2.0 * v).sin();
+        let (su, cu) = u.sin_cos();
+        // Tube around +Y.
+        Vec3::new(r * cu, sh * v.sin() + ch * (2.0 * v).sin(), r * su).mul(SCALE)
+    })
+}
+
+/// Möbius strip of radius 0.5 around +Y, 0.5 units wide.
+pub(crate) fn mobius_strip(segments_u: u32, segments_v: u32) -> Mesh {
+    parametric(segments_u, segments_v, |u, v| {
+        let (u, w) = (u * std::f32::consts::TAU, v - 0.5);
+        let (sh, ch) = (u * 0.5).sin_cos();
+        let r = 1.0 + w * ch;
+        let (su, cu) = u.sin_cos();
+        Vec3::new(r * cu, w * sh, r * su).mul(0.5)
+    })
+}
+
+/// Reference grid through the origin in the plane spanned by the unit vectors `side` and
+/// `front`, covering `[-half_extent, half_extent]` along each, as a line list:
+/// `divisions + 1` lines along each axis. `(X, Z)` gives the Y-up ground.
+pub(crate) fn grid_lines(half_extent: f32, divisions: u32, side: Vec3, front: Vec3) -> Mesh {
+    let n = divisions.max(1);
+    let mut positions = Vec::with_capacity((n as usize + 1) * 4 * 3);
+    let mut push = |a: f32, b: f32| {
+        let p = side.mul(a).add(front.mul(b));
+        positions.extend_from_slice(&[p.x, p.y, p.z]);
+    };
+    for i in 0..=n {
+        let t = (i as f32 / n as f32 * 2.0 - 1.0) * half_extent;
+        // One line parallel to `front`, one parallel to `side`.
+        push(t, -half_extent);
+        push(t, half_extent);
+        push(-half_extent, t);
+        push(half_extent, t);
+    }
+    line_list(positions)
+}
+
+/// X, Y and Z axes from the origin as a line list, colored red, green and blue.
+pub(crate) fn axes(length: f32) -> Mesh {
+    let mut mesh = line_list(vec![
+        0.0, 0.0, 0.0, length, 0.0, 0.0, //
+        0.0, 0.0, 0.0, 0.0, length, 0.0, //
+        0.0, 0.0, 0.0, 0.0, 0.0, length,
+    ]);
+    mesh.colors = vec![
+        1.0, 0.0, 0.0, 1.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, 1.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+    ];
+    mesh
+}
+
+/// One segment per vertex from its position along its normal, scaled to `length`, as a
+/// line list for inspecting normals. Extra positions or normals without a partner are
+/// ignored.
+pub(crate) fn normal_lines(positions: &[f32], normals: &[f32], length: f32) -> Mesh {
+    let mut out = Vec::with_capacity(positions.len().min(normals.len()) * 2);
+    for (p, n) in positions.chunks_exact(3).zip(normals.chunks_exact(3)) {
+        let tip = Vec3::new(p[0], p[1], p[2]).add(Vec3::new(n[0], n[1], n[2]).mul(length));
+        out.extend_from_slice(&[p[0], p[1], p[2], tip.x, tip.y, tip.z]);
+    }
+    line_list(out)
+}
+
+/// A polyline through `points` (flat xyz) in order, drawn as a line strip. A trailing
+/// partial point is ignored.
+pub(crate) fn polyline(points: &[f32]) -> Mesh {
+    let mut mesh = line_list(points[..points.len() / 3 * 3].to_vec());
+    mesh.topology = Topology::LineStrip;
+    mesh
+}
+
+/// Line segments expanded into screen-facing quads, two triangles per segment, for lines
+/// wider than `lineWidth` allows. Each vertex carries its own endpoint, the segment's
+/// other endpoint and which side of the line it is pushed to; the vertex shader does the
+/// pushing in screen space.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LineQuads {
+    pub positions: Vec<f32>,
+    pub others: Vec<f32>,
+    /// +1 or -1 per vertex.
+    pub sides: Vec<f32>,
+}
+
+/// Expand the segments of a `Lines` or `LineStrip` mesh into `LineQuads`.
+pub(crate) fn line_quads(mesh: &Mesh) -> LineQuads {
+    let segments = mesh.segments();
+    let mut quads = LineQuads {
+        positions: Vec::with_capacity(segments.len() * 18),
+        others: Vec::with_capacity(segments.len() * 18),
+        sides: Vec::with_capacity(segments.len() * 6),
+    };
+    for [a, b] in segments {
+        let (pa, pb) = (mesh.position(a), mesh.position(b));
+        // Sides are measured against the direction toward `other`, which flips at `b`:
+        // `b`'s +1 corner lies across from `a`'s, so the quad runs a+, a-, b+, b-.
+        let corners = [(pa, pb, 1.0), (pa, pb, -1.0), (pb, pa, 1.0), (pb, pa, -1.0)];
+        for corner in [0, 1, 2, 0, 2, 3] {
+            let (p, other, side) = corners[corner];
+            quads.positions.extend_from_slice(&[p.x, p.y, p.z]);
+            quads.others.extend_from_slice(&[other.x, other.y, other.z]);
+            quads.sides.push(side);
+        }
+    }
+    quads
+}
+
+/// Build a mesh from caller-supplied buffers, recomputing bounds and normals.
+/// `positions` is flat xyz; empty `indices` means non-indexed triangles.
+/// Meshes with more than 65,536 vertices get 32-bit indices.
+pub(crate) fn mesh_from_raw(positions: &[f32], indices: &[u32]) -> Result<Mesh, String> {
+    if !positions.len().is_multiple_of(3) {
+        return Err(format!(
+            "positions length {} is not a multiple of 3",
+            positions.len()
+        ));
+    }
+    if !indices.len().is_multiple_of(3) {
+        return Err(format!(
+            "indices length {} is not a multiple of 3",
+            indices.len()
+        ));
+    }
+    let vertex_count = positions.len() / 3;
+    if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertex_count) {
+        return Err(format!(
+            "index {bad} out of range for {vertex_count} vertices"
+        ));
+    }
+    // No UV source for raw data; zeros keep the attribute buffer sized.
+    Ok(mesh_from_positions_indices(
+        positions.to_vec(),
+        IndexBuffer::from_u32(indices.to_vec(), vertex_count),
+        vec![0.0; vertex_count * 2],
+    ))
+}
+
+/// Swap the 2nd and 3rd corner of every triangle, reversing its winding.
+pub(crate) fn flip_winding<I>(indices: &mut [I]) {
+    for tri in indices.chunks_exact_mut(3) {
+        tri.swap(1, 2);
+    }
+}
+
+/// Which way the triangles turn, from the signed volume they enclose about the center of
+/// their bounds (positive for counter-clockwise seen from outside). Volume that mostly
+/// cancels out, as for flat or mixed meshes, gives `Winding::Ambiguous`.
+pub(crate) fn detect_winding<I: Copy + Into<u32>>(positions: &[f32], indices: &[I]) -> Winding {
+    let center = compute_bounds(positions).center();
+    let vertex = |i: I| {
+        let i = i.into() as usize * 3;
+        Vec3::new(positions[i], positions[i + 1], positions[i + 2]).sub(center)
+    };
+    let (mut volume, mut magnitude) = (0.0f64, 0.0f64);
+    for tri in indices.chunks_exact(3) {
+        let (a, b, c) = (vertex(tri[0]), vertex(tri[1]), vertex(tri[2]));
+        let v = a.dot(b.cross(c)) as f64;
+        volume += v;
+        magnitude += v.abs();
+    }
+    if volume.abs() <= magnitude * 1e-3 {
+        Winding::Ambiguous
+    } else if volume > 0.0 {
+        Winding::CounterClockwise
+    } else {
+        Winding::Clockwise
+    }
+}
+
+/// Flip triangles so that neighbours sharing an edge run it in opposite directions, which
+/// is what a consistently wound surface does. Each connected patch keeps the winding of
+/// the larger part of its area (ties keep the first triangle's) and the rest is flipped to
+/// match. Returns how many triangles flipped. Non-manifold edges are followed too, so such
+/// a mesh can still come out mixed.
+pub(crate) fn make_consistent_winding<I: Copy + Into<u32>>(
+    indices: &mut [I],
+    positions: &[f32],
+) -> usize {
+    let tris: Vec<[u32; 3]> = indices
+        .chunks_exact(3)
+        .map(|t| [t[0].into(), t[1].into(), t[2].into()])
+        .collect();
+    let mut edge_tris: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (t, tri) in tris.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            if a != b {
+                edge_tris.entry((a.min(b), a.max(b))).or_default().push(t);
+            }
+        }
+    }
+    let runs = |tri: &[u32; 3], a, b| (0..3).any(|k| tri[k] == a && tri[(k + 1) % 3] == b);
+    let area = |tri: &[u32; 3]| {
+        let p = |i: u32| {
+            let i = i as usize * 3;
+            Vec3::new(positions[i], positions[i + 1], positions[i + 2])
+        };
+        let (a, b, c) = (p(tri[0]), p(tri[1]), p(tri[2]));
+        b.sub(a).cross(c.sub(a)).length()
+    };
+
+    // Walk each patch from its first triangle, recording which ones disagree with it.
+    let mut flip: Vec<Option<bool>> = vec![None; tris.len()];
+    let mut flipped = 0;
+    for seed in 0..tris.len() {
+        if flip[seed].is_some() {
+            continue;
+        }
+        flip[seed] = Some(false);
+        let mut patch = vec![seed];
+        let mut next = 0;
+        while let Some(&t) = patch.get(next) {
+            next += 1;
+            let turned = flip[t] == Some(true);
+            for k in 0..3 {
+                let (mut a, mut b) = (tris[t][k], tris[t][(k + 1) % 3]);
+                if turned {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                let Some(neighbours) = edge_tris.get(&(a.min(b), a.max(b))) else {
+                    continue;
+                };
+                for &u in neighbours {
+                    if flip[u].is_none() {
+                        // As wound, `t` runs a -> b, so a consistent `u` runs b -> a.
+                        flip[u] = Some(runs(&tris[u], a, b));
+                        patch.push(u);
+                    }
+                }
+            }
+        }
+
+        let (mut kept, mut turned) = (0.0, 0.0);
+        for &t in &patch {
+            if flip[t] == Some(true) {
+                turned += area(&tris[t]);
+            } else {
+                kept += area(&tris[t]);
+            }
+        }
+        for &t in &patch {
+            if flip[t] == Some(kept >= turned) {
+                indices.swap(t * 3 + 1, t * 3 + 2);
+                flipped += 1;
+            }
+        }
+    }
+    flipped
+}
+
+/// Merge vertices closer than `epsilon` and remap `indices` onto the survivors.
+/// Empty `indices` means non-indexed triangles. Indices must already be in range.
+/// Candidates are bucketed on an `epsilon`-sized grid, so only neighbouring cells are compared.
+pub(crate) fn weld_vertices(
+    positions: &[f32],
+    indices: &[u32],
+    epsilon: f32,
+) -> (Vec<f32>, Vec<u32>) {
+    let eps = epsilon.max(f32::MIN_POSITIVE);
+    let cell_of = |v: Vec3| {
+        (
+            (v.x / eps).floor() as i64,
+            (v.y / eps).floor() as i64,
+            (v.z / eps).floor() as i64,
+        )
+    };
+
+    let mut grid: HashMap<(i64, i64, i64), Vec<u32>> = HashMap::new();
+    let mut welded: Vec<f32> = Vec::new();
+    let mut remap = Vec::with_capacity(positions.len() / 3);
+    for v in positions.chunks_exact(3) {
+        let p = Vec3::new(v[0], v[1], v[2]);
+        let (cx, cy, cz) = cell_of(p);
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(bucket) = grid.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+                    for &j in bucket {
+                        let b = j as usize * 3;
+                        let q = Vec3::new(welded[b], welded[b + 1], welded[b + 2]);
+                        if p.sub(q).length() <= epsilon {
+                            found = Some(j);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        let index = found.unwrap_or_else(|| {
+            let j = (welded.len() / 3) as u32;
+            welded.extend_from_slice(&[p.x, p.y, p.z]);
+            grid.entry((cx, cy, cz)).or_default().push(j);
+            j
+        });
+        remap.push(index);
+    }
+
+    let indices = if indices.is_empty() {
+        remap
+    } else {
+        indices.iter().map(|&i| remap[i as usize]).collect()
+    };
+    (welded, indices)
+}
+
+/// Loop subdivision: every triangle becomes four and vertices are smoothed toward their
+/// neighbours. Boundary (and UV-seam) edges use the boundary rule so open edges stay put
+/// as curves. Normals, tangents and bounds are recomputed; UVs and colors are interpolated.
+/// `iterations` is capped at 5 (each one multiplies the face count by 4).
+pub(crate) fn subdivide_loop(mesh: &Mesh, iterations: u32) -> Mesh {
+    if mesh.topology != Topology::Triangles {
+        return mesh.clone();
+    }
+    let mut positions = mesh.positions.clone();
+    let mut uvs = mesh.uvs.clone();
+    let mut colors = mesh.colors.clone();
+    let mut corners = mesh.corner_indices();
+    for _ in 0..iterations.min(5) {
+        (positions, uvs, colors, corners) = loop_step(&positions, &uvs, &colors, &corners);
+    }
+    let vertex_count = positions.len() / 3;
+    let indices = corners.iter().map(|&i| i as u32).collect();
+    let mut out =
+        mesh_from_positions_indices(positions, IndexBuffer::from_u32(indices, vertex_count), uvs);
+    out.colors = colors;
+    out
+}
+
+/// Weight of the planes that hold open edges in place during `simplify`, per squared
+/// edge length. High enough that boundaries only move where nothing else is left.
+const SIMPLIFY_BOUNDARY_WEIGHT: f64 = 100.0;
+
+/// Quadric error metric edge collapse (Garland and Heckbert): repeatedly merge the two
+/// ends of the edge whose merged vertex strays least from the planes of the faces around
+/// both, until about `target_ratio` of the triangles are left. Open edges (UV seams
+/// included, since their vertices are split) are weighted to stay put, and collapses that
+/// would flip a face are skipped, so the result can stop above the target. Normals,
+/// tangents and bounds are recomputed; each kept vertex keeps its UV and color.
+pub(crate) fn simplify(mesh: &Mesh, target_ratio: f32) -> Mesh {
+    if mesh.topology != Topology::Triangles || !(target_ratio > 0.0 && target_ratio < 1.0) {
+        return mesh.clone();
+    }
+    let mut positions: Vec<Vec3> = mesh
+        .positions
+        .chunks_exact(3)
+        .map(|p| Vec3::new(p[0], p[1], p[2]))
+        .collect();
+    let vertex_count = positions.len();
+    let mut tris: Vec<[usize; 3]> = mesh
+        .corner_indices()
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+    let target = ((tris.len() as f32 * target_ratio).round() as usize).max(1);
+
+    // Each vertex starts with the planes of its faces; faces per edge find the open ones.
+    let mut quadrics = vec![Quadric::default(); vertex_count];
+    let mut faces_of: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    let mut edge_faces: HashMap<(usize, usize), u32> = HashMap::new();
+    for (f, &tri) in tris.iter().enumerate() {
+        let plane = Quadric::face(&positions, tri);
+        for k in 0..3 {
+            quadrics[tri[k]].add(&plane);
+            faces_of[tri[k]].push(f);
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            *edge_faces.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    // An open edge gets a steep plane through it, upright on its face.
+    for &tri in &tris {
+        let normal = face_normal(&positions, tri);
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            if edge_faces[&(a.min(b), a.max(b))] != 1 {
+                continue;
+            }
+            let edge = positions[b].sub(positions[a]);
+            let side = edge.cross(normal);
+            if side.length() <= 1e-12 {
+                continue;
+            }
+            let weight = SIMPLIFY_BOUNDARY_WEIGHT * edge.dot(edge) as f64;
+            let wall = Quadric::plane(side.normalize(), positions[a]).scaled(weight);
+            quadrics[a].add(&wall);
+            quadrics[b].add(&wall);
+        }
+    }
+
+    // Cheapest collapse first; an entry is stale once either end has changed since.
+    let mut version = vec![0u32; vertex_count];
+    let mut edges: Vec<(usize, usize)> = edge_faces.into_keys().collect();
+    edges.sort_unstable();
+    let mut heap: BinaryHeap<Reverse<Collapse>> = edges
+        .into_iter()
+        .map(|(a, b)| Reverse(Collapse::new(&positions, &quadrics, &version, a, b)))
+        .collect();
+
+    let mut alive = vec![true; tris.len()];
+    let mut live = tris.len();
+    while live > target {
+        let Some(Reverse(c)) = heap.pop() else {
+            break;
+        };
+        let (a, b) = (c.a, c.b);
+        if c.stamp != (version[a], version[b]) || faces_of[a].is_empty() || faces_of[b].is_empty() {
+            continue;
+        }
+        let moved = |v: usize| {
+            if v == a || v == b {
+                c.target
+            } else {
+                positions[v]
+            }
+        };
+        let flips = faces_of[a].iter().chain(&faces_of[b]).any(|&f| {
+            let tri = tris[f];
+            // Faces on the edge disappear; the rest must keep facing the same way.
+            if tri.contains(&a) && tri.contains(&b) {
+                return false;
+            }
+            let before = face_normal(&positions, tri);
+            let p = tri.map(moved);
+            let after = p[1].sub(p[0]).cross(p[2].sub(p[0]));
+            before.dot(after) <= 0.0
+        });
+        if flips {
+            continue;
+        }
+
+        // `a` moves to the target and takes over `b`'s faces; the ones on the edge go.
+        positions[a] = c.target;
+        quadrics[a] = quadrics[a].sum(&quadrics[b]);
+        for f in std::mem::take(&mut faces_of[b]) {
+            if !alive[f] {
+                continue;
+            }
+            if tris[f].contains(&a) {
+                alive[f] = false;
+                live -= 1;
+                // Forget the face at every corner, so no later collapse meets it again.
+                for v in tris[f] {
+                    faces_of[v].retain(|&g| g != f);
+                }
+            } else {
+                tris[f] = tris[f].map(|v| if v == b { a } else { v });
+                faces_of[a].push(f);
+            }
+        }
+        version[a] += 1;
+        version[b] += 1;
+
+        let mut ring: Vec<usize> = faces_of[a].iter().flat_map(|&f| tris[f]).collect();
+        ring.sort_unstable();
+        ring.dedup();
+        for n in ring.into_iter().filter(|&n| n != a) {
+            let (lo, hi) = (a.min(n), a.max(n));
+            heap.push(Reverse(Collapse::new(
+                &positions, &quadrics, &version, lo, hi,
+            )));
+        }
+    }
+
+    debug_assert_eq!(live, alive.iter().filter(|&&alive| alive).count());
+
+    // Keep the vertices still in use, in their original order.
+    let mut remap = vec![u32::MAX; vertex_count];
+    let (mut out_positions, mut uvs, mut colors) = (Vec::new(), Vec::new(), Vec::new());
+    for v in (0..vertex_count).filter(|&v| !faces_of[v].is_empty()) {
+        remap[v] = (out_positions.len() / 3) as u32;
+        out_positions.extend_from_slice(&[positions[v].x, positions[v].y, positions[v].z]);
+        uvs.extend_from_slice(mesh.uvs.get(v * 2..v * 2 + 2).unwrap_or(&[0.0, 0.0]));
+        if let Some(color) = mesh.colors.get(v * 3..v * 3 + 3) {
+            colors.extend_from_slice(color);
+        }
+    }
+    let indices: Vec<u32> = tris
+        .iter()
+        .zip(&alive)
+        .filter(|&(_, &alive)| alive)
+        .flat_map(|(tri, _)| tri.map(|v| remap[v]))
+        .collect();
+    let count = out_positions.len() / 3;
+    let mut out =
+        mesh_from_positions_indices(out_positions, IndexBuffer::from_u32(indices, count), uvs);
+    out.colors = colors;
+    out
+}
+
+/// Unnormalized normal of triangle `tri` (twice its area long).
+fn face_normal(positions: &[Vec3], [a, b, c]: [usize; 3]) -> Vec3 {
+    positions[b]
+        .sub(positions[a])
+        .cross(positions[c].sub(positions[a]))
+}
+
+/// Sum of squared distances to a set of planes, as the symmetric 4x4 matrix `Q` with
+/// error `[p 1] Q [p 1]^T`; upper triangle stored row by row. In f64 since the terms
+/// cancel heavily near the minimum.
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// The plane with unit `normal` through `point`.
+    fn plane(normal: Vec3, point: Vec3) -> Self {
+        let [a, b, c] = [normal.x, normal.y, normal.z].map(f64::from);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        Self([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+    }
+
+    /// The plane of triangle `tri`, weighted by its area; zero for degenerate triangles.
+    fn face(positions: &[Vec3], tri: [usize; 3]) -> Self {
+        let n = face_normal(positions, tri);
+        let double_area = n.length();
+        if double_area <= 1e-12 {
+            return Self::default();
+        }
+        Self::plane(n.div(double_area), positions[tri[0]]).scaled(0.5 * double_area as f64)
+    }
+
+    fn scaled(self, s: f64) -> Self {
+        Self(self.0.map(|q| q * s))
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn sum(&self, other: &Self) -> Self {
+        let mut out = *self;
+        out.add(other);
+        out
+    }
+
+    fn error(&self, p: Vec3) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+        let [x, y, z] = [p.x, p.y, p.z].map(f64::from);
+        a2 * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + b2 * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + c2 * z * z
+            + 2.0 * cd * z
+            + d2
+    }
+
+    /// Where to put the vertex merged from `p` and `q`, and its error: the minimum of
+    /// the quadric when it has a single one, else the better of the ends and midpoint.
+    fn best_point(&self, p: Vec3, q: Vec3) -> (f64, Vec3) {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, _] = self.0;
+        let det = a2 * (b2 * c2 - bc * bc) - ab * (ab * c2 - bc * ac) + ac * (ab * bc - b2 * ac);
+        let mut candidates = vec![p, q, p.add(q).mul(0.5)];
+        if det.abs() > 1e-12 {
+            // Cramer's rule on the gradient being zero.
+            let x = -(ad * (b2 * c2 - bc * bc) - ab * (bd * c2 - bc * cd)
+                + ac * (bd * bc - b2 * cd))
+                / det;
+            let y = -(a2 * (bd * c2 - cd * bc) - ad * (ab * c2 - bc * ac)
+                + ac * (ab * cd - bd * ac))
+                / det;
+            let z = -(a2 * (b2 * cd - bc * bd) - ab * (ab * cd - bd * ac)
+                + ad * (ab * bc - b2 * ac))
+                / det;
+            candidates.push(Vec3::new(x as f32, y as f32, z as f32));
+        }
+        candidates
+            .into_iter()
+            .map(|c| (self.error(c), c))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .unwrap()
+    }
+}
+
+/// A pending `simplify` edge collapse, ordered by cost (ties by edge, for a stable
+/// result). `stamp` holds both ends' versions when it was queued.
+#[derive(Debug)]
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    target: Vec3,
+    stamp: (u32, u32),
+}
+
+impl Collapse {
+    fn new(positions: &[Vec3], quadrics: &[Quadric], version: &[u32], a: usize, b: usize) -> Self {
+        let q = quadrics[a].sum(&quadrics[b]);
+        let (cost, target) = q.best_point(positions[a], positions[b]);
+        Self {
+            cost,
+            a,
+            b,
+            target,
+            stamp: (version[a], version[b]),
+        }
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cost
+            .total_cmp(&other.cost)
+            .then((self.a, self.b).cmp(&(other.a, other.b)))
+    }
+}
+
+/// One subdivision round. UVs and (optional, possibly empty) colors are interpolated
+/// linearly at edge midpoints; only positions are smoothed.
+fn loop_step(
+    positions: &[f32],
+    uvs: &[f32],
+    colors: &[f32],
+    corners: &[usize],
+) -> (Vec<f32>, Vec<f32>, Vec<f32>, Vec<usize>) {
+    let vertex_count = positions.len() / 3;
+    let p = |i: usize| Vec3::new(positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]);
+
+    // Opposite corners of every edge; one entry per adjacent face.
+    let mut edge_opposites: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for tri in corners.chunks_exact(3) {
+        for k in 0..3 {
+            let (a, b, c) = (tri[k], tri[(k + 1) % 3], tri[(k + 2) % 3]);
+            edge_opposites
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push(c);
+        }
+    }
+
+    // Neighbour rings, plus the subset reached over boundary edges.
+    let mut ring: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    let mut boundary_ring: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for (&(a, b), opposites) in &edge_opposites {
+        ring[a].push(b);
+        ring[b].push(a);
+        if opposites.len() != 2 {
+            boundary_ring[a].push(b);
+            boundary_ring[b].push(a);
+        }
+    }
+
+    let mut out_positions = Vec::with_capacity((vertex_count + edge_opposites.len()) * 3);
+    for v in 0..vertex_count {
+        let moved = match (boundary_ring[v].as_slice(), ring[v].len()) {
+            ([b0, b1], _) => p(v).mul(0.75).add(p(*b0).add(p(*b1)).mul(0.125)),
+            ([], n) if n >= 3 => {
+                let beta = if n == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n as f32)
+                };
+                let sum = ring[v]
+                    .iter()
+                    .fold(Vec3::new(0.0, 0.0, 0.0), |acc, &u| acc.add(p(u)));
+                p(v).mul(1.0 - n as f32 * beta).add(sum.mul(beta))
+            }
+            // Corners and non-manifold vertices keep their position.
+            _ => p(v),
+        };
+        out_positions.extend_from_slice(&[moved.x, moved.y, moved.z]);
+    }
+    let mut out_uvs = uvs.to_vec();
+    let mut out_colors = colors.to_vec();
+
+    // Sorted so the new vertex order does not depend on hash iteration.
+    let mut edges: Vec<_> = edge_opposites.into_iter().collect();
+    edges.sort_unstable_by_key(|(edge, _)| *edge);
+    let mut edge_vertex: HashMap<(usize, usize), usize> = HashMap::with_capacity(edges.len());
+    for ((a, b), opposites) in edges {
+        let point = match opposites.as_slice() {
+            [c, d] => p(a).add(p(b)).mul(0.375).add(p(*c).add(p(*d)).mul(0.125)),
+            _ => p(a).add(p(b)).mul(0.5),
+        };
+        edge_vertex.insert((a, b), out_positions.len() / 3);
+        out_positions.extend_from_slice(&[point.x, point.y, point.z]);
+        for k in 0..2 {
+            out_uvs.push((uvs[a * 2 + k] + uvs[b * 2 + k]) * 0.5);
+        }
+        if !colors.is_empty() {
+            for k in 0..3 {
+                out_colors.push((colors[a * 3 + k] + colors[b * 3 + k]) * 0.5);
+            }
+        }
+    }
+
+    let mut out_corners = Vec::with_capacity(corners.len() * 4);
+    for tri in corners.chunks_exact(3) {
+        let (a, b, c) = (tri[0], tri[1], tri[2]);
+        let mid = |u: usize, v: usize| edge_vertex[&(u.min(v), u.max(v))];
+        let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+        out_corners.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+    }
+    (out_positions, out_uvs, out_colors, out_corners)
+}
+
+/// `mesh_from_positions_indices` for a grid of quads, which also gets its strip ordering.
+fn grid_mesh(positions: Vec<f32>, indices: Vec<u16>, uvs: Vec<f32>) -> Mesh {
+    let strip = to_triangle_strip(&indices);
+    let mut mesh = mesh_from_positions_indices(positions, indices.into(), uvs);
+    mesh.strip = strip;
+    mesh
+}
+
+fn mesh_from_positions_indices(positions: Vec<f32>, indices: IndexBuffer, uvs: Vec<f32>) -> Mesh {
+    let bounds = compute_bounds(&positions);
+    let mut mesh = Mesh {
+        positions,
+        normals: Vec::new(),
+        uvs,
+        tangents: Vec::new(),
+        colors: Vec::new(),
+        indices,
+        strip: Vec::new(),
+        bounds,
+        topology: Topology::Triangles,
+    };
+    mesh.recompute_normals();
+    mesh
+}
+
+/// Assemble a point cloud. Without normals, points face away from the bounds center
+/// so lighting still gives the cloud some shape.
+fn point_cloud(positions: Vec<f32>, normals: Option<Vec<f32>>) -> Mesh {
+    let bounds = compute_bounds(&positions);
+    let normals = normals.unwrap_or_else(|| {
+        let center = bounds.center();
+        positions
+            .chunks_exact(3)
+            .flat_map(|v| {
+                let n = Vec3::new(v[0], v[1], v[2]).sub(center).normalize();
+                [n.x, n.y, n.z]
+            })
+            .collect()
+    });
+    let vertex_count = positions.len() / 3;
+    Mesh {
+        positions,
+        normals,
+        uvs: vec![0.0; vertex_count * 2],
+        tangents: vec![0.0; vertex_count * 4],
+        colors: Vec::new(),
+        indices: IndexBuffer::U16(Vec::new()),
+        strip: Vec::new(),
+        bounds,
+        topology: Topology::Points,
+    }
+}
+
+/// Assemble unindexed line segments. Lines are unlit, so the other attributes are zeros.
+fn line_list(positions: Vec<f32>) -> Mesh {
+    let vertex_count = positions.len() / 3;
+    Mesh {
+        bounds: compute_bounds(&positions),
+        normals: vec![0.0; vertex_count * 3],
+        uvs: vec![0.0; vertex_count * 2],
+        tangents: vec![0.0; vertex_count * 4],
+        colors: Vec::new(),
+        indices: IndexBuffer::U16(Vec::new()),
+        strip: Vec::new(),
+        topology: Topology::Lines,
+        positions,
+    }
+}
+
+/// Assemble an imported mesh. Missing normals are computed from the winding and
+/// missing UVs are zero-filled.
+fn mesh_from_parts(
+    positions: Vec<f32>,
+    normals: Option<Vec<f32>>,
+    uvs: Option<Vec<f32>>,
+    indices: Vec<u32>,
+) -> Mesh {
+    let vertex_count = positions.len() / 3;
+    let uvs = uvs.unwrap_or_else(|| vec![0.0; vertex_count * 2]);
+    let indices = IndexBuffer::from_u32(indices, vertex_count);
+    match normals {
+        Some(normals) => {
+            let bounds = compute_bounds(&positions);
+            let mut mesh = Mesh {
+                positions,
+                normals,
+                uvs,
+                tangents: Vec::new(),
+                colors: Vec::new(),
+                indices,
+                strip: Vec::new(),
+                bounds,
+                topology: Topology::Triangles,
+            };
+            mesh.recompute_tangents();
+            mesh
+        }
+        None => mesh_from_positions_indices(positions, indices, uvs),
+    }
+}
+
+fn flat_shaded(mesh: &Mesh) -> Mesh {
+    let corners = mesh.corner_indices();
+
+    let mut positions = Vec::with_capacity(corners.len() * 3);
+    let mut normals = Vec::with_capacity(corners.len() * 3);
+    let mut uvs = Vec::with_capacity(corners.len() * 2);
+    let mut colors = Vec::new();
+    for tri in corners.chunks_exact(3) {
+        let (a, b, c) = (
+            mesh.position(tri[0]),
+            mesh.position(tri[1]),
+            mesh.position(tri[2]),
+        );
+        let n = b.sub(a).cross(c.sub(a)).normalize();
+        for v in [a, b, c] {
+            positions.extend_from_slice(&[v.x, v.y, v.z]);
+            normals.extend_from_slice(&[n.x, n.y, n.z]);
+        }
+        for &i in tri {
+            uvs.extend_from_slice(&mesh.uvs[i * 2..i * 2 + 2]);
+            if !mesh.colors.is_empty() {
+                colors.extend_from_slice(&mesh.colors[i * 3..i * 3 + 3]);
+            }
+        }
+    }
+
+    // Keep an index buffer so wireframe edges still pair up per triangle.
+    let vertex_count = positions.len() / 3;
+    let sequential: Vec<u32> = (0..vertex_count as u32).collect();
+    let tangents = compute_tangents(&positions, &normals, &uvs, &sequential);
+    let indices = IndexBuffer::from_u32(sequential, vertex_count);
+    Mesh {
+        positions,
+        normals,
+        uvs,
+        tangents,
+        colors,
+        indices,
+        strip: Vec::new(),
+        bounds: mesh.bounds,
+        topology: Topology::Triangles,
+    }
+}
+
+/// Rebuild the mesh with `compute_normals_with_crease` normals, copying the other vertex
+/// attributes from the vertices each new one was split from.
+fn crease_shaded(mesh: &Mesh, crease_radians: f32) -> Mesh {
+    let corners = mesh.corner_indices();
+    let split = compute_normals_with_crease(&mesh.positions, &corners, crease_radians);
+
+    let mut positions = Vec::with_capacity(split.sources.len() * 3);
+    let mut uvs = Vec::with_capacity(split.sources.len() * 2);
+    let mut colors = Vec::new();
+    for &i in &split.sources {
+        positions.extend_from_slice(&mesh.positions[i * 3..i * 3 + 3]);
+        uvs.extend_from_slice(&mesh.uvs[i * 2..i * 2 + 2]);
+        if !mesh.colors.is_empty() {
+            colors.extend_from_slice(&mesh.colors[i * 3..i * 3 + 3]);
+        }
+    }
+
+    let vertex_count = split.sources.len();
+    let tangents = compute_tangents(&positions, &split.normals, &uvs, &split.indices);
+    Mesh {
+        positions,
+        normals: split.normals,
+        uvs,
+        tangents,
+        colors,
+        indices: IndexBuffer::from_u32(split.indices, vertex_count),
+        strip: Vec::new(),
+        bounds: mesh.bounds,
+        topology: Topology::Triangles,
+    }
+}
+
+/// Box-mapped texture coordinates for a triangle list (three vertices per triangle, no
+/// index buffer). Each triangle is projected onto the side of the bounding box its summed
+/// vertex normals point at most, so seams fall only where that side changes. UVs count in
+/// units of the longest side of the bounds, so every side has the same texel size.
+pub(crate) fn box_project_uvs(positions: &[f32], normals: &[f32]) -> Vec<f32> {
+    let bounds = compute_bounds(positions);
+    let mut uvs = Vec::with_capacity(positions.len() / 3 * 2);
+    for (tri, n) in positions.chunks_exact(9).zip(normals.chunks_exact(9)) {
+        let sum: [f32; 3] = std::array::from_fn(|k| n[k] + n[k + 3] + n[k + 6]);
+        let axis = (0..3)
+            .max_by(|&a, &b| sum[a].abs().total_cmp(&sum[b].abs()))
+            .unwrap();
+        for p in tri.chunks_exact(3) {
+            uvs.extend_from_slice(&project_uv(&bounds, p, axis, sum[axis] >= 0.0));
+        }
+    }
+    uvs
+}
+
+/// `p` projected along `axis` onto the side of `bounds` facing `positive`, oriented as seen
+/// from outside so textures aren't mirrored, in units of the longest side of the bounds.
+fn project_uv(bounds: &Bounds, p: &[f32], axis: usize, positive: bool) -> [f32; 2] {
+    let size = bounds.max.sub(bounds.min);
+    let unit = size.x.max(size.y).max(size.z).max(f32::EPSILON);
+    let (min, max) = (bounds.min, bounds.max);
+    // Distance from the low and from the high side along each axis.
+    let near = [
+        (p[0] - min.x) / unit,
+        (p[1] - min.y) / unit,
+        (p[2] - min.z) / unit,
+    ];
+    let far = [
+        (max.x - p[0]) / unit,
+        (max.y - p[1]) / unit,
+        (max.z - p[2]) / unit,
+    ];
+    match (axis, positive) {
+        (0, true) => [far[2], near[1]],
+        (0, false) => [near[2], near[1]],
+        (1, true) => [near[0], far[2]],
+        (1, false) => [near[0], near[2]],
+        (_, true) => [near[0], near[1]],
+        (_, false) => [far[0], near[1]],
+    }
+}
+
+fn compute_bounds(positions: &[f32]) -> Bounds {
+    if positions.len() < 3 {
+        return Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+    }
+    let first = Vec3::new(positions[0], positions[1], positions[2]);
+    let (min, max) = positions
+        .chunks_exact(3)
+        .fold((first, first), |(min, max), v| {
+            let p = Vec3::new(v[0], v[1], v[2]);
+            (min.min(p), max.max(p))
+        });
+    Bounds::new(min, max)
+}
+
+/// Approximate minimal bounding sphere (Ritter): seed from a far-apart pair, then grow
+/// the sphere just enough to take in each outlying point. Within a few percent of optimal.
+pub(crate) fn bounding_sphere(positions: &[f32]) -> (Vec3, f32) {
+    let points: Vec<Vec3> = positions
+        .chunks_exact(3)
+        .map(|v| Vec3::new(v[0], v[1], v[2]))
+        .collect();
+    let Some(&first) = points.first() else {
+        return (Vec3::new(0.0, 0.0, 0.0), 0.0);
+    };
+    let farthest_from = |p: Vec3| {
+        points
+            .iter()
+            .copied()
+            .max_by(|a, b| a.sub(p).length().total_cmp(&b.sub(p).length()))
+            .unwrap_or(p)
+    };
+    let y = farthest_from(first);
+    let z = farthest_from(y);
+
+    let mut center = y.add(z).mul(0.5);
+    let mut radius = z.sub(y).length() * 0.5;
+    for &p in &points {
+        let d = p.sub(center).length();
+        if d > radius {
+            // Move the center toward p so the new sphere touches p and the old far side.
+            let new_radius = (radius + d) * 0.5;
+            center = center.add(p.sub(center).mul((new_radius - radius) / d));
+            radius = new_radius;
+        }
+    }
+    (center, radius)
+}
+
+/// Compute per-vertex normals by averaging adjacent triangle normals.
+/// Handles both indexed and non-indexed geometry.
+fn compute_normals<I: Copy + Into<u32>>(positions: &[f32], indices: &[I]) -> Vec<f32> {
+    let mut normals = vec![0.0f32; positions.len()];
+
+    if indices.is_empty() {
+        // Non-indexed: assume triangles laid out sequentially.
+        for (tri_idx, tri) in positions.chunks_exact(9).enumerate() {
+            let a = Vec3::new(tri[0], tri[1], tri[2]);
+            let b = Vec3::new(tri[3], tri[4], tri[5]);
+            let c = Vec3::new(tri[6], tri[7], tri[8]);
+            let n = b.sub(a).cross(c.sub(a)).normalize();
+            let base = tri_idx * 9;
+            for v in 0..3 {
+                let dst = base + v * 3;
+                normals[dst] += n.x;
+                normals[dst + 1] += n.y;
+                normals[dst + 2] += n.z;
+            }
+        }
+    } else {
+        // Indexed: accumulate face normals for each referenced vertex.
+        for idx in indices.chunks_exact(3) {
+            let ia = idx[0].into() as usize * 3;
+            let ib = idx[1].into() as usize * 3;
+            let ic = idx[2].into() as usize * 3;
+            if ic + 2 >= positions.len() {
+                continue;
+            }
+            let a = Vec3::new(positions[ia], positions[ia + 1], positions[ia + 2]);
+            let b = Vec3::new(positions[ib], positions[ib + 1], positions[ib + 2]);
+            let c = Vec3::new(positions[ic], positions[ic + 1], positions[ic + 2]);
+            let n = b.sub(a).cross(c.sub(a)).normalize();
+            for &i in &[ia, ib, ic] {
+                normals[i] += n.x;
+                normals[i + 1] += n.y;
+                normals[i + 2] += n.z;
+            }
+        }
+    }
+
+    // Normalize the accumulated normals; default to +Z when degenerate.
+    for n in normals.chunks_exact_mut(3) {
+        let v = Vec3::new(n[0], n[1], n[2]).normalize();
         if v.length() <= 1e-8 {
             n[0] = 0.0;
             n[1] = 0.0;
@@ -306,3 +2023,1139 @@ fn compute_normals(positions: &[f32], indices: &[u16]) -> Vec<f32> {
     normals
 }
 
+/// Vertices re-split by `compute_normals_with_crease`.
+pub(crate) struct CreaseNormals {
+    /// Input vertex each output vertex copies its position and other attributes from.
+    pub sources: Vec<usize>,
+    /// Unit normal (x,y,z) per output vertex.
+    pub normals: Vec<f32>,
+    /// The input triangles, re-pointed at the output vertices.
+    pub indices: Vec<u32>,
+}
+
+/// Smooth normals that keep hard edges: each triangle corner averages the face normals
+/// of the triangles around the same position (coincident vertices count as one, so
+/// meshes split per face like `cube` smooth too) that lie within `crease_radians` of its
+/// own face. Corners of a vertex that end up with different normals become separate
+/// vertices; the rest stay shared.
+pub(crate) fn compute_normals_with_crease(
+    positions: &[f32],
+    corners: &[usize],
+    crease_radians: f32,
+) -> CreaseNormals {
+    let position =
+        |i: usize| Vec3::new(positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]);
+    let face_normals: Vec<Vec3> = corners
+        .chunks_exact(3)
+        .map(|t| {
+            let (a, b, c) = (position(t[0]), position(t[1]), position(t[2]));
+            let n = b.sub(a).cross(c.sub(a));
+            // Degenerate triangles contribute nothing.
+            if n.length() <= 1e-12 {
+                n.mul(0.0)
+            } else {
+                n.normalize()
+            }
+        })
+        .collect();
+
+    // Triangles around each distinct position; `+ 0.0` folds -0.0 into 0.0.
+    let key = |i: usize| {
+        let p = position(i);
+        [
+            (p.x + 0.0).to_bits(),
+            (p.y + 0.0).to_bits(),
+            (p.z + 0.0).to_bits(),
+        ]
+    };
+    let mut faces_at: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+    for (corner, &i) in corners.iter().enumerate() {
+        faces_at.entry(key(i)).or_default().push(corner / 3);
+    }
+
+    let cos_limit = crease_radians.clamp(0.0, std::f32::consts::PI).cos();
+    let mut split = CreaseNormals {
+        sources: Vec::new(),
+        normals: Vec::new(),
+        indices: Vec::with_capacity(corners.len()),
+    };
+    let mut vertex_of: HashMap<(usize, [u32; 3]), u32> = HashMap::new();
+    for (corner, &i) in corners.iter().enumerate() {
+        let own = face_normals[corner / 3];
+        let sum = faces_at[&key(i)]
+            .iter()
+            .map(|&t| face_normals[t])
+            .filter(|n| n.dot(own) >= cos_limit)
+            .fold(Vec3::new(0.0, 0.0, 0.0), Vec3::add);
+        let n = if sum.length() <= 1e-8 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            sum.normalize()
+        };
+        let index = *vertex_of
+            .entry((i, [n.x.to_bits(), n.y.to_bits(), n.z.to_bits()]))
+            .or_insert_with(|| {
+                split.sources.push(i);
+                split.normals.extend_from_slice(&[n.x, n.y, n.z]);
+                (split.sources.len() - 1) as u32
+            });
+        split.indices.push(index);
+    }
+    split
+}
+
+/// Vertices the `optimize_vertex_cache` model keeps cached; a little above real GPUs so
+/// the order holds up on all of them.
+const FORSYTH_CACHE_SIZE: usize = 32;
+
+/// Reorder triangles for the GPU's post-transform vertex cache with Tom Forsyth's
+/// linear-speed algorithm: greedily emit the triangle whose vertices score best, favouring
+/// vertices still in a modelled LRU cache and those with few triangles left (so no
+/// lonely triangles are stranded). Each triangle keeps its corners and winding; only
+/// the triangle order changes. `indices` is a triangle list over `vertex_count` vertices.
+pub(crate) fn optimize_vertex_cache<I: Copy + Into<u32>>(indices: &mut [I], vertex_count: usize) {
+    let triangle_count = indices.len() / 3;
+    if triangle_count < 2 {
+        return;
+    }
+    let corner = |t: usize, k: usize| indices[t * 3 + k].into() as usize;
+
+    // Triangles around each vertex, as ranges into one shared list; `remaining[v]` of
+    // them are not emitted yet and sit at the front of the vertex's range.
+    let mut offsets = vec![0usize; vertex_count + 1];
+    for t in 0..triangle_count {
+        for k in 0..3 {
+            offsets[corner(t, k) + 1] += 1;
+        }
+    }
+    for v in 0..vertex_count {
+        offsets[v + 1] += offsets[v];
+    }
+    let mut remaining: Vec<usize> = (0..vertex_count)
+        .map(|v| offsets[v + 1] - offsets[v])
+        .collect();
+    let mut adjacent = vec![0usize; offsets[vertex_count]];
+    let mut fill = offsets.clone();
+    for t in 0..triangle_count {
+        for k in 0..3 {
+            let v = corner(t, k);
+            adjacent[fill[v]] = t;
+            fill[v] += 1;
+        }
+    }
+
+    let vertex_score = |cache_pos: Option<usize>, remaining: usize| -> f32 {
+        if remaining == 0 {
+            return -1.0;
+        }
+        let cached = match cache_pos {
+            // The last triangle's corners score a fixed amount so it isn't just redrawn.
+            Some(p) if p < 3 => 0.75,
+            Some(p) => (1.0 - (p - 3) as f32 / (FORSYTH_CACHE_SIZE - 3) as f32).powf(1.5),
+            None => 0.0,
+        };
+        cached + 2.0 / (remaining as f32).sqrt()
+    };
+    let mut score: Vec<f32> = (0..vertex_count)
+        .map(|v| vertex_score(None, remaining[v]))
+        .collect();
+    let triangle_score =
+        |score: &[f32], t: usize| -> f32 { (0..3).map(|k| score[corner(t, k)]).sum() };
+    let mut emitted = vec![false; triangle_count];
+
+    let mut order = Vec::with_capacity(triangle_count);
+    let mut cache: Vec<usize> = Vec::with_capacity(FORSYTH_CACHE_SIZE + 3);
+    let mut best = (0..triangle_count)
+        .max_by(|&a, &b| triangle_score(&score, a).total_cmp(&triangle_score(&score, b)));
+    // Where to resume, in input order, once the cache has no triangles left to offer.
+    let mut scan_from = 0;
+    while let Some(t) = best {
+        emitted[t] = true;
+        order.push(t);
+        let tri = [corner(t, 0), corner(t, 1), corner(t, 2)];
+        for &v in &tri {
+            // Move the triangle past the vertex's still-pending ones.
+            let range = &mut adjacent[offsets[v]..offsets[v] + remaining[v]];
+            if let Some(i) = range.iter().position(|&a| a == t) {
+                range.swap(i, remaining[v] - 1);
+            }
+            remaining[v] -= 1;
+        }
+
+        // The triangle's corners move to the front; the rest shift back, and whatever
+        // falls off the end loses its cache bonus.
+        let mut next: Vec<usize> = Vec::with_capacity(cache.len() + 3);
+        for v in tri.into_iter().chain(cache.iter().copied()) {
+            if !next.contains(&v) {
+                next.push(v);
+            }
+        }
+        for &v in next.iter().skip(FORSYTH_CACHE_SIZE) {
+            score[v] = vertex_score(None, remaining[v]);
+        }
+        next.truncate(FORSYTH_CACHE_SIZE);
+        cache = next;
+        for (p, &v) in cache.iter().enumerate() {
+            score[v] = vertex_score(Some(p), remaining[v]);
+        }
+
+        best = None;
+        let mut best_score = f32::NEG_INFINITY;
+        for &v in &cache {
+            for &a in &adjacent[offsets[v]..offsets[v] + remaining[v]] {
+                let s = triangle_score(&score, a);
+                if s > best_score {
+                    best_score = s;
+                    best = Some(a);
+                }
+            }
+        }
+        if best.is_none() {
+            while scan_from < triangle_count && emitted[scan_from] {
+                scan_from += 1;
+            }
+            best = (scan_from < triangle_count).then_some(scan_from);
+        }
+    }
+
+    let original: Vec<I> = indices.to_vec();
+    for (slot, &t) in order.iter().enumerate() {
+        indices[slot * 3..slot * 3 + 3].copy_from_slice(&original[t * 3..t * 3 + 3]);
+    }
+}
+
+/// Reorder a triangle list into one `TRIANGLE_STRIP` drawing the same triangles with the
+/// same winding. Runs of triangles that each share an edge with the previous one (like
+/// the quads along a grid row) cost one index per triangle; elsewhere the strip restarts,
+/// joined by repeated indices whose zero-area triangles the GPU discards. Large grids
+/// come out at about a third of the list's size; arbitrary meshes can come out larger.
+pub(crate) fn to_triangle_strip(indices: &[u16]) -> Vec<u16> {
+    let triangles: Vec<[u16; 3]> = indices
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+    let mut strip: Vec<u16> = Vec::with_capacity(indices.len() / 2);
+    for (i, &tri) in triangles.iter().enumerate() {
+        if let Some(v) = strip_continuation(&strip, tri) {
+            strip.push(v);
+            continue;
+        }
+        // Restart with whichever rotation and parity lets the next triangle continue.
+        let next = triangles.get(i + 1).copied();
+        let start = (0..6)
+            .map(|option| {
+                let s = [
+                    tri[option % 3],
+                    tri[(option + 1) % 3],
+                    tri[(option + 2) % 3],
+                ];
+                // Odd-numbered strip triangles read their first two vertices swapped.
+                let odd = option >= 3;
+                (odd, if odd { [s[1], s[0], s[2]] } else { s })
+            })
+            .find(|(odd, seq)| {
+                let mut probe = if *odd { vec![seq[0]] } else { Vec::new() };
+                probe.extend_from_slice(seq);
+                next.is_some_and(|n| strip_continuation(&probe, n).is_some())
+            })
+            .unwrap_or((false, tri));
+        let (odd, seq) = start;
+        if let Some(&last) = strip.last() {
+            strip.push(last);
+            // The bridge copy of `seq[0]` below must land at an index of the other parity.
+            if strip.len() % 2 == usize::from(odd) {
+                strip.push(last);
+            }
+            strip.push(seq[0]);
+        } else if odd {
+            strip.push(seq[0]);
+        }
+        strip.extend_from_slice(&seq);
+    }
+    strip
+}
+
+/// The vertex that appends `tri` to `strip` with its winding intact, if any.
+fn strip_continuation(strip: &[u16], tri: [u16; 3]) -> Option<u16> {
+    let [.., x, y] = *strip else {
+        return None;
+    };
+    // Strip triangle k is (k, k+1, k+2), with the first two swapped for odd k.
+    let (a, b) = if strip.len().is_multiple_of(2) {
+        (x, y)
+    } else {
+        (y, x)
+    };
+    (0..3)
+        .find(|&r| tri[r] == a && tri[(r + 1) % 3] == b)
+        .map(|r| tri[(r + 2) % 3])
+}
+
+/// Convert triangle indices into line-segment pairs, one per unique edge.
+/// Edges shared by neighbouring triangles are emitted once, in first-seen order.
+pub(crate) fn build_wireframe_indices<T: Copy + Ord + std::hash::Hash>(indices: &[T]) -> Vec<T> {
+    let mut seen = std::collections::HashSet::with_capacity(indices.len());
+    let mut out = Vec::with_capacity(indices.len());
+    for tri in indices.chunks_exact(3) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            if seen.insert((a.min(b), a.max(b))) {
+                out.extend_from_slice(&[a, b]);
+            }
+        }
+    }
+    out
+}
+
+/// Per-vertex tangents (x,y,z,w) using Lengyel's method: accumulate the UV-space
+/// derivatives of each triangle, then Gram-Schmidt against the normal.
+/// `w` is the bitangent handedness (+1 or -1).
+pub(crate) fn compute_tangents<I: Copy + Into<u32>>(
+    positions: &[f32],
+    normals: &[f32],
+    uvs: &[f32],
+    indices: &[I],
+) -> Vec<f32> {
+    let vertex_count = positions.len() / 3;
+    let mut tan1 = vec![Vec3::default(); vertex_count];
+    let mut tan2 = vec![Vec3::default(); vertex_count];
+
+    let corners: Vec<usize> = if indices.is_empty() {
+        (0..vertex_count).collect()
+    } else {
+        indices.iter().map(|&i| i.into() as usize).collect()
+    };
+
+    let pos = |i: usize| Vec3::new(positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]);
+    let uv = |i: usize| (uvs[i * 2], uvs[i * 2 + 1]);
+
+    for tri in corners.chunks_exact(3) {
+        let (i0, i1, i2) = (tri[0], tri[1], tri[2]);
+        if i0.max(i1).max(i2) >= vertex_count || uvs.len() < vertex_count * 2 {
+            continue;
+        }
+        let e1 = pos(i1).sub(pos(i0));
+        let e2 = pos(i2).sub(pos(i0));
+        let (u0, v0) = uv(i0);
+        let (du1, dv1) = (uv(i1).0 - u0, uv(i1).1 - v0);
+        let (du2, dv2) = (uv(i2).0 - u0, uv(i2).1 - v0);
+
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() <= 1e-12 {
+            continue;
+        }
+        let r = 1.0 / det;
+        let sdir = e1.mul(dv2).sub(e2.mul(dv1)).mul(r);
+        let tdir = e2.mul(du1).sub(e1.mul(du2)).mul(r);
+        for i in [i0, i1, i2] {
+            tan1[i] = tan1[i].add(sdir);
+            tan2[i] = tan2[i].add(tdir);
+        }
+    }
+
+    let mut tangents = Vec::with_capacity(vertex_count * 4);
+    for i in 0..vertex_count {
+        let n = Vec3::new(normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]);
+        let t = tan1[i];
+        let mut ortho = t.sub(n.mul(n.dot(t))).normalize();
+        if ortho.length() <= 1e-8 {
+            // No UV gradient here: any unit vector perpendicular to the normal will do.
+            let axis = if n.x.abs() < 0.9 {
+                Vec3::new(1.0, 0.0, 0.0)
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            };
+            ortho = axis.sub(n.mul(n.dot(axis))).normalize();
+        }
+        let w = if n.cross(t).dot(tan2[i]) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        tangents.extend_from_slice(&[ortho.x, ortho.y, ortho.z, w]);
+    }
+    tangents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesh_from_raw_accepts_indexed_and_non_indexed() {
+        let quad = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let indexed = mesh_from_raw(&quad, &[0, 1, 2, 0, 2, 3]).unwrap();
+        assert_eq!(indexed.indices.len(), 6);
+        assert_eq!(indexed.normals.len(), quad.len());
+
+        let soup = mesh_from_raw(&quad[..9], &[]).unwrap();
+        assert!(soup.indices.is_empty());
+        assert_eq!(soup.normals.len(), 9);
+        assert!((soup.bounds.max.x - 1.0).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn vertex_colors_follow_the_vertices() {
+        let tri = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let rgb = vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let mut mesh = mesh_from_raw(&tri, &[0, 1, 2]).unwrap();
+        assert!(mesh.colors.is_empty());
+        mesh.set_colors(rgb.clone()).unwrap();
+        assert_eq!(mesh.colors.len(), mesh.positions.len());
+        assert_eq!(mesh.with_normal_mode(NormalMode::Flat).colors, rgb);
+
+        // One midpoint per edge, each the average of its endpoints.
+        let fine = subdivide_loop(&mesh, 1);
+        assert_eq!(fine.colors.len(), fine.positions.len());
+        assert_eq!(fine.colors[9..12], [0.5, 0.5, 0.0]);
+
+        assert!(mesh.set_colors(vec![1.0; 6]).is_err());
+        mesh.set_colors(Vec::new()).unwrap();
+        assert!(mesh.colors.is_empty());
+    }
+
+    #[test]
+    fn mesh_from_raw_rejects_bad_input() {
+        assert!(mesh_from_raw(&[0.0, 1.0], &[]).is_err());
+        assert!(mesh_from_raw(&[0.0; 9], &[0, 1]).is_err());
+        assert!(mesh_from_raw(&[0.0; 9], &[0, 1, 3]).is_err());
+    }
+
+    #[test]
+    fn unit_cube_stats_count_its_buffers() {
+        let corners: Vec<f32> = (0..8)
+            .flat_map(|i| [(i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32])
+            .collect();
+        let indices = [
+            0, 2, 1, 1, 2, 3, 4, 5, 6, 5, 7, 6, 0, 1, 4, 1, 5, 4, //
+            2, 6, 3, 3, 6, 7, 0, 4, 2, 2, 4, 6, 1, 3, 5, 3, 7, 5,
+        ];
+        let mesh = mesh_from_raw(&corners, &indices).unwrap();
+        let stats = mesh.stats();
+        assert_eq!(stats.vertices, 8);
+        assert_eq!(stats.triangles, 12);
+        // Positions and normals (3 floats), UVs (2) and tangents (4); 16-bit indices.
+        let floats = 8 * (3 + 3 + 2 + 4);
+        assert_eq!(stats.bytes, (floats * 4 + 36 * 2) as u64);
+
+        // The built-in cube splits its corners per face for flat normals.
+        let split = cube().stats();
+        assert_eq!((split.vertices, split.triangles), (24, 12));
+        let points = Mesh {
+            topology: Topology::Points,
+            ..cube()
+        };
+        assert_eq!(points.stats().triangles, 0);
+    }
+
+    #[test]
+    fn plane_normals_point_up() {
+        let mesh = plane(2.0, 3.0, 4);
+        assert_eq!(mesh.positions.len(), 5 * 5 * 3);
+        for n in mesh.normals.chunks_exact(3) {
+            assert_eq!(n, [0.0, 1.0, 0.0]);
+        }
+        assert!((mesh.bounds.max.z - 1.5).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn detailed_primitives_follow_the_requested_segments() {
+        let vertices = |m: &Mesh| m.positions.len() / 3;
+        let default = make_primitive(Primitive::Sphere);
+        let fine = make_primitive_detailed(Primitive::Sphere, 64, 32);
+        assert!(vertices(&fine) > vertices(&default));
+        assert_eq!(vertices(&fine), 65 * 33);
+        // Below the minimum of 3 x 2 segments.
+        assert_eq!(
+            vertices(&make_primitive_detailed(Primitive::Sphere, 1, 0)),
+            4 * 3
+        );
+        assert_eq!(
+            vertices(&make_primitive_detailed(Primitive::Torus, 0, 0)),
+            4 * 4
+        );
+        // Capped to what u16 indices address.
+        let huge = make_primitive_detailed(Primitive::Torus, 10_000, 10_000);
+        assert_eq!(vertices(&huge), 256 * 256);
+    }
+
+    #[test]
+    fn triangle_strip_keeps_every_triangle_and_its_winding() {
+        // Rotate each triangle to start at its smallest index so windings compare.
+        let canonical = |t: [u16; 3]| {
+            let r = (0..3).min_by_key(|&r| t[r]).unwrap();
+            [t[r], t[(r + 1) % 3], t[(r + 2) % 3]]
+        };
+        let from_list = |ix: &[u16]| {
+            let mut tris: Vec<_> = ix
+                .chunks_exact(3)
+                .map(|t| canonical([t[0], t[1], t[2]]))
+                .collect();
+            tris.sort_unstable();
+            tris
+        };
+        let from_strip = |strip: &[u16]| {
+            let mut tris: Vec<_> = strip
+                .windows(3)
+                .enumerate()
+                .filter(|(_, w)| w[0] != w[1] && w[1] != w[2] && w[0] != w[2])
+                .map(|(k, w)| {
+                    let odd = !k.is_multiple_of(2);
+                    canonical(if odd {
+                        [w[1], w[0], w[2]]
+                    } else {
+                        [w[0], w[1], w[2]]
+                    })
+                })
+                .collect();
+            tris.sort_unstable();
+            tris
+        };
+
+        for mesh in [
+            plane(1.0, 1.0, 4),
+            sphere(0.5, 8, 4),
+            torus(0.6, 0.2, 8, 6),
+            cube(),
+        ] {
+            let IndexBuffer::U16(ix) = &mesh.indices else {
+                panic!("16-bit indices");
+            };
+            let strip = to_triangle_strip(ix);
+            assert_eq!(from_strip(&strip), from_list(ix));
+        }
+
+        // One row of quads needs two indices per quad plus two to start.
+        let strip = to_triangle_strip(&[0, 4, 1, 1, 4, 5, 1, 5, 2, 2, 5, 6]);
+        assert_eq!(strip.len(), 6);
+        // A 4x4 plane: four rows of 10, joined by two or three repeated indices each.
+        let plane = plane(1.0, 1.0, 4);
+        assert!(plane.strip.len() <= 4 * 10 + 3 * 3);
+        assert!(plane.strip.len() * 2 < plane.indices.len());
+    }
+
+    #[test]
+    fn uncapped_cylinder_is_only_side_quads() {
+        let capped = cylinder(0.5, 1.0, 8);
+        let open = cylinder_with_caps(0.5, 1.0, 8, false);
+        // Two cap rings of 8 plus their two centers are gone.
+        assert_eq!(
+            capped.positions.len() / 3 - open.positions.len() / 3,
+            2 * 8 + 2
+        );
+        assert_eq!(open.positions.len() / 3, 9 * 2);
+        // Both cap fans (8 triangles each) are gone; 8 side quads remain.
+        assert_eq!(capped.indices.len() - open.indices.len(), 2 * 8 * 3);
+        assert_eq!(open.indices.len(), 8 * 6);
+        // Side normals are horizontal.
+        assert!(open.normals.chunks_exact(3).all(|n| n[1].abs() <= 1e-6));
+    }
+
+    #[test]
+    fn flat_parametric_surface_has_planar_normals() {
+        let mesh = parametric(4, 3, |u, v| Vec3::new(u * 2.0 - 1.0, v, 0.0));
+        assert_eq!(mesh.positions.len() / 3, 5 * 4);
+        assert_eq!(mesh.indices.len(), 4 * 3 * 6);
+        // d/du = +x and d/dv = +y, so the front faces +z.
+        for n in mesh.normals.chunks_exact(3) {
+            assert_eq!(n, [0.0, 0.0, 1.0]);
+        }
+        assert_eq!((mesh.bounds.min.x, mesh.bounds.max.y), (-1.0, 1.0));
+
+        for mesh in [klein_bottle(16, 8), mobius_strip(16, 2)] {
+            assert!(mesh.normals.iter().all(|c| c.is_finite()));
+        }
+    }
+
+    #[test]
+    fn grid_has_two_lines_per_division_step() {
+        let grid = grid_lines(5.0, 10, Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(grid.topology, Topology::Lines);
+        // 11 lines along each axis, two endpoints apiece.
+        assert_eq!(grid.positions.len() / 3, 2 * 11 * 2);
+        assert!(grid.positions.chunks_exact(3).all(|p| p[1] == 0.0));
+        assert_eq!(grid.bounds.max.x, 5.0);
+        assert_eq!(grid.bounds.min.z, -5.0);
+        assert!(grid.corner_indices().is_empty());
+    }
+
+    #[test]
+    fn normal_lines_run_from_each_vertex_along_its_normal() {
+        let positions = [0.0, 0.0, 0.0, 1.0, 2.0, 3.0, -1.0, 0.0, 1.0];
+        let normals = [0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0];
+        let lines = normal_lines(&positions, &normals, 0.5);
+        assert_eq!(lines.topology, Topology::Lines);
+        assert_eq!(lines.positions.len() / 3, 2 * 3);
+        assert_eq!(
+            lines.positions,
+            vec![
+                0.0, 0.0, 0.0, 0.0, 0.5, 0.0, //
+                1.0, 2.0, 3.0, 1.5, 2.0, 3.0, //
+                -1.0, 0.0, 1.0, -1.0, 0.0, 0.5,
+            ]
+        );
+    }
+
+    #[test]
+    fn polyline_has_one_segment_fewer_than_points() {
+        let points = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 2.0];
+        let line = polyline(&points);
+        assert_eq!(line.topology, Topology::LineStrip);
+        assert!(line.indices.is_empty());
+        assert_eq!(line.segments(), vec![[0, 1], [1, 2], [2, 3]]);
+        assert_eq!(line.bounds.max.z, 2.0);
+        assert!(polyline(&points[..3]).segments().is_empty());
+        // The same points as a line list pair up instead.
+        assert_eq!(line_list(points.to_vec()).segments(), vec![[0, 1], [2, 3]]);
+
+        let quads = line_quads(&line);
+        assert_eq!(quads.sides.len(), 3 * 6);
+        assert_eq!(quads.positions.len(), quads.others.len());
+        // The first quad spans segment 0 -> 1, pushed both ways at each end.
+        assert_eq!(quads.positions[..3], [0.0, 0.0, 0.0]);
+        assert_eq!(quads.others[..3], [1.0, 0.0, 0.0]);
+        assert_eq!(quads.sides[..6], [1.0, -1.0, 1.0, 1.0, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn axes_are_colored_by_direction() {
+        let gizmo = axes(2.0);
+        assert_eq!(gizmo.topology, Topology::Lines);
+        assert_eq!(gizmo.positions.len() / 3, 6);
+        for (axis, segment) in gizmo.positions.chunks_exact(6).enumerate() {
+            assert_eq!(segment[..3], [0.0; 3]);
+            let tip = &segment[3..];
+            let colors = &gizmo.colors[axis * 6..axis * 6 + 6];
+            for k in 0..3 {
+                let expected = if k == axis { 1.0 } else { 0.0 };
+                assert_eq!(tip[k], expected * 2.0);
+                assert_eq!((colors[k], colors[3 + k]), (expected, expected));
+            }
+        }
+    }
+
+    #[test]
+    fn single_segment_plane_is_one_quad() {
+        let mesh = plane(1.0, 1.0, 1);
+        assert_eq!(mesh.positions.len() / 3, 4);
+        assert_eq!(mesh.indices.len(), 6);
+    }
+
+    #[test]
+    fn primitive_normals_point_outward() {
+        let meshes = [
+            cube(),
+            cylinder(0.5, 1.0, 16),
+            cone(0.5, 1.0, 16),
+            sphere(0.5, 16, 8),
+            icosphere(0.5, 1),
+        ];
+        for mesh in meshes {
+            for (p, n) in mesh
+                .positions
+                .chunks_exact(3)
+                .zip(mesh.normals.chunks_exact(3))
+            {
+                let d = Vec3::new(p[0], p[1], p[2]).dot(Vec3::new(n[0], n[1], n[2]));
+                assert!(d > 0.0, "inward normal {n:?} at {p:?}");
+            }
+        }
+        // Torus normals point away from the tube's center ring.
+        let t = torus(0.6, 0.2, 16, 8);
+        for (p, n) in t.positions.chunks_exact(3).zip(t.normals.chunks_exact(3)) {
+            let ring = Vec3::new(p[0], 0.0, p[2]).normalize().mul(0.6);
+            let d = Vec3::new(p[0], p[1], p[2])
+                .sub(ring)
+                .dot(Vec3::new(n[0], n[1], n[2]));
+            assert!(d > 0.0, "inward normal {n:?} at {p:?}");
+        }
+    }
+
+    #[test]
+    fn cone_apex_and_base_extent() {
+        let mesh = cone(0.75, 2.0, 32);
+        let apex = &mesh.positions[32 * 3..32 * 3 + 3];
+        assert_eq!(apex, [0.0, 1.0, 0.0]);
+
+        assert!((mesh.bounds.max.x - 0.75).abs() <= 1e-6);
+        assert!((mesh.bounds.min.x + 0.75).abs() <= 1e-6);
+        assert!((mesh.bounds.max.y - 1.0).abs() <= 1e-6);
+        assert!((mesh.bounds.min.y + 1.0).abs() <= 1e-6);
+        assert!(mesh.bounds.radius() >= 0.75);
+
+        // Base cap faces down.
+        let base = &mesh.normals[33 * 3..33 * 3 + 3];
+        assert!(base[1] < -0.99);
+    }
+
+    #[test]
+    fn capsule_height_and_welded_seams() {
+        let (radius, cyl_h, u) = (0.3, 0.8, 16);
+        let mesh = capsule(radius, cyl_h, u, 4);
+
+        let height = mesh.bounds.max.y - mesh.bounds.min.y;
+        assert!((height - (cyl_h + 2.0 * radius)).abs() <= 1e-5);
+
+        // Exactly one ring (u + 1 vertices incl. the longitude seam) per joint.
+        for y in [cyl_h * 0.5, -cyl_h * 0.5] {
+            let ring = mesh
+                .positions
+                .chunks_exact(3)
+                .filter(|p| (p[1] - y).abs() <= 1e-5)
+                .count();
+            assert_eq!(ring, u as usize + 1);
+        }
+    }
+
+    #[test]
+    fn icosphere_base_is_icosahedron() {
+        let mesh = icosphere(1.0, 0);
+        assert_eq!(mesh.positions.len() / 3, 12);
+        assert_eq!(mesh.indices.len() / 3, 20);
+    }
+
+    #[test]
+    fn icosphere_vertices_lie_on_sphere_without_duplicates() {
+        let radius = 0.75;
+        let mesh = icosphere(radius, 2);
+        // V = 10 * 4^n + 2 when shared edge midpoints are reused.
+        assert_eq!(mesh.positions.len() / 3, 162);
+        assert_eq!(mesh.indices.len() / 3, 320);
+        for p in mesh.positions.chunks_exact(3) {
+            let r = Vec3::new(p[0], p[1], p[2]).length();
+            assert!((r - radius).abs() <= 1e-5, "r = {r}");
+        }
+    }
+
+    #[test]
+    fn primitives_have_one_uv_per_vertex() {
+        let meshes = [
+            triangle(),
+            cube(),
+            cylinder(0.5, 1.0, 16),
+            cone(0.5, 1.0, 16),
+            sphere(0.5, 16, 8),
+            capsule(0.3, 0.6, 16, 4),
+            icosphere(0.5, 1),
+            torus(0.6, 0.2, 16, 8),
+            plane(1.0, 1.0, 2),
+        ];
+        for mesh in meshes {
+            assert_eq!(mesh.uvs.len() / 2, mesh.positions.len() / 3);
+        }
+    }
+
+    #[test]
+    fn sphere_poles_have_axis_normals() {
+        let mesh = sphere(0.5, 16, 8);
+        let mut poles = 0;
+        for (p, n) in mesh
+            .positions
+            .chunks_exact(3)
+            .zip(mesh.normals.chunks_exact(3))
+        {
+            if (p[1].abs() - 0.5).abs() <= 1e-6 {
+                assert_eq!(n, [0.0, p[1].signum(), 0.0]);
+                poles += 1;
+            }
+        }
+        assert_eq!(poles, 2 * 17);
+
+        // No zero-area triangles are left in the pole fans.
+        let corners = mesh.corner_indices();
+        assert_eq!(corners.len(), (16 * 8 * 2 - 2 * 16) * 3);
+        let at = |i: usize| {
+            let p = &mesh.positions[i * 3..i * 3 + 3];
+            Vec3::new(p[0], p[1], p[2])
+        };
+        for t in corners.chunks_exact(3) {
+            let area = at(t[1])
+                .sub(at(t[0]))
+                .cross(at(t[2]).sub(at(t[0])))
+                .length();
+            assert!(area > 1e-6);
+        }
+    }
+
+    #[test]
+    fn sphere_uvs_stay_in_unit_range() {
+        let mesh = sphere(0.5, 32, 16);
+        for uv in &mesh.uvs {
+            assert!((0.0..=1.0).contains(uv), "uv component {uv}");
+        }
+    }
+
+    #[test]
+    fn flip_winding_twice_is_identity_and_reverses_normals() {
+        let mut indices: Vec<u16> = vec![0, 1, 2, 2, 3, 0];
+        flip_winding(&mut indices);
+        assert_eq!(indices, vec![0, 2, 1, 2, 0, 3]);
+        flip_winding(&mut indices);
+        assert_eq!(indices, vec![0, 1, 2, 2, 3, 0]);
+
+        let original = cube();
+        let mut flipped = original.clone();
+        flipped.flip_winding();
+        flipped.recompute_normals();
+        for (a, b) in original
+            .normals
+            .chunks_exact(3)
+            .zip(flipped.normals.chunks_exact(3))
+        {
+            let (a, b) = (Vec3::new(a[0], a[1], a[2]), Vec3::new(b[0], b[1], b[2]));
+            assert!((a.dot(b) + 1.0).abs() <= 1e-5, "{a:?} vs {b:?}");
+        }
+        flipped.flip_winding();
+        assert_eq!(flipped.indices, original.indices);
+    }
+
+    #[test]
+    fn loop_subdivision_quadruples_faces() {
+        #[rustfmt::skip]
+        let tetra = mesh_from_raw(
+            &[1.0, 1.0, 1.0,  -1.0, -1.0, 1.0,  -1.0, 1.0, -1.0,  1.0, -1.0, -1.0],
+            &[0, 1, 2,  0, 3, 1,  0, 2, 3,  1, 3, 2],
+        )
+        .unwrap();
+
+        let once = subdivide_loop(&tetra, 1);
+        assert_eq!(once.positions.len() / 3, 4 + 6); // V + E
+        assert_eq!(once.indices.len() / 3, 16);
+
+        let twice = subdivide_loop(&tetra, 2);
+        assert_eq!(twice.positions.len() / 3, 10 + 24);
+        assert_eq!(twice.indices.len() / 3, 64);
+        // Smoothing pulls the shape inside the original hull.
+        assert!(twice.bounds.radius() < tetra.bounds.radius());
+    }
+
+    #[test]
+    fn loop_subdivision_keeps_boundary_in_plane() {
+        let quad = plane(2.0, 2.0, 1);
+        let smooth = subdivide_loop(&quad, 2);
+        assert_eq!(smooth.indices.len() / 3, quad.indices.len() / 3 * 16);
+        assert!(smooth.positions.chunks_exact(3).all(|v| v[1].abs() <= 1e-6));
+        assert_eq!(smooth.uvs.len() / 2, smooth.positions.len() / 3);
+    }
+
+    #[test]
+    fn simplify_halves_a_sphere_and_keeps_its_size() {
+        let sphere = icosphere(1.0, 3);
+        let faces = sphere.indices.len() / 3;
+        let half = simplify(&sphere, 0.5);
+        let kept = half.indices.len() / 3;
+        assert!(
+            kept * 100 >= faces * 45 && kept * 100 <= faces * 55,
+            "{kept} of {faces}"
+        );
+        assert!(half.positions.len() < sphere.positions.len());
+        assert_eq!(half.normals.len(), half.positions.len());
+
+        let (_, radius) = bounding_sphere(&half.positions);
+        assert!((radius - 1.0).abs() < 0.05, "radius {radius}");
+    }
+
+    #[test]
+    fn simplify_counts_each_removed_face_once() {
+        // A closed sphere loses exactly two faces per collapse, so a correct count of the
+        // live faces stops within one of the target instead of above it.
+        let sphere = icosphere(1.0, 3);
+        let faces = sphere.indices.len() / 3;
+        for ratio in [0.5, 0.2, 0.05, 0.01] {
+            let target = ((faces as f32 * ratio).round() as usize).max(1);
+            let kept = simplify(&sphere, ratio).indices.len() / 3;
+            assert!(kept.abs_diff(target) <= 1, "{kept} for target {target}");
+        }
+    }
+
+    #[test]
+    fn box_uvs_project_the_right_face_onto_yz() {
+        let cube = cube();
+        let corners = cube.corner_indices();
+        let gather = |data: &[f32]| -> Vec<f32> {
+            corners
+                .iter()
+                .flat_map(|&i| data[i * 3..i * 3 + 3].to_vec())
+                .collect()
+        };
+        let (positions, normals) = (gather(&cube.positions), gather(&cube.normals));
+        let uvs = box_project_uvs(&positions, &normals);
+        assert_eq!(uvs.len(), corners.len() * 2);
+
+        let mut right = 0;
+        for (k, n) in normals.chunks_exact(3).enumerate() {
+            if n[0] > 0.5 {
+                right += 1;
+                let p = &positions[k * 3..k * 3 + 3];
+                // Seen from +X, u runs toward -Z and v up +Y.
+                assert_eq!(uvs[k * 2..k * 2 + 2], [0.5 - p[2], p[1] + 0.5]);
+            }
+        }
+        assert_eq!(right, 6);
+        // Each cube face already has its own vertices, so nothing needs splitting.
+        let boxed = cube.with_generated_uvs(UvProjection::Box);
+        assert_eq!(boxed.positions.len(), cube.positions.len());
+    }
+
+    #[test]
+    fn mixed_quad_gets_one_winding() {
+        let positions = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        // The second triangle runs clockwise, along the diagonal in the same direction.
+        let mut indices: Vec<u32> = vec![0, 1, 2, 0, 3, 2];
+        assert_eq!(make_consistent_winding(&mut indices, &positions), 1);
+
+        let mut directed: Vec<(u32, u32)> = indices
+            .chunks_exact(3)
+            .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect();
+        directed.sort_unstable();
+        directed.dedup();
+        assert_eq!(
+            directed.len(),
+            6,
+            "an edge runs the same way twice: {indices:?}"
+        );
+        // The first triangle's winding won the tie; the quad now faces +Z throughout.
+        assert_eq!(indices[..3], [0, 1, 2]);
+        assert_eq!(detect_winding(&positions, &indices), Winding::Ambiguous);
+
+        let IndexBuffer::U16(ix) = cube().indices else {
+            panic!("the cube has 16-bit indices");
+        };
+        assert_eq!(
+            detect_winding(&cube().positions, &ix),
+            Winding::CounterClockwise
+        );
+    }
+
+    #[test]
+    fn weld_merges_triangle_soup_quad() {
+        #[rustfmt::skip]
+        let soup = [
+            0.0, 0.0, 0.0,  1.0, 0.0, 0.0,  1.0, 1.0, 0.0,
+            0.0, 0.0, 0.0,  1.0, 1.0, 0.0,  0.0, 1.0, 0.0,
+        ];
+        let (positions, indices) = weld_vertices(&soup, &[], 1e-5);
+        assert_eq!(positions.len() / 3, 4);
+        assert_eq!(indices, vec![0, 1, 2, 0, 2, 3]);
+
+        // Near-duplicates within epsilon merge too; ones beyond it stay apart.
+        let mut jittered = soup;
+        jittered[9] = 1e-6;
+        assert_eq!(weld_vertices(&jittered, &[], 1e-5).0.len() / 3, 4);
+        assert_eq!(weld_vertices(&jittered, &[], 1e-7).0.len() / 3, 5);
+    }
+
+    #[test]
+    fn vertex_cache_order_keeps_the_triangles() {
+        let IndexBuffer::U16(grid) = plane(1.0, 1.0, 16).indices else {
+            panic!("a 17x17 grid has 16-bit indices");
+        };
+        // Scatter the triangles so consecutive ones rarely share vertices.
+        let count = grid.len() / 3;
+        let mut indices: Vec<u16> = (0..count)
+            .flat_map(|i| grid[(i * 97 % count) * 3..][..3].to_vec())
+            .collect();
+        let scattered = indices.clone();
+        optimize_vertex_cache(&mut indices, 17 * 17);
+
+        assert_eq!(indices.len(), scattered.len());
+        let sorted = |ix: &[u16]| {
+            let mut tris: Vec<&[u16]> = ix.chunks_exact(3).collect();
+            tris.sort();
+            tris.into_iter().flatten().copied().collect::<Vec<u16>>()
+        };
+        assert_eq!(sorted(&indices), sorted(&scattered));
+
+        // Vertices a 16-entry FIFO cache would have to transform again.
+        let misses = |ix: &[u16]| {
+            let mut fifo = std::collections::VecDeque::new();
+            ix.iter()
+                .filter(|&&v| {
+                    if fifo.contains(&v) {
+                        return false;
+                    }
+                    fifo.push_back(v);
+                    if fifo.len() > 16 {
+                        fifo.pop_front();
+                    }
+                    true
+                })
+                .count()
+        };
+        assert!(misses(&indices) * 2 < misses(&scattered));
+    }
+
+    #[test]
+    fn bounding_sphere_of_uv_sphere_is_tight() {
+        let mesh = sphere(0.5, 32, 16);
+        let (center, radius) = bounding_sphere(&mesh.positions);
+        assert!((radius - 0.5).abs() <= 0.01, "radius={radius}");
+        assert!(center.length() <= 0.01, "center={center:?}");
+        // The AABB-derived radius overestimates by sqrt(3).
+        assert!(mesh.bounds.radius() > 0.85);
+
+        for v in mesh.positions.chunks_exact(3) {
+            let d = Vec3::new(v[0], v[1], v[2]).sub(center).length();
+            assert!(d <= radius + 1e-5);
+        }
+    }
+
+    #[test]
+    fn cube_top_face_spans_full_uv_range() {
+        let mesh = cube();
+        let top: Vec<&[f32]> = mesh
+            .normals
+            .chunks_exact(3)
+            .zip(mesh.uvs.chunks_exact(2))
+            .filter(|(n, _)| n[1] > 0.99)
+            .map(|(_, uv)| uv)
+            .collect();
+        assert_eq!(top.len(), 4);
+        for axis in 0..2 {
+            let min = top.iter().map(|uv| uv[axis]).fold(f32::MAX, f32::min);
+            let max = top.iter().map(|uv| uv[axis]).fold(f32::MIN, f32::max);
+            assert_eq!((min, max), (0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn subdivided_cube_keeps_shape_and_outward_normals() {
+        let single = cube_subdivided(1);
+        let original = cube();
+        assert_eq!(single.positions.len(), original.positions.len());
+        let extent = |m: &Mesh| {
+            let Bounds { min, max } = m.bounds;
+            [min.x, min.y, min.z, max.x, max.y, max.z]
+        };
+        assert_eq!(extent(&single), extent(&original));
+
+        let mesh = cube_subdivided(2);
+        // 3x3 grid vertices and 2x2 quads per face.
+        assert_eq!(mesh.positions.len() / 3, 6 * 9);
+        assert_eq!(mesh.indices.len(), 6 * 4 * 6);
+        assert_eq!(extent(&mesh), extent(&original));
+        for i in 0..mesh.positions.len() / 3 {
+            let n = &mesh.normals[i * 3..i * 3 + 3];
+            // Flat per face: one axis-aligned unit normal, pointing away from the center.
+            assert_eq!(n.iter().filter(|c| c.abs() > 0.999).count(), 1);
+            assert!(Vec3::new(n[0], n[1], n[2]).dot(mesh.position(i)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn wireframe_dedups_shared_edges() {
+        // Two triangles: 6 raw edges, the shared diagonal collapses into one -> 5 segments.
+        let lines = build_wireframe_indices(&[0u16, 1, 2, 0, 2, 3]);
+        assert_eq!(lines, vec![0, 1, 1, 2, 2, 0, 2, 3, 3, 0]);
+
+        // Cube: faces share no vertices, so each of the 6 gives its 4 sides plus 1 diagonal.
+        let IndexBuffer::U16(ix) = cube().indices else {
+            panic!("cube uses u16 indices");
+        };
+        assert_eq!(build_wireframe_indices(&ix).len() / 2, 6 * 5);
+    }
+
+    #[test]
+    fn quad_tangent_follows_u_direction() {
+        let positions = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let normals = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        let uvs = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        let tangents = compute_tangents(&positions, &normals, &uvs, &indices);
+        assert_eq!(tangents.len(), 16);
+        for t in tangents.chunks_exact(4) {
+            assert!((t[0] - 1.0).abs() <= 1e-6 && t[1].abs() <= 1e-6 && t[2].abs() <= 1e-6);
+            assert_eq!(t[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn primitives_have_unit_tangents() {
+        for mesh in [
+            cube(),
+            sphere(0.5, 16, 8),
+            cube().with_normal_mode(NormalMode::Flat),
+        ] {
+            assert_eq!(mesh.tangents.len() / 4, mesh.positions.len() / 3);
+            for t in mesh.tangents.chunks_exact(4) {
+                assert!((Vec3::new(t[0], t[1], t[2]).length() - 1.0).abs() <= 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn flat_cube_has_faceted_normals() {
+        let mesh = cube().with_normal_mode(NormalMode::Flat);
+        assert_eq!(mesh.positions.len() / 3, 36);
+        assert_eq!(mesh.indices.len(), 36);
+        // Each face is two consecutive triangles = six identical normals.
+        for face in mesh.normals.chunks_exact(18) {
+            for n in face.chunks_exact(3) {
+                assert_eq!(n, &face[..3]);
+            }
+            let axis_aligned = face[..3].iter().filter(|c| c.abs() > 0.999).count();
+            assert_eq!(axis_aligned, 1);
+        }
+    }
+
+    #[test]
+    fn crease_angle_keeps_sharp_edges_and_smooths_shallow_ones() {
+        let axis_aligned = |n: &[f32]| n.iter().filter(|c| c.abs() > 0.999).count() == 1;
+
+        let sharp = cube().with_normal_mode(NormalMode::Crease(30f32.to_radians()));
+        assert_eq!(sharp.positions.len() / 3, 24);
+        assert!(sharp.normals.chunks_exact(3).all(axis_aligned));
+
+        // Each corner averages the three faces meeting there.
+        let smooth = cube().with_normal_mode(NormalMode::Crease(179f32.to_radians()));
+        assert_eq!(smooth.indices.len(), 36);
+        for (i, n) in smooth.normals.chunks_exact(3).enumerate() {
+            assert!(n.iter().all(|c| c.abs() > 0.1), "vertex {i}: {n:?}");
+            assert!(Vec3::new(n[0], n[1], n[2]).dot(smooth.position(i)) > 0.0);
+        }
+
+        // A shared vertex whose faces disagree is split in two.
+        let positions = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let split = compute_normals_with_crease(&positions, &[0, 1, 2, 0, 3, 1], 0.5);
+        assert_eq!(split.sources, [0, 1, 2, 0, 3, 1]);
+        let joined = compute_normals_with_crease(&positions, &[0, 1, 2, 0, 3, 1], 2.0);
+        assert_eq!(joined.sources, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn smooth_mode_is_unchanged() {
+        let smooth = cube().with_normal_mode(NormalMode::Smooth);
+        assert_eq!(smooth.positions, cube().positions);
+        assert_eq!(smooth.indices, cube().indices);
+        assert_eq!(NormalMode::from_str("Flat"), Some(NormalMode::Flat));
+        assert_eq!(NormalMode::from_str("faceted"), None);
+    }
+
+    #[test]
+    fn large_meshes_switch_to_u32_indices() {
+        // A strip of triangles needing more vertices than u16 can address.
+        let vertex_count = 70_002;
+        let mut positions = Vec::with_capacity(vertex_count * 3);
+        for i in 0..vertex_count {
+            positions.extend_from_slice(&[i as f32, (i % 2) as f32, 0.0]);
+        }
+        let indices: Vec<u32> = (0..vertex_count as u32 - 2)
+            .flat_map(|i| [i, i + 1, i + 2])
+            .collect();
+
+        let mesh = mesh_from_raw(&positions, &indices).unwrap();
+        match &mesh.indices {
+            IndexBuffer::U32(ix) => assert_eq!(ix.len(), indices.len()),
+            IndexBuffer::U16(_) => panic!("expected 32-bit indices"),
+        }
+    }
+
+    #[test]
+    fn small_meshes_and_primitives_keep_u16_indices() {
+        let quad = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0];
+        let mesh = mesh_from_raw(&quad, &[0, 1, 2]).unwrap();
+        assert_eq!(mesh.indices, IndexBuffer::U16(vec![0, 1, 2]));
+        assert!(matches!(sphere(0.5, 32, 16).indices, IndexBuffer::U16(_)));
+    }
+}
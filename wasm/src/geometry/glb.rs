@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::{mesh_from_parts, Mesh};
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const COMPONENT_U8: u32 = 5121;
+const COMPONENT_U16: u32 = 5123;
+const COMPONENT_U32: u32 = 5125;
+const COMPONENT_F32: u32 = 5126;
+const MODE_TRIANGLES: u32 = 4;
+
+/// The subset of the glTF JSON needed to pull out one triangle primitive.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Gltf {
+    #[serde(default)]
+    meshes: Vec<GltfMesh>,
+    #[serde(default)]
+    accessors: Vec<Accessor>,
+    #[serde(default)]
+    buffer_views: Vec<BufferView>,
+}
+
+#[derive(Deserialize)]
+struct GltfMesh {
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+/// Parse a binary glTF (GLB) and return the first primitive of the first mesh.
+/// Reads FLOAT `POSITION`/`NORMAL` and `TEXCOORD_0` plus 8/16/32-bit indices from the
+/// embedded BIN chunk; external buffers, sparse accessors and node transforms are ignored.
+pub(crate) fn parse_glb(bytes: &[u8]) -> Result<Mesh, String> {
+    let word = |at: usize| -> Result<u32, String> {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| "GLB truncated".to_string())
+    };
+    if word(0)? != GLB_MAGIC {
+        return Err("not a GLB file (bad magic)".to_string());
+    }
+    if word(4)? != 2 {
+        return Err(format!("unsupported glTF version {}", word(4)?));
+    }
+
+    // Chunks follow the 12-byte header, each 4-byte aligned.
+    let mut json = None;
+    let mut bin: &[u8] = &[];
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let len = word(at)? as usize;
+        let kind = word(at + 4)?;
+        let data = bytes
+            .get(at + 8..at + 8 + len)
+            .ok_or("GLB chunk runs past end of file")?;
+        match kind {
+            CHUNK_JSON => json = Some(data),
+            CHUNK_BIN => bin = data,
+            _ => {}
+        }
+        at += 8 + len;
+    }
+    let json = json.ok_or("GLB has no JSON chunk")?;
+    let gltf: Gltf = serde_json::from_slice(json).map_err(|e| format!("invalid glTF JSON: {e}"))?;
+
+    let primitive = gltf
+        .meshes
+        .first()
+        .and_then(|m| m.primitives.first())
+        .ok_or("glTF has no mesh primitives")?;
+    if primitive.mode.unwrap_or(MODE_TRIANGLES) != MODE_TRIANGLES {
+        return Err("only triangle primitives are supported".to_string());
+    }
+
+    let floats = |name: &str, kind: &str| -> Result<Option<Vec<f32>>, String> {
+        primitive
+            .attributes
+            .get(name)
+            .map(|&i| read_floats(&gltf, bin, i, kind).map_err(|e| format!("{name}: {e}")))
+            .transpose()
+    };
+    let positions = floats("POSITION", "VEC3")?.ok_or("primitive has no POSITION")?;
+    let normals = floats("NORMAL", "VEC3")?;
+    let uvs = floats("TEXCOORD_0", "VEC2")?;
+    let indices = match primitive.indices {
+        Some(i) => read_indices(&gltf, bin, i).map_err(|e| format!("indices: {e}"))?,
+        None => Vec::new(),
+    };
+
+    let vertex_count = positions.len() / 3;
+    if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertex_count) {
+        return Err(format!(
+            "index {bad} out of range for {vertex_count} vertices"
+        ));
+    }
+    if normals.as_ref().is_some_and(|n| n.len() != positions.len()) {
+        return Err("NORMAL count does not match POSITION".to_string());
+    }
+    if uvs.as_ref().is_some_and(|t| t.len() / 2 != vertex_count) {
+        return Err("TEXCOORD_0 count does not match POSITION".to_string());
+    }
+    Ok(mesh_from_parts(positions, normals, uvs, indices))
+}
+
+/// Bytes for each element of accessor `index`, honouring the view's stride.
+fn accessor_elements<'a>(
+    gltf: &Gltf,
+    bin: &'a [u8],
+    index: usize,
+    element_size: usize,
+) -> Result<Vec<&'a [u8]>, String> {
+    let accessor = gltf.accessors.get(index).ok_or("accessor out of range")?;
+    let view_index = accessor.buffer_view.ok_or("accessor has no bufferView")?;
+    let view = gltf
+        .buffer_views
+        .get(view_index)
+        .ok_or("bufferView out of range")?;
+    let stride = view.byte_stride.unwrap_or(element_size);
+    let view_bytes = bin
+        .get(view.byte_offset..view.byte_offset + view.byte_length)
+        .ok_or("bufferView runs past the BIN chunk")?;
+
+    (0..accessor.count)
+        .map(|i| {
+            let start = accessor.byte_offset + i * stride;
+            view_bytes
+                .get(start..start + element_size)
+                .ok_or_else(|| "accessor runs past its bufferView".to_string())
+        })
+        .collect()
+}
+
+fn read_floats(gltf: &Gltf, bin: &[u8], index: usize, kind: &str) -> Result<Vec<f32>, String> {
+    let accessor = gltf.accessors.get(index).ok_or("accessor out of range")?;
+    if accessor.component_type != COMPONENT_F32 || accessor.kind != kind {
+        let got = format!("{} {}", accessor.component_type, accessor.kind);
+        return Err(format!("expected FLOAT {kind}, got {got}"));
+    }
+    let width = if kind == "VEC2" { 2 } else { 3 };
+    let elements = accessor_elements(gltf, bin, index, width * 4)?;
+    Ok(elements
+        .iter()
+        .flat_map(|e| {
+            e.chunks_exact(4)
+                .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+        })
+        .collect())
+}
+
+fn read_indices(gltf: &Gltf, bin: &[u8], index: usize) -> Result<Vec<u32>, String> {
+    let accessor = gltf.accessors.get(index).ok_or("accessor out of range")?;
+    if accessor.kind != "SCALAR" {
+        return Err(format!("expected SCALAR, got {}", accessor.kind));
+    }
+    let size = match accessor.component_type {
+        COMPONENT_U8 => 1,
+        COMPONENT_U16 => 2,
+        COMPONENT_U32 => 4,
+        other => return Err(format!("unsupported index component type {other}")),
+    };
+    let elements = accessor_elements(gltf, bin, index, size)?;
+    Ok(elements
+        .iter()
+        .map(|e| match e.len() {
+            1 => e[0] as u32,
+            2 => u16::from_le_bytes([e[0], e[1]]) as u32,
+            _ => u32::from_le_bytes([e[0], e[1], e[2], e[3]]),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal GLB: one triangle with u16 indices, no normals.
+    fn triangle_glb() -> Vec<u8> {
+        let mut bin = Vec::new();
+        for c in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend_from_slice(&c.to_le_bytes());
+        }
+        for i in [0u16, 1, 2] {
+            bin.extend_from_slice(&i.to_le_bytes());
+        }
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        let mut json = r#"{
+            "asset": {"version": "2.0"},
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+                {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 6}
+            ],
+            "buffers": [{"byteLength": 44}]
+        }"#
+        .as_bytes()
+        .to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut out = Vec::new();
+        for w in [GLB_MAGIC, 2, total as u32, json.len() as u32, CHUNK_JSON] {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.extend_from_slice(&json);
+        for w in [bin.len() as u32, CHUNK_BIN] {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.extend_from_slice(&bin);
+        out
+    }
+
+    #[test]
+    fn parses_embedded_triangle() {
+        let mesh = parse_glb(&triangle_glb()).unwrap();
+        assert_eq!(
+            mesh.positions,
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(mesh.indices.len(), 3);
+        // No NORMAL attribute: computed from the CCW winding.
+        assert_eq!(&mesh.normals[..3], &[0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_non_glb() {
+        assert!(parse_glb(b"solid cube").is_err());
+        let mut truncated = triangle_glb();
+        truncated.truncate(truncated.len() - 8);
+        assert!(parse_glb(&truncated).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use super::{mesh_from_parts, Mesh};
+use crate::math::Vec3;
+
+/// Parse Wavefront OBJ text: `v`, `vt`, `vn` and `f` lines.
+/// Faces accept `v`, `v/vt`, `v//vn` and `v/vt/vn` corners (1-based, negative = relative)
+/// and polygons are fan-triangulated. Other statements (groups, materials) are ignored.
+/// Normals are computed when any face corner lacks one.
+pub(crate) fn parse_obj(src: &str) -> Result<Mesh, String> {
+    let mut v: Vec<Vec3> = Vec::new();
+    let mut vt: Vec<[f32; 2]> = Vec::new();
+    let mut vn: Vec<Vec3> = Vec::new();
+
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    // One output vertex per distinct (v, vt, vn) combination.
+    let mut corner_ids: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut has_uvs = false;
+    let mut all_normals = true;
+
+    for (line_no, raw) in src.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        let mut parts = line.split_whitespace();
+        let err = |msg: &str| format!("line {}: {msg}", line_no + 1);
+        match parts.next() {
+            Some("v") => {
+                let [x, y, z] = parse_floats::<3>(&mut parts).ok_or_else(|| err("bad vertex"))?;
+                v.push(Vec3::new(x, y, z));
+            }
+            Some("vt") => {
+                let [s, t] = parse_floats::<2>(&mut parts).ok_or_else(|| err("bad texcoord"))?;
+                vt.push([s, t]);
+            }
+            Some("vn") => {
+                let [x, y, z] = parse_floats::<3>(&mut parts).ok_or_else(|| err("bad normal"))?;
+                vn.push(Vec3::new(x, y, z));
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+                for token in parts {
+                    let key =
+                        parse_corner(token, v.len(), vt.len(), vn.len()).map_err(|e| err(&e))?;
+                    let id = match corner_ids.get(&key) {
+                        Some(&id) => id,
+                        None => {
+                            let (pi, ti, ni) = key;
+                            let id = (positions.len() / 3) as u32;
+                            let p = v[pi];
+                            positions.extend_from_slice(&[p.x, p.y, p.z]);
+                            let t = ti.map_or([0.0, 0.0], |i| vt[i]);
+                            uvs.extend_from_slice(&t);
+                            let n = ni.map_or(Vec3::new(0.0, 0.0, 0.0), |i| vn[i]);
+                            normals.extend_from_slice(&[n.x, n.y, n.z]);
+                            has_uvs |= ti.is_some();
+                            all_normals &= ni.is_some();
+                            corner_ids.insert(key, id);
+                            id
+                        }
+                    };
+                    face.push(id);
+                }
+                if face.len() < 3 {
+                    return Err(err("face needs at least 3 vertices"));
+                }
+                for i in 1..face.len() - 1 {
+                    indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if indices.is_empty() {
+        return Err("OBJ contains no faces".to_string());
+    }
+    Ok(mesh_from_parts(
+        positions,
+        all_normals.then_some(normals),
+        has_uvs.then_some(uvs),
+        indices,
+    ))
+}
+
+fn parse_floats<'a, const N: usize>(parts: &mut impl Iterator<Item = &'a str>) -> Option<[f32; N]> {
+    let mut out = [0.0; N];
+    for slot in &mut out {
+        *slot = parts.next()?.parse().ok()?;
+    }
+    Some(out)
+}
+
+/// Resolve one `v/vt/vn` face corner to zero-based indices.
+fn parse_corner(
+    token: &str,
+    v_count: usize,
+    vt_count: usize,
+    vn_count: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let mut fields = token.split('/');
+    let v = resolve_index(fields.next(), v_count)?
+        .ok_or_else(|| format!("missing vertex in {token:?}"))?;
+    let vt = resolve_index(fields.next(), vt_count)?;
+    let vn = resolve_index(fields.next(), vn_count)?;
+    Ok((v, vt, vn))
+}
+
+fn resolve_index(field: Option<&str>, count: usize) -> Result<Option<usize>, String> {
+    let Some(field) = field.filter(|f| !f.is_empty()) else {
+        return Ok(None);
+    };
+    let i: i64 = field.parse().map_err(|_| format!("bad index {field:?}"))?;
+    let resolved = if i < 0 { count as i64 + i } else { i - 1 };
+    if i == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {i} out of range for {count} elements"));
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE_OBJ: &str = "\
+# unit cube
+o cube
+v -0.5 -0.5 -0.5
+v  0.5 -0.5 -0.5
+v  0.5  0.5 -0.5
+v -0.5  0.5 -0.5
+v -0.5 -0.5  0.5
+v  0.5 -0.5  0.5
+v  0.5  0.5  0.5
+v -0.5  0.5  0.5
+f 1 4 3 2
+f 5 6 7 8
+f 1 5 8 4
+f 2 3 7 6
+f 1 2 6 5
+f 4 8 7 3
+";
+
+    #[test]
+    fn parses_minimal_cube() {
+        let mesh = parse_obj(CUBE_OBJ).unwrap();
+        assert_eq!(mesh.positions.len() / 3, 8);
+        assert_eq!(mesh.indices.len(), 6 * 2 * 3);
+        let (min, max) = (mesh.bounds.min, mesh.bounds.max);
+        assert_eq!([min.x, min.y, min.z], [-0.5, -0.5, -0.5]);
+        assert_eq!([max.x, max.y, max.z], [0.5, 0.5, 0.5]);
+
+        // No `vn` lines, so normals are computed and point away from the center.
+        for (p, n) in mesh
+            .positions
+            .chunks_exact(3)
+            .zip(mesh.normals.chunks_exact(3))
+        {
+            let outward = Vec3::new(p[0], p[1], p[2]).dot(Vec3::new(n[0], n[1], n[2]));
+            assert!(outward > 0.0);
+        }
+    }
+
+    #[test]
+    fn keeps_explicit_normals_and_uvs() {
+        let src = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vn 0 0 -1
+f 1/1/1 2/2/1 -1/-1/-1
+";
+        let mesh = parse_obj(src).unwrap();
+        assert_eq!(mesh.positions.len() / 3, 3);
+        assert_eq!(mesh.uvs, vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        assert!(mesh.normals.chunks_exact(3).all(|n| n == [0.0, 0.0, -1.0]));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse_obj("v 0 0 0\nf 1 2 3\n")
+            .unwrap_err()
+            .starts_with("line 2"));
+        assert!(parse_obj("v 0 0\n").is_err());
+        assert!(parse_obj("v 0 0 0\n").is_err());
+    }
+}
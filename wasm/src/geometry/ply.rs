@@ -0,0 +1,215 @@
+use super::{mesh_from_parts, point_cloud, Mesh};
+
+/// One `element` block from the header.
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<String>,
+}
+
+/// Parse an ASCII PLY file with a `vertex` element (`x`, `y`, `z`, optional `nx`, `ny`,
+/// `nz`) and an optional `face` element whose first property is the index list.
+/// Polygons are fan-triangulated; without faces the result is a point cloud.
+/// Other elements and properties are skipped; binary PLY is rejected.
+pub(crate) fn parse_ply(src: &[u8]) -> Result<Mesh, String> {
+    let text = std::str::from_utf8(src).map_err(|_| "PLY is not ASCII text".to_string())?;
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    if lines.next() != Some("ply") {
+        return Err("not a PLY file (missing 'ply' magic)".to_string());
+    }
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let line = lines.next().ok_or("PLY header has no end_header")?;
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("format") if parts.next() != Some("ascii") => {
+                return Err("only ASCII PLY is supported".to_string());
+            }
+            Some("element") => {
+                let name = parts.next().ok_or("element without a name")?.to_string();
+                let count = parts
+                    .next()
+                    .and_then(|c| c.parse().ok())
+                    .ok_or_else(|| format!("element {name} has no count"))?;
+                elements.push(Element {
+                    name,
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            Some("property") => {
+                let element = elements.last_mut().ok_or("property before any element")?;
+                // `property <type> <name>` or `property list <count> <item> <name>`.
+                let name = parts.last().ok_or("property without a name")?;
+                element.properties.push(name.to_string());
+            }
+            Some("end_header") => break,
+            _ => {} // comment, obj_info
+        }
+    }
+
+    let mut positions: Vec<f32> = Vec::new();
+    let mut normals: Vec<f32> = Vec::new();
+    let mut has_normals = false;
+    let mut indices: Vec<u32> = Vec::new();
+    let mut has_faces = false;
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                let column = |name: &str| element.properties.iter().position(|p| p == name);
+                let xyz = [column("x"), column("y"), column("z")];
+                let [Some(x), Some(y), Some(z)] = xyz else {
+                    return Err("vertex element needs x, y and z".to_string());
+                };
+                let nxyz = [column("nx"), column("ny"), column("nz")];
+                has_normals = nxyz.iter().all(Option::is_some);
+                for i in 0..element.count {
+                    let line = lines.next().ok_or("PLY ends inside the vertex list")?;
+                    let values: Vec<f32> = line
+                        .split_whitespace()
+                        .map(|v| {
+                            v.parse()
+                                .map_err(|_| format!("vertex {i}: bad number {v:?}"))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let get = |c: usize| {
+                        values
+                            .get(c)
+                            .copied()
+                            .ok_or(format!("vertex {i}: too few values"))
+                    };
+                    positions.extend_from_slice(&[get(x)?, get(y)?, get(z)?]);
+                    if let [Some(nx), Some(ny), Some(nz)] = nxyz {
+                        normals.extend_from_slice(&[get(nx)?, get(ny)?, get(nz)?]);
+                    }
+                }
+            }
+            "face" => {
+                has_faces = element.count > 0;
+                let vertex_count = positions.len() / 3;
+                for i in 0..element.count {
+                    let line = lines.next().ok_or("PLY ends inside the face list")?;
+                    let values: Vec<u32> = line
+                        .split_whitespace()
+                        .map(|v| v.parse().map_err(|_| format!("face {i}: bad index {v:?}")))
+                        .collect::<Result<_, _>>()?;
+                    let n = *values.first().ok_or(format!("face {i}: empty line"))? as usize;
+                    let face = values
+                        .get(1..1 + n)
+                        .ok_or(format!("face {i}: too few indices"))?;
+                    if n < 3 {
+                        return Err(format!("face {i}: needs at least 3 vertices"));
+                    }
+                    if let Some(&bad) = face.iter().find(|&&v| v as usize >= vertex_count) {
+                        return Err(format!("face {i}: index {bad} out of range"));
+                    }
+                    for k in 1..n - 1 {
+                        indices.extend_from_slice(&[face[0], face[k], face[k + 1]]);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    lines
+                        .next()
+                        .ok_or_else(|| format!("PLY ends inside {}", element.name))?;
+                }
+            }
+        }
+    }
+
+    if positions.is_empty() {
+        return Err("PLY contains no vertices".to_string());
+    }
+    let normals = has_normals.then_some(normals);
+    if has_faces {
+        Ok(mesh_from_parts(positions, normals, None, indices))
+    } else {
+        Ok(point_cloud(positions, normals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Topology;
+
+    const CUBE_PLY: &str = "\
+ply
+format ascii 1.0
+comment unit cube
+element vertex 8
+property float x
+property float y
+property float z
+element face 6
+property list uchar int vertex_indices
+end_header
+-0.5 -0.5 -0.5
+0.5 -0.5 -0.5
+0.5 0.5 -0.5
+-0.5 0.5 -0.5
+-0.5 -0.5 0.5
+0.5 -0.5 0.5
+0.5 0.5 0.5
+-0.5 0.5 0.5
+4 0 3 2 1
+4 4 5 6 7
+4 0 4 7 3
+4 1 2 6 5
+4 0 1 5 4
+4 3 7 6 2
+";
+
+    #[test]
+    fn parses_ascii_cube_and_computes_normals() {
+        let mesh = parse_ply(CUBE_PLY.as_bytes()).unwrap();
+        assert_eq!(mesh.topology, Topology::Triangles);
+        assert_eq!(mesh.positions.len() / 3, 8);
+        assert_eq!(mesh.indices.len() / 3, 12);
+
+        // Normals were absent from the file: they are computed, unit length and outward.
+        for (p, n) in mesh
+            .positions
+            .chunks_exact(3)
+            .zip(mesh.normals.chunks_exact(3))
+        {
+            let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            assert!((len - 1.0).abs() <= 1e-5);
+            assert!(p[0] * n[0] + p[1] * n[1] + p[2] * n[2] > 0.0);
+        }
+    }
+
+    #[test]
+    fn vertices_only_is_a_point_cloud() {
+        let src = "\
+ply
+format ascii 1.0
+element vertex 2
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property uchar red
+end_header
+0 0 0 0 1 0 255
+1 2 3 0 0 1 128
+";
+        let mesh = parse_ply(src.as_bytes()).unwrap();
+        assert_eq!(mesh.topology, Topology::Points);
+        assert!(mesh.indices.is_empty());
+        assert_eq!(mesh.normals, vec![0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+        assert!(mesh.corner_indices().is_empty());
+    }
+
+    #[test]
+    fn rejects_binary_and_truncated() {
+        let binary = CUBE_PLY.replace("ascii", "binary_little_endian");
+        assert!(parse_ply(binary.as_bytes()).unwrap_err().contains("ASCII"));
+        let truncated = &CUBE_PLY[..CUBE_PLY.len() - 12];
+        assert!(parse_ply(truncated.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use super::{mesh_from_parts, Mesh};
+use crate::math::Vec3;
+
+const HEADER_LEN: usize = 80;
+const TRIANGLE_LEN: usize = 50;
+
+/// Parse a binary STL: 80-byte header, little-endian `u32` triangle count, then per
+/// triangle a facet normal, three vertices (all `f32` xyz) and a 2-byte attribute.
+/// The result is non-indexed; weld it to share vertices and get smooth normals.
+/// Zero facet normals (common in exporters) are recomputed from the winding.
+pub(crate) fn parse_stl_binary(bytes: &[u8]) -> Result<Mesh, String> {
+    let Some(count_bytes) = bytes.get(HEADER_LEN..HEADER_LEN + 4) else {
+        return Err(format!(
+            "STL too short: {} bytes, need at least 84",
+            bytes.len()
+        ));
+    };
+    let count = u32::from_le_bytes(count_bytes.try_into().unwrap()) as usize;
+    let expected = count
+        .checked_mul(TRIANGLE_LEN)
+        .and_then(|n| n.checked_add(HEADER_LEN + 4))
+        .ok_or_else(|| format!("STL triangle count {count} is too large"))?;
+    if bytes.len() != expected {
+        return Err(format!(
+            "STL length {} does not match {count} triangles ({expected} bytes)",
+            bytes.len()
+        ));
+    }
+    if count == 0 {
+        return Err("STL contains no triangles".to_string());
+    }
+
+    let mut positions = Vec::with_capacity(count * 9);
+    let mut normals = Vec::with_capacity(count * 9);
+    for tri in bytes[HEADER_LEN + 4..].chunks_exact(TRIANGLE_LEN) {
+        let vec3 = |slot: usize| {
+            let f = |k: usize| {
+                let b = slot * 12 + k * 4;
+                f32::from_le_bytes(tri[b..b + 4].try_into().unwrap())
+            };
+            Vec3::new(f(0), f(1), f(2))
+        };
+        let (a, b, c) = (vec3(1), vec3(2), vec3(3));
+        let mut n = vec3(0);
+        if n.length() <= 1e-12 {
+            n = b.sub(a).cross(c.sub(a)).normalize();
+        }
+        for p in [a, b, c] {
+            positions.extend_from_slice(&[p.x, p.y, p.z]);
+            normals.extend_from_slice(&[n.x, n.y, n.z]);
+        }
+    }
+    Ok(mesh_from_parts(positions, Some(normals), None, Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stl_bytes(triangles: &[[[f32; 3]; 4]]) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LEN];
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for tri in triangles {
+            for v in tri {
+                for c in v {
+                    bytes.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&[0, 0]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_single_triangle() {
+        let bytes = stl_bytes(&[[
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]]);
+        assert_eq!(bytes.len(), 84 + 50);
+
+        let mesh = parse_stl_binary(&bytes).unwrap();
+        assert_eq!(
+            mesh.positions,
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            mesh.normals,
+            vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]
+        );
+        assert!(mesh.indices.is_empty());
+        assert_eq!(mesh.bounds.max.x, 1.0);
+    }
+
+    #[test]
+    fn zero_facet_normal_is_recomputed() {
+        let bytes = stl_bytes(&[[
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ]]);
+        let mesh = parse_stl_binary(&bytes).unwrap();
+        assert_eq!(&mesh.normals[..3], &[0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn rejects_length_mismatch() {
+        let mut bytes = stl_bytes(&[[[0.0; 3]; 4]]);
+        bytes.push(0);
+        assert!(parse_stl_binary(&bytes)
+            .unwrap_err()
+            .contains("does not match"));
+        assert!(parse_stl_binary(&bytes[..40]).is_err());
+        assert!(parse_stl_binary(&stl_bytes(&[])).is_err());
+    }
+}
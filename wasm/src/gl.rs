@@ -0,0 +1,477 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    AngleInstancedArrays, HtmlCanvasElement, WebGl2RenderingContext, WebGlBuffer,
+    WebGlContextAttributes, WebGlProgram, WebGlRenderingContext, WebGlShader, WebGlTexture,
+    WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+/// The GL calls the renderer makes, so it can drive a real context (`Gl`) or, in tests,
+/// a stand-in that records what would have been drawn. Names and arguments follow
+/// WebGL; calls that take JS-specific arguments there take plain Rust data here.
+pub(crate) trait GraphicsBackend: Clone {
+    type Buffer;
+    type Program;
+    type Shader;
+    type Texture;
+    type UniformLocation;
+    type VertexArray;
+    type Instancing: InstancedDraw;
+
+    fn is_webgl2(&self) -> bool;
+    /// A new vertex array object, or `None` where they are unsupported (WebGL1).
+    fn create_vertex_array(&self) -> Option<Self::VertexArray>;
+    fn bind_vertex_array(&self, vao: Option<&Self::VertexArray>);
+    fn delete_vertex_array(&self, vao: Option<&Self::VertexArray>);
+    /// Instanced draw entry points, if the context has them.
+    fn instancing(&self) -> Option<Self::Instancing>;
+    /// Enable a WebGL extension by name; false if it is unavailable.
+    fn enable_extension(&self, name: &str) -> bool;
+    /// Whether fragment shaders implement `highp` floats.
+    fn fragment_highp_supported(&self) -> bool;
+    fn shader_compiled(&self, shader: &Self::Shader) -> bool;
+    fn program_linked(&self, program: &Self::Program) -> bool;
+    fn buffer_data_f32(&self, target: u32, data: &[f32], usage: u32);
+    fn buffer_data_u16(&self, target: u32, data: &[u16], usage: u32);
+    fn buffer_data_u32(&self, target: u32, data: &[u32], usage: u32);
+    /// Upload RGBA8 pixels to mip level 0 of the bound texture's `target` (`TEXTURE_2D`
+    /// or a cube face), first row first.
+    fn tex_image_2d_rgba(
+        &self,
+        target: u32,
+        width: i32,
+        height: i32,
+        pixels: &[u8],
+    ) -> Result<(), JsValue>;
+    /// Read RGBA8 pixels, bottom row first.
+    fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        pixels: &mut [u8],
+    ) -> Result<(), JsValue>;
+    /// High-resolution timestamp in milliseconds, for frame timing.
+    fn now_ms(&self) -> f64;
+
+    fn active_texture(&self, texture: u32);
+    fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader);
+    fn bind_buffer(&self, target: u32, buffer: Option<&Self::Buffer>);
+    fn bind_texture(&self, target: u32, texture: Option<&Self::Texture>);
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32);
+    fn clear(&self, mask: u32);
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32);
+    fn compile_shader(&self, shader: &Self::Shader);
+    fn create_buffer(&self) -> Option<Self::Buffer>;
+    fn create_program(&self) -> Option<Self::Program>;
+    fn create_shader(&self, ty: u32) -> Option<Self::Shader>;
+    fn create_texture(&self) -> Option<Self::Texture>;
+    fn delete_buffer(&self, buffer: Option<&Self::Buffer>);
+    fn delete_program(&self, program: Option<&Self::Program>);
+    fn delete_shader(&self, shader: Option<&Self::Shader>);
+    fn delete_texture(&self, texture: Option<&Self::Texture>);
+    fn depth_mask(&self, flag: bool);
+    fn disable(&self, cap: u32);
+    fn disable_vertex_attrib_array(&self, index: u32);
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32);
+    fn draw_elements_with_i32(&self, mode: u32, count: i32, ty: u32, offset: i32);
+    fn enable(&self, cap: u32);
+    fn enable_vertex_attrib_array(&self, index: u32);
+    fn front_face(&self, mode: u32);
+    fn generate_mipmap(&self, target: u32);
+    fn get_attrib_location(&self, program: &Self::Program, name: &str) -> i32;
+    fn get_error(&self) -> u32;
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String>;
+    fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String>;
+    fn get_uniform_location(
+        &self,
+        program: &Self::Program,
+        name: &str,
+    ) -> Option<Self::UniformLocation>;
+    fn is_context_lost(&self) -> bool;
+    fn link_program(&self, program: &Self::Program);
+    fn polygon_offset(&self, factor: f32, units: f32);
+    fn shader_source(&self, shader: &Self::Shader, src: &str);
+    fn stencil_func(&self, func: u32, ref_: i32, mask: u32);
+    fn stencil_mask(&self, mask: u32);
+    fn stencil_op(&self, fail: u32, zfail: u32, zpass: u32);
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32);
+    fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32);
+    fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32);
+    fn uniform2fv_with_f32_array(&self, location: Option<&Self::UniformLocation>, data: &[f32]);
+    fn uniform3fv_with_f32_array(&self, location: Option<&Self::UniformLocation>, data: &[f32]);
+    fn uniform_matrix3fv_with_f32_array(
+        &self,
+        location: Option<&Self::UniformLocation>,
+        transpose: bool,
+        data: &[f32],
+    );
+    fn uniform_matrix4fv_with_f32_array(
+        &self,
+        location: Option<&Self::UniformLocation>,
+        transpose: bool,
+        data: &[f32],
+    );
+    fn use_program(&self, program: Option<&Self::Program>);
+    fn vertex_attrib_pointer_with_i32(
+        &self,
+        index: u32,
+        size: i32,
+        ty: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    );
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+}
+
+/// Instanced draws, which WebGL1 only has through an extension.
+pub(crate) trait InstancedDraw {
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instances: i32);
+    fn draw_elements_instanced(&self, mode: u32, count: i32, ty: u32, offset: i32, instances: i32);
+}
+
+/// A WebGL1 or WebGL2 context.
+///
+/// The shaders are GLSL ES 1.00, which both versions accept, so the renderer only needs
+/// the calls in `GraphicsBackend`. Most forward to the same-named `web_sys` method;
+/// WebGL2-only features (vertex array objects) are no-ops on WebGL1.
+#[derive(Clone)]
+pub(crate) enum Gl {
+    WebGl1(WebGlRenderingContext),
+    WebGl2(WebGl2RenderingContext),
+}
+
+macro_rules! forward {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?;)*) => {
+        $(
+            fn $name(&self $(, $arg: $ty)*) $(-> $ret)? {
+                match self {
+                    Gl::WebGl1(gl) => gl.$name($($arg),*),
+                    Gl::WebGl2(gl) => gl.$name($($arg),*),
+                }
+            }
+        )*
+    };
+}
+
+/// Run `$body` with `$gl` bound to whichever context `$self` holds.
+macro_rules! with_context {
+    ($self:expr, $gl:ident => $body:expr) => {
+        match $self {
+            Gl::WebGl1($gl) => $body,
+            Gl::WebGl2($gl) => $body,
+        }
+    };
+}
+
+impl Gl {
+    /// Prefer WebGL2 and fall back to WebGL1, requesting the `antialias` and `alpha`
+    /// context attributes. Both are hints: the browser may still hand back an aliased
+    /// framebuffer (WebGL1 MSAA in particular depends on browser and GPU support).
+    /// A stencil buffer is always requested; the selection outline needs it.
+    pub(crate) fn from_canvas(
+        canvas: &HtmlCanvasElement,
+        antialias: bool,
+        alpha: bool,
+    ) -> Result<Self, JsValue> {
+        let attributes = WebGlContextAttributes::new();
+        attributes.set_antialias(antialias);
+        attributes.set_alpha(alpha);
+        attributes.set_stencil(true);
+        match crate::get_webgl2_context(canvas, &attributes)? {
+            Some(gl) => Ok(Self::WebGl2(gl)),
+            None => crate::get_webgl_context(canvas, &attributes).map(Self::WebGl1),
+        }
+    }
+}
+
+impl GraphicsBackend for Gl {
+    type Buffer = WebGlBuffer;
+    type Program = WebGlProgram;
+    type Shader = WebGlShader;
+    type Texture = WebGlTexture;
+    type UniformLocation = WebGlUniformLocation;
+    type VertexArray = WebGlVertexArrayObject;
+    type Instancing = Instancing;
+
+    fn is_webgl2(&self) -> bool {
+        matches!(self, Self::WebGl2(_))
+    }
+
+    fn create_vertex_array(&self) -> Option<WebGlVertexArrayObject> {
+        match self {
+            Self::WebGl1(_) => None,
+            Self::WebGl2(gl) => gl.create_vertex_array(),
+        }
+    }
+
+    fn bind_vertex_array(&self, vao: Option<&WebGlVertexArrayObject>) {
+        if let Self::WebGl2(gl) = self {
+            gl.bind_vertex_array(vao);
+        }
+    }
+
+    fn delete_vertex_array(&self, vao: Option<&WebGlVertexArrayObject>) {
+        if let Self::WebGl2(gl) = self {
+            gl.delete_vertex_array(vao);
+        }
+    }
+
+    /// Native on WebGL2, otherwise `ANGLE_instanced_arrays` if the browser exposes it.
+    fn instancing(&self) -> Option<Instancing> {
+        match self {
+            Self::WebGl2(gl) => Some(Instancing::Native(gl.clone())),
+            Self::WebGl1(gl) => gl
+                .get_extension("ANGLE_instanced_arrays")
+                .ok()
+                .flatten()
+                .map(|ext| Instancing::Angle(ext.unchecked_into())),
+        }
+    }
+
+    fn enable_extension(&self, name: &str) -> bool {
+        with_context!(self, gl => gl.get_extension(name).ok().flatten().is_some())
+    }
+
+    /// A zero-precision format is how WebGL reports an unimplemented one.
+    fn fragment_highp_supported(&self) -> bool {
+        with_context!(self, gl => gl
+            .get_shader_precision_format(
+                WebGlRenderingContext::FRAGMENT_SHADER,
+                WebGlRenderingContext::HIGH_FLOAT,
+            )
+            .is_some_and(|format| format.precision() > 0))
+    }
+
+    fn shader_compiled(&self, shader: &WebGlShader) -> bool {
+        with_context!(self, gl => gl
+            .get_shader_parameter(shader, WebGlRenderingContext::COMPILE_STATUS)
+            .as_bool()
+            .unwrap_or(false))
+    }
+
+    fn program_linked(&self, program: &WebGlProgram) -> bool {
+        with_context!(self, gl => gl
+            .get_program_parameter(program, WebGlRenderingContext::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false))
+    }
+
+    fn buffer_data_f32(&self, target: u32, data: &[f32], usage: u32) {
+        // The views borrow wasm memory; nothing allocates before the upload copies them.
+        unsafe {
+            let view = js_sys::Float32Array::view(data);
+            with_context!(self, gl => gl.buffer_data_with_array_buffer_view(target, &view, usage));
+        }
+    }
+
+    fn buffer_data_u16(&self, target: u32, data: &[u16], usage: u32) {
+        unsafe {
+            let view = js_sys::Uint16Array::view(data);
+            with_context!(self, gl => gl.buffer_data_with_array_buffer_view(target, &view, usage));
+        }
+    }
+
+    fn buffer_data_u32(&self, target: u32, data: &[u32], usage: u32) {
+        unsafe {
+            let view = js_sys::Uint32Array::view(data);
+            with_context!(self, gl => gl.buffer_data_with_array_buffer_view(target, &view, usage));
+        }
+    }
+
+    fn tex_image_2d_rgba(
+        &self,
+        target: u32,
+        width: i32,
+        height: i32,
+        pixels: &[u8],
+    ) -> Result<(), JsValue> {
+        with_context!(self, gl => gl
+        .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            target,
+            0,
+            WebGlRenderingContext::RGBA as i32,
+            width,
+            height,
+            0,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(pixels),
+        ))
+    }
+
+    fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        pixels: &mut [u8],
+    ) -> Result<(), JsValue> {
+        with_context!(self, gl => gl.read_pixels_with_opt_u8_array(
+            x,
+            y,
+            width,
+            height,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(pixels),
+        ))
+    }
+
+    /// 0 outside a window (e.g. in a worker).
+    fn now_ms(&self) -> f64 {
+        web_sys::window()
+            .and_then(|w| w.performance())
+            .map_or(0.0, |p| p.now())
+    }
+
+    forward! {
+        fn active_texture(&self, texture: u32);
+        fn attach_shader(&self, program: &WebGlProgram, shader: &WebGlShader);
+        fn bind_buffer(&self, target: u32, buffer: Option<&WebGlBuffer>);
+        fn bind_texture(&self, target: u32, texture: Option<&WebGlTexture>);
+        fn blend_func_separate(
+            &self,
+            src_rgb: u32,
+            dst_rgb: u32,
+            src_alpha: u32,
+            dst_alpha: u32
+        );
+        fn clear(&self, mask: u32);
+        fn clear_color(&self, r: f32, g: f32, b: f32, a: f32);
+        fn compile_shader(&self, shader: &WebGlShader);
+        fn create_buffer(&self) -> Option<WebGlBuffer>;
+        fn create_program(&self) -> Option<WebGlProgram>;
+        fn create_shader(&self, ty: u32) -> Option<WebGlShader>;
+        fn create_texture(&self) -> Option<WebGlTexture>;
+        fn delete_buffer(&self, buffer: Option<&WebGlBuffer>);
+        fn delete_program(&self, program: Option<&WebGlProgram>);
+        fn delete_shader(&self, shader: Option<&WebGlShader>);
+        fn delete_texture(&self, texture: Option<&WebGlTexture>);
+        fn depth_mask(&self, flag: bool);
+        fn disable(&self, cap: u32);
+        fn disable_vertex_attrib_array(&self, index: u32);
+        fn draw_arrays(&self, mode: u32, first: i32, count: i32);
+        fn draw_elements_with_i32(&self, mode: u32, count: i32, ty: u32, offset: i32);
+        fn enable(&self, cap: u32);
+        fn enable_vertex_attrib_array(&self, index: u32);
+        fn front_face(&self, mode: u32);
+        fn generate_mipmap(&self, target: u32);
+        fn get_attrib_location(&self, program: &WebGlProgram, name: &str) -> i32;
+        fn get_error(&self) -> u32;
+        fn get_program_info_log(&self, program: &WebGlProgram) -> Option<String>;
+        fn get_shader_info_log(&self, shader: &WebGlShader) -> Option<String>;
+        fn get_uniform_location(&self, program: &WebGlProgram, name: &str)
+            -> Option<WebGlUniformLocation>;
+        fn is_context_lost(&self) -> bool;
+        fn link_program(&self, program: &WebGlProgram);
+        fn polygon_offset(&self, factor: f32, units: f32);
+        fn shader_source(&self, shader: &WebGlShader, src: &str);
+        fn stencil_func(&self, func: u32, ref_: i32, mask: u32);
+        fn stencil_mask(&self, mask: u32);
+        fn stencil_op(&self, fail: u32, zfail: u32, zpass: u32);
+        fn tex_parameteri(&self, target: u32, pname: u32, param: i32);
+        fn uniform1f(&self, location: Option<&WebGlUniformLocation>, x: f32);
+        fn uniform1i(&self, location: Option<&WebGlUniformLocation>, x: i32);
+        fn uniform2fv_with_f32_array(&self, location: Option<&WebGlUniformLocation>, data: &[f32]);
+        fn uniform3fv_with_f32_array(&self, location: Option<&WebGlUniformLocation>, data: &[f32]);
+        fn uniform_matrix3fv_with_f32_array(
+            &self,
+            location: Option<&WebGlUniformLocation>,
+            transpose: bool,
+            data: &[f32]
+        );
+        fn uniform_matrix4fv_with_f32_array(
+            &self,
+            location: Option<&WebGlUniformLocation>,
+            transpose: bool,
+            data: &[f32]
+        );
+        fn use_program(&self, program: Option<&WebGlProgram>);
+        fn vertex_attrib_pointer_with_i32(
+            &self,
+            index: u32,
+            size: i32,
+            ty: u32,
+            normalized: bool,
+            stride: i32,
+            offset: i32
+        );
+        fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+    }
+}
+
+/// `Gl`'s instanced draws: native on WebGL2, `ANGLE_instanced_arrays` on WebGL1.
+#[derive(Clone)]
+pub(crate) enum Instancing {
+    Native(WebGl2RenderingContext),
+    Angle(AngleInstancedArrays),
+}
+
+impl InstancedDraw for Instancing {
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        match self {
+            Self::Native(gl) => gl.vertex_attrib_divisor(index, divisor),
+            Self::Angle(ext) => ext.vertex_attrib_divisor_angle(index, divisor),
+        }
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instances: i32) {
+        match self {
+            Self::Native(gl) => gl.draw_arrays_instanced(mode, first, count, instances),
+            Self::Angle(ext) => ext.draw_arrays_instanced_angle(mode, first, count, instances),
+        }
+    }
+
+    fn draw_elements_instanced(&self, mode: u32, count: i32, ty: u32, offset: i32, instances: i32) {
+        match self {
+            Self::Native(gl) => {
+                gl.draw_elements_instanced_with_i32(mode, count, ty, offset, instances)
+            }
+            Self::Angle(ext) => {
+                ext.draw_elements_instanced_angle_with_i32(mode, count, ty, offset, instances)
+            }
+        }
+    }
+}
+
+/// Turn the context's pending error flag into a JS error naming `context`, e.g.
+/// "uploading mesh: OUT_OF_MEMORY". Errors raised by earlier calls are reported too.
+pub(crate) fn check_gl_error<B: GraphicsBackend>(gl: &B, context: &str) -> Result<(), JsValue> {
+    match gl.get_error() {
+        WebGlRenderingContext::NO_ERROR => Ok(()),
+        code => Err(JsValue::from_str(&format!(
+            "{context}: {}",
+            gl_error_name(code)
+        ))),
+    }
+}
+
+fn gl_error_name(code: u32) -> String {
+    match code {
+        WebGlRenderingContext::INVALID_ENUM => "INVALID_ENUM".to_string(),
+        WebGlRenderingContext::INVALID_VALUE => "INVALID_VALUE".to_string(),
+        WebGlRenderingContext::INVALID_OPERATION => "INVALID_OPERATION".to_string(),
+        WebGlRenderingContext::INVALID_FRAMEBUFFER_OPERATION => {
+            "INVALID_FRAMEBUFFER_OPERATION".to_string()
+        }
+        WebGlRenderingContext::OUT_OF_MEMORY => "OUT_OF_MEMORY".to_string(),
+        WebGlRenderingContext::CONTEXT_LOST_WEBGL => "CONTEXT_LOST_WEBGL".to_string(),
+        other => format!("GL error 0x{other:04X}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_have_readable_names() {
+        assert_eq!(gl_error_name(0x0505), "OUT_OF_MEMORY");
+        assert_eq!(gl_error_name(0x0502), "INVALID_OPERATION");
+        assert_eq!(gl_error_name(0x1234), "GL error 0x1234");
+    }
+}
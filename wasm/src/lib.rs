@@ -3,17 +3,23 @@ use web_sys::*;
 
 mod camera;
 mod geometry;
+mod gl;
+mod lines;
 mod math;
+mod pick;
+mod png;
+#[cfg(test)]
+mod raster;
 mod renderer;
 mod shader;
 mod viewer;
-use renderer::Renderer;
+use renderer::{Placement, Renderer};
 pub use viewer::Viewer;
 
 #[wasm_bindgen]
 pub fn start(canvas_id: &str) -> Result<(), JsValue> {
     let canvas = get_canvas_by_id(canvas_id)?;
-    let gl = get_webgl_context(&canvas)?;
+    let gl = gl::Gl::from_canvas(&canvas, true, true)?;
 
     let mut renderer = Renderer::new(gl)?;
     let width = canvas.width() as i32;
@@ -22,12 +28,42 @@ pub fn start(canvas_id: &str) -> Result<(), JsValue> {
     let view = crate::math::Mat4::identity();
     let model = crate::math::Mat4::identity();
     let mesh = geometry::triangle();
-    renderer.set_mesh(&mesh);
-    renderer.draw(width, height, &proj.m, &view.m, &model.m);
+    renderer.set_mesh(&mesh)?;
+    let normal_matrix = view.mul(model).normal_matrix();
+    let placement = Placement::Single {
+        model: &model.m,
+        normal_matrix: &normal_matrix,
+    };
+    renderer.draw(width, height, &proj.m, &view.m, placement);
 
     Ok(())
 }
 
+/// Route Rust panics to `console.error` with the panic message and a JS stack trace,
+/// instead of the bare "unreachable" trap. Safe to call repeatedly; `Viewer::new`
+/// calls it too.
+#[wasm_bindgen]
+pub fn start_diagnostics() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            let stack = js_sys::Reflect::get(&js_sys::Error::new(""), &"stack".into())
+                .ok()
+                .and_then(|s| s.as_string());
+            console::error_1(&panic_report(&info.to_string(), stack.as_deref()).into());
+        }));
+    });
+}
+
+fn panic_report(message: &str, stack: Option<&str>) -> String {
+    // wasm frames only carry function names when the module keeps its name section.
+    let hint = "(build without stripping names, e.g. `wasm-pack build --dev`, for readable frames)";
+    match stack.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(stack) => format!("{message}\n\nStack:\n{stack}\n{hint}"),
+        None => format!("{message}\n\nNo stack trace available {hint}"),
+    }
+}
+
 fn get_canvas_by_id(canvas_id: &str) -> Result<HtmlCanvasElement, JsValue> {
     let window = web_sys::window().ok_or_else(|| JsValue::from_str("missing window"))?;
     let document = window
@@ -41,10 +77,43 @@ fn get_canvas_by_id(canvas_id: &str) -> Result<HtmlCanvasElement, JsValue> {
         .map_err(JsValue::from)
 }
 
-pub(crate) fn get_webgl_context(canvas: &HtmlCanvasElement) -> Result<WebGlRenderingContext, JsValue> {
+/// WebGL2 context, or `None` when the browser (or GPU blocklist) doesn't offer one.
+pub(crate) fn get_webgl2_context(
+    canvas: &HtmlCanvasElement,
+    attributes: &WebGlContextAttributes,
+) -> Result<Option<WebGl2RenderingContext>, JsValue> {
+    match canvas.get_context_with_context_options("webgl2", attributes)? {
+        Some(ctx) => ctx
+            .dyn_into::<WebGl2RenderingContext>()
+            .map(Some)
+            .map_err(JsValue::from),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn get_webgl_context(
+    canvas: &HtmlCanvasElement,
+    attributes: &WebGlContextAttributes,
+) -> Result<WebGlRenderingContext, JsValue> {
     let ctx = canvas
-        .get_context("webgl")?
+        .get_context_with_context_options("webgl", attributes)?
         .ok_or_else(|| JsValue::from_str("WebGL context unavailable"))?;
     ctx.dyn_into::<WebGlRenderingContext>()
         .map_err(JsValue::from)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_report_includes_message_and_stack() {
+        let report = panic_report(
+            "panicked at src/lib.rs:1:1:\nboom",
+            Some("  at f (x.wasm)\n"),
+        );
+        assert!(report.starts_with("panicked at src/lib.rs:1:1:\nboom\n\nStack:\n"));
+        assert!(report.contains("at f (x.wasm)"));
+        assert!(panic_report("boom", Some("  ")).contains("No stack trace available"));
+    }
+}
@@ -0,0 +1,359 @@
+use wasm_bindgen::prelude::*;
+use web_sys::WebGlRenderingContext;
+
+use crate::geometry::{LineQuads, Mesh};
+use crate::gl::{Gl, GraphicsBackend};
+use crate::shader::{
+    compile_shader, link_program, LINE_FRAGMENT_SHADER_SRC, LINE_VERTEX_SHADER_SRC,
+    THICK_LINE_VERTEX_SHADER_SRC,
+};
+
+/// Line overlay (ground grid, axes gizmo) drawn with its own unlit program.
+pub(crate) struct LinePass<B: GraphicsBackend = Gl> {
+    gl: B,
+    program: B::Program,
+    /// Vertex and fragment shader, kept to be deleted with the program.
+    shaders: [B::Shader; 2],
+    /// See `Renderer::vao`; `None` on WebGL1.
+    vao: Option<B::VertexArray>,
+    vbo: B::Buffer,
+    color_bo: B::Buffer,
+    position_location: u32,
+    /// -1 when the compiler stripped the attribute.
+    vertex_color_location: i32,
+    view_location: B::UniformLocation,
+    proj_location: B::UniformLocation,
+    color_location: B::UniformLocation,
+    use_vertex_color_location: B::UniformLocation,
+    vertex_count: i32,
+    /// Whether the current lines uploaded per-vertex colors.
+    has_vertex_colors: bool,
+}
+
+impl<B: GraphicsBackend> LinePass<B> {
+    pub(crate) fn new(gl: B) -> Result<Self, JsValue> {
+        let vs = compile_shader(
+            &gl,
+            WebGlRenderingContext::VERTEX_SHADER,
+            LINE_VERTEX_SHADER_SRC,
+        )?;
+        let fs = compile_shader(
+            &gl,
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            LINE_FRAGMENT_SHADER_SRC,
+        )?;
+        let program = link_program(&gl, &vs, &fs)?;
+
+        let vbo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create line buffer"))?;
+        let color_bo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create line color buffer"))?;
+        let position_location = gl.get_attrib_location(&program, "position") as u32;
+        let vertex_color_location = gl.get_attrib_location(&program, "a_color");
+        let view_location = gl
+            .get_uniform_location(&program, "u_view")
+            .ok_or_else(|| js_error("missing uniform u_view"))?;
+        let proj_location = gl
+            .get_uniform_location(&program, "u_proj")
+            .ok_or_else(|| js_error("missing uniform u_proj"))?;
+        let color_location = gl
+            .get_uniform_location(&program, "u_color")
+            .ok_or_else(|| js_error("missing uniform u_color"))?;
+        let use_vertex_color_location = gl
+            .get_uniform_location(&program, "u_use_vertex_color")
+            .ok_or_else(|| js_error("missing uniform u_use_vertex_color"))?;
+
+        let vao = gl.create_vertex_array();
+        let pass = Self {
+            gl,
+            program,
+            shaders: [vs, fs],
+            vao,
+            vbo,
+            color_bo,
+            position_location,
+            vertex_color_location,
+            view_location,
+            proj_location,
+            color_location,
+            use_vertex_color_location,
+            vertex_count: 0,
+            has_vertex_colors: false,
+        };
+        pass.record_attributes();
+        Ok(pass)
+    }
+
+    /// Upload the segments of a `Topology::Lines` mesh, with its colors if it has any.
+    pub(crate) fn set_lines(&mut self, mesh: &Mesh) {
+        upload_f32(&self.gl, &self.vbo, &mesh.positions);
+        self.vertex_count = (mesh.positions.len() / 3) as i32;
+        self.has_vertex_colors = !mesh.colors.is_empty() && self.vertex_color_location >= 0;
+        if self.has_vertex_colors {
+            upload_f32(&self.gl, &self.color_bo, &mesh.colors);
+        }
+        self.record_attributes();
+    }
+
+    /// Draw with the current viewport and depth state. Leaves the line program bound.
+    /// `color` applies when the lines have no per-vertex colors. Returns the number of
+    /// vertices drawn (0 when there was nothing to draw).
+    pub(crate) fn draw(&self, proj: &[f32; 16], view: &[f32; 16], color: [f32; 3]) -> i32 {
+        if self.vertex_count == 0 {
+            return 0;
+        }
+        self.gl.use_program(Some(&self.program));
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&self.view_location), false, view);
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&self.proj_location), false, proj);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&self.color_location), &color);
+        self.gl.uniform1i(
+            Some(&self.use_vertex_color_location),
+            self.has_vertex_colors as i32,
+        );
+
+        self.gl.bind_vertex_array(self.vao.as_ref());
+        if self.vao.is_none() {
+            self.enable_attributes();
+        }
+        self.gl
+            .draw_arrays(WebGlRenderingContext::LINES, 0, self.vertex_count);
+        if self.vao.is_none() {
+            self.gl.disable_vertex_attrib_array(self.position_location);
+            if self.vertex_color_location >= 0 {
+                self.gl
+                    .disable_vertex_attrib_array(self.vertex_color_location as u32);
+            }
+        }
+        self.vertex_count
+    }
+
+    /// Free the program, shaders and buffers. The pass must not draw afterwards.
+    pub(crate) fn delete(&self) {
+        self.gl.delete_buffer(Some(&self.vbo));
+        self.gl.delete_buffer(Some(&self.color_bo));
+        self.gl.delete_vertex_array(self.vao.as_ref());
+        self.gl.delete_program(Some(&self.program));
+        for shader in &self.shaders {
+            self.gl.delete_shader(Some(shader));
+        }
+    }
+
+    /// Store the attribute setup in the VAO (WebGL2); WebGL1 sets it up in `draw`.
+    fn record_attributes(&self) {
+        if self.vao.is_some() {
+            self.gl.bind_vertex_array(self.vao.as_ref());
+            self.enable_attributes();
+            self.gl.bind_vertex_array(None);
+        }
+    }
+
+    fn enable_attributes(&self) {
+        let attributes = [
+            (self.position_location as i32, &self.vbo, true),
+            (
+                self.vertex_color_location,
+                &self.color_bo,
+                self.has_vertex_colors,
+            ),
+        ];
+        for (location, buffer, used) in attributes {
+            if location < 0 {
+                continue;
+            }
+            if used {
+                self.gl
+                    .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+                self.gl.vertex_attrib_pointer_with_i32(
+                    location as u32,
+                    3,
+                    WebGlRenderingContext::FLOAT,
+                    false,
+                    0,
+                    0,
+                );
+                self.gl.enable_vertex_attrib_array(location as u32);
+            } else {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+        }
+    }
+}
+
+/// Lines wider than one pixel, drawn as quads that the vertex shader turns to face the
+/// screen. Browsers clamp `lineWidth` to 1 almost everywhere, so `LINES` can't do this.
+pub(crate) struct ThickLinePass<B: GraphicsBackend = Gl> {
+    gl: B,
+    program: B::Program,
+    shaders: [B::Shader; 2],
+    /// See `Renderer::vao`; `None` on WebGL1.
+    vao: Option<B::VertexArray>,
+    vbo: B::Buffer,
+    other_bo: B::Buffer,
+    side_bo: B::Buffer,
+    /// -1 when the compiler stripped the attribute.
+    position_location: i32,
+    other_location: i32,
+    side_location: i32,
+    view_location: B::UniformLocation,
+    proj_location: B::UniformLocation,
+    viewport_location: B::UniformLocation,
+    width_location: B::UniformLocation,
+    color_location: B::UniformLocation,
+    use_vertex_color_location: B::UniformLocation,
+    vertex_count: i32,
+}
+
+impl<B: GraphicsBackend> ThickLinePass<B> {
+    pub(crate) fn new(gl: B) -> Result<Self, JsValue> {
+        let vs = compile_shader(
+            &gl,
+            WebGlRenderingContext::VERTEX_SHADER,
+            THICK_LINE_VERTEX_SHADER_SRC,
+        )?;
+        let fs = compile_shader(
+            &gl,
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            LINE_FRAGMENT_SHADER_SRC,
+        )?;
+        let program = link_program(&gl, &vs, &fs)?;
+
+        let buffer = || {
+            gl.create_buffer()
+                .ok_or_else(|| js_error("failed to create thick line buffer"))
+        };
+        let uniform = |name: &str| {
+            gl.get_uniform_location(&program, name)
+                .ok_or_else(|| js_error(&format!("missing uniform {name}")))
+        };
+        let pass = Self {
+            view_location: uniform("u_view")?,
+            proj_location: uniform("u_proj")?,
+            viewport_location: uniform("u_viewport")?,
+            width_location: uniform("u_width")?,
+            color_location: uniform("u_color")?,
+            use_vertex_color_location: uniform("u_use_vertex_color")?,
+            position_location: gl.get_attrib_location(&program, "position"),
+            other_location: gl.get_attrib_location(&program, "a_other"),
+            side_location: gl.get_attrib_location(&program, "a_side"),
+            vao: gl.create_vertex_array(),
+            vbo: buffer()?,
+            other_bo: buffer()?,
+            side_bo: buffer()?,
+            vertex_count: 0,
+            program,
+            shaders: [vs, fs],
+            gl,
+        };
+        if pass.vao.is_some() {
+            pass.gl.bind_vertex_array(pass.vao.as_ref());
+            pass.enable_attributes();
+            pass.gl.bind_vertex_array(None);
+        }
+        Ok(pass)
+    }
+
+    /// Upload new quads; an empty set draws nothing.
+    pub(crate) fn set_quads(&mut self, quads: &LineQuads) {
+        upload_f32(&self.gl, &self.vbo, &quads.positions);
+        upload_f32(&self.gl, &self.other_bo, &quads.others);
+        upload_f32(&self.gl, &self.side_bo, &quads.sides);
+        self.vertex_count = quads.sides.len() as i32;
+    }
+
+    /// Draw `width` pixels wide in `color` over a `viewport` of that many pixels, with
+    /// the current depth state. Leaves the program bound. Returns the vertices drawn.
+    pub(crate) fn draw(
+        &self,
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        viewport: [f32; 2],
+        width: f32,
+        color: [f32; 3],
+    ) -> i32 {
+        if self.vertex_count == 0 {
+            return 0;
+        }
+        self.gl.use_program(Some(&self.program));
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&self.view_location), false, view);
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&self.proj_location), false, proj);
+        self.gl
+            .uniform2fv_with_f32_array(Some(&self.viewport_location), &viewport);
+        self.gl.uniform1f(Some(&self.width_location), width);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&self.color_location), &color);
+        self.gl.uniform1i(Some(&self.use_vertex_color_location), 0);
+
+        self.gl.bind_vertex_array(self.vao.as_ref());
+        if self.vao.is_none() {
+            self.enable_attributes();
+        }
+        self.gl
+            .draw_arrays(WebGlRenderingContext::TRIANGLES, 0, self.vertex_count);
+        if self.vao.is_none() {
+            for location in self.locations().into_iter().filter(|&l| l >= 0) {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+        }
+        self.vertex_count
+    }
+
+    /// Free the program, shaders and buffers. The pass must not draw afterwards.
+    pub(crate) fn delete(&self) {
+        for buffer in [&self.vbo, &self.other_bo, &self.side_bo] {
+            self.gl.delete_buffer(Some(buffer));
+        }
+        self.gl.delete_vertex_array(self.vao.as_ref());
+        self.gl.delete_program(Some(&self.program));
+        for shader in &self.shaders {
+            self.gl.delete_shader(Some(shader));
+        }
+    }
+
+    fn locations(&self) -> [i32; 3] {
+        [
+            self.position_location,
+            self.other_location,
+            self.side_location,
+        ]
+    }
+
+    fn enable_attributes(&self) {
+        let buffers = [(&self.vbo, 3), (&self.other_bo, 3), (&self.side_bo, 1)];
+        for (location, (buffer, size)) in self.locations().into_iter().zip(buffers) {
+            if location < 0 {
+                continue;
+            }
+            self.gl
+                .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+            self.gl.vertex_attrib_pointer_with_i32(
+                location as u32,
+                size,
+                WebGlRenderingContext::FLOAT,
+                false,
+                0,
+                0,
+            );
+            self.gl.enable_vertex_attrib_array(location as u32);
+        }
+    }
+}
+
+fn upload_f32<B: GraphicsBackend>(gl: &B, buffer: &B::Buffer, data: &[f32]) {
+    gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+    gl.buffer_data_f32(
+        WebGlRenderingContext::ARRAY_BUFFER,
+        data,
+        WebGlRenderingContext::STATIC_DRAW,
+    );
+}
+
+fn js_error(msg: &str) -> JsValue {
+    JsValue::from_str(msg)
+}
@@ -22,6 +22,38 @@ impl Vec3 {
         Vec3::new(self.x * s, self.y * s, self.z * s)
     }
 
+    /// Divide by `s`; like `normalize`, a near-zero divisor returns `self` unchanged.
+    pub fn div(self, s: f32) -> Vec3 {
+        if s.abs() <= 1e-8 {
+            self
+        } else {
+            self.mul(1.0 / s)
+        }
+    }
+
+    /// Component-wise product.
+    pub fn mul_vec(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+
+    /// Component-wise minimum.
+    pub fn min(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// Component-wise maximum.
+    pub fn max(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
     pub fn dot(self, other: Vec3) -> f32 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
@@ -34,17 +66,24 @@ impl Vec3 {
         )
     }
 
+    /// Linear interpolation: `self` at `t = 0`, `other` at `t = 1`.
+    #[allow(dead_code)] // for camera animation; no caller yet
+    pub fn lerp(self, other: Vec3, t: f32) -> Vec3 {
+        self.add(other.sub(self).mul(t))
+    }
+
+    /// Mirror `self` about the plane with unit normal `normal`: `v - 2(v.n)n`.
+    #[allow(dead_code)] // for shading effects; no caller yet
+    pub fn reflect(self, normal: Vec3) -> Vec3 {
+        self.sub(normal.mul(2.0 * self.dot(normal)))
+    }
+
     pub fn length(self) -> f32 {
         self.dot(self).sqrt()
     }
 
     pub fn normalize(self) -> Vec3 {
-        let len = self.length();
-        if len <= 1e-8 {
-            self
-        } else {
-            self.mul(1.0 / len)
-        }
+        self.div(self.length())
     }
 }
 
@@ -66,6 +105,7 @@ impl Mat4 {
         }
     }
 
+    #[allow(clippy::identity_op, clippy::erasing_op)]
     pub fn mul(self, b: Mat4) -> Mat4 {
         // Column-major multiplication: out = self * b
         let a = self.m;
@@ -82,6 +122,104 @@ impl Mat4 {
         Mat4 { m: out }
     }
 
+    /// Column-major matrix-vector product: out = self * v.
+    pub fn mul_vec4(self, v: [f32; 4]) -> [f32; 4] {
+        let a = self.m;
+        let mut out = [0.0f32; 4];
+        for (row, o) in out.iter_mut().enumerate() {
+            *o = a[row] * v[0] + a[4 + row] * v[1] + a[8 + row] * v[2] + a[12 + row] * v[3];
+        }
+        out
+    }
+
+    /// Transform a point (w = 1) and apply the perspective divide.
+    pub fn mul_point(self, p: Vec3) -> Vec3 {
+        let [x, y, z, w] = self.mul_vec4([p.x, p.y, p.z, 1.0]);
+        if w.abs() <= 1e-8 {
+            Vec3::new(x, y, z)
+        } else {
+            Vec3::new(x / w, y / w, z / w)
+        }
+    }
+
+    #[allow(dead_code)] // normal_matrix inverts the 3x3 directly; no caller transposes yet
+    pub fn transpose(self) -> Mat4 {
+        let a = self.m;
+        let mut out = [0.0f32; 16];
+        for col in 0..4 {
+            for row in 0..4 {
+                out[col * 4 + row] = a[row * 4 + col];
+            }
+        }
+        Mat4 { m: out }
+    }
+
+    /// Inverse-transpose of the upper-left 3x3, column-major, for transforming normals.
+    /// Falls back to the plain 3x3 when it is singular.
+    pub fn normal_matrix(self) -> [f32; 9] {
+        let a = self.m;
+        let c0 = Vec3::new(a[0], a[1], a[2]);
+        let c1 = Vec3::new(a[4], a[5], a[6]);
+        let c2 = Vec3::new(a[8], a[9], a[10]);
+
+        let det = c0.dot(c1.cross(c2));
+        if det.abs() <= 1e-8 {
+            return [a[0], a[1], a[2], a[4], a[5], a[6], a[8], a[9], a[10]];
+        }
+        // Rows of the inverse are the cross products / det; transposed, they become columns.
+        let inv_det = 1.0 / det;
+        let x = c1.cross(c2).mul(inv_det);
+        let y = c2.cross(c0).mul(inv_det);
+        let z = c0.cross(c1).mul(inv_det);
+        [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z]
+    }
+
+    /// General 4x4 inverse via cofactor expansion.
+    /// Returns `None` when the matrix is (near) singular.
+    pub fn inverse(self) -> Option<Mat4> {
+        let a = self.m;
+
+        let b00 = a[0] * a[5] - a[1] * a[4];
+        let b01 = a[0] * a[6] - a[2] * a[4];
+        let b02 = a[0] * a[7] - a[3] * a[4];
+        let b03 = a[1] * a[6] - a[2] * a[5];
+        let b04 = a[1] * a[7] - a[3] * a[5];
+        let b05 = a[2] * a[7] - a[3] * a[6];
+        let b06 = a[8] * a[13] - a[9] * a[12];
+        let b07 = a[8] * a[14] - a[10] * a[12];
+        let b08 = a[8] * a[15] - a[11] * a[12];
+        let b09 = a[9] * a[14] - a[10] * a[13];
+        let b10 = a[9] * a[15] - a[11] * a[13];
+        let b11 = a[10] * a[15] - a[11] * a[14];
+
+        let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+        if det.abs() <= 1e-8 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        Some(Mat4 {
+            m: [
+                (a[5] * b11 - a[6] * b10 + a[7] * b09) * inv_det,
+                (a[2] * b10 - a[1] * b11 - a[3] * b09) * inv_det,
+                (a[13] * b05 - a[14] * b04 + a[15] * b03) * inv_det,
+                (a[10] * b04 - a[9] * b05 - a[11] * b03) * inv_det,
+                (a[6] * b08 - a[4] * b11 - a[7] * b07) * inv_det,
+                (a[0] * b11 - a[2] * b08 + a[3] * b07) * inv_det,
+                (a[14] * b02 - a[12] * b05 - a[15] * b01) * inv_det,
+                (a[8] * b05 - a[10] * b02 + a[11] * b01) * inv_det,
+                (a[4] * b10 - a[5] * b08 + a[7] * b06) * inv_det,
+                (a[1] * b08 - a[0] * b10 - a[3] * b06) * inv_det,
+                (a[12] * b04 - a[13] * b02 + a[15] * b00) * inv_det,
+                (a[9] * b02 - a[8] * b04 - a[11] * b00) * inv_det,
+                (a[5] * b07 - a[4] * b09 - a[6] * b06) * inv_det,
+                (a[0] * b09 - a[1] * b07 + a[2] * b06) * inv_det,
+                (a[13] * b01 - a[12] * b03 - a[14] * b00) * inv_det,
+                (a[8] * b03 - a[9] * b01 + a[10] * b00) * inv_det,
+            ],
+        })
+    }
+
     pub fn translation(v: Vec3) -> Mat4 {
         let mut m = Mat4::identity().m;
         m[12] = v.x;
@@ -90,6 +228,52 @@ impl Mat4 {
         Mat4 { m }
     }
 
+    pub fn scaling(v: Vec3) -> Mat4 {
+        let mut m = Mat4::identity().m;
+        m[0] = v.x;
+        m[5] = v.y;
+        m[10] = v.z;
+        Mat4 { m }
+    }
+
+    #[allow(dead_code)] // the model rotation goes through Quat; kept for one-axis turns
+    pub fn rotation_x(angle_radians: f32) -> Mat4 {
+        let (s, c) = angle_radians.sin_cos();
+        Mat4 {
+            m: [
+                1.0, 0.0, 0.0, 0.0, //
+                0.0, c, s, 0.0, //
+                0.0, -s, c, 0.0, //
+                0.0, 0.0, 0.0, 1.0, //
+            ],
+        }
+    }
+
+    pub fn rotation_y(angle_radians: f32) -> Mat4 {
+        let (s, c) = angle_radians.sin_cos();
+        Mat4 {
+            m: [
+                c, 0.0, -s, 0.0, //
+                0.0, 1.0, 0.0, 0.0, //
+                s, 0.0, c, 0.0, //
+                0.0, 0.0, 0.0, 1.0, //
+            ],
+        }
+    }
+
+    #[allow(dead_code)] // only the auto-rotate spin (rotation_y) is wired up so far
+    pub fn rotation_z(angle_radians: f32) -> Mat4 {
+        let (s, c) = angle_radians.sin_cos();
+        Mat4 {
+            m: [
+                c, s, 0.0, 0.0, //
+                -s, c, 0.0, 0.0, //
+                0.0, 0.0, 1.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0, //
+            ],
+        }
+    }
+
     pub fn perspective(fovy_radians: f32, aspect: f32, znear: f32, zfar: f32) -> Mat4 {
         let f = 1.0 / (0.5 * fovy_radians).tan();
         let nf = 1.0 / (znear - zfar);
@@ -168,6 +352,159 @@ impl Mat4 {
             ],
         }
     }
+
+    /// Flattens points onto `plane` (`[a, b, c, d]` with `a*x + b*y + c*z + d = 0`) along
+    /// `light_dir`, the direction a directional light travels: the matrix of a planar
+    /// projected shadow. Degenerate when the light runs parallel to the plane.
+    pub fn shadow_projection(plane: [f32; 4], light_dir: Vec3) -> Mat4 {
+        // dot(P, L) * I - L * P^T with the light as the point at infinity L = (dir, 0).
+        let light = [light_dir.x, light_dir.y, light_dir.z, 0.0];
+        let dot: f32 = plane.iter().zip(light).map(|(p, l)| p * l).sum();
+        let mut m = [0.0; 16];
+        for col in 0..4 {
+            for row in 0..4 {
+                let diagonal = if row == col { dot } else { 0.0 };
+                m[col * 4 + row] = diagonal - light[row] * plane[col];
+            }
+        }
+        Mat4 { m }
+    }
+}
+
+/// Rotation quaternion `x*i + y*j + z*k + w`, kept unit length by its constructors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+// Groundwork for arcball rotation and view interpolation; not every method has a caller.
+impl Quat {
+    pub fn identity() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        }
+    }
+
+    /// Right-handed rotation by `angle_radians` around `axis` (normalized here; a zero
+    /// axis gives the identity).
+    pub fn from_axis_angle(axis: Vec3, angle_radians: f32) -> Self {
+        let len = axis.length();
+        if len <= 1e-8 {
+            return Self::identity();
+        }
+        let (s, c) = (angle_radians * 0.5).sin_cos();
+        let a = axis.mul(s / len);
+        Self {
+            x: a.x,
+            y: a.y,
+            z: a.z,
+            w: c,
+        }
+    }
+
+    /// Hamilton product: the rotation `b` followed by `self`.
+    pub fn mul(self, b: Quat) -> Quat {
+        let a = self;
+        Quat {
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        }
+    }
+
+    pub fn dot(self, b: Quat) -> f32 {
+        self.x * b.x + self.y * b.y + self.z * b.z + self.w * b.w
+    }
+
+    /// Rescale to unit length, undoing drift from repeated products. A degenerate
+    /// quaternion becomes the identity.
+    pub fn normalize(self) -> Quat {
+        let len = self.dot(self).sqrt();
+        if len.is_nan() || len <= 1e-8 {
+            return Self::identity();
+        }
+        Quat {
+            x: self.x / len,
+            y: self.y / len,
+            z: self.z / len,
+            w: self.w / len,
+        }
+    }
+
+    /// Constant-speed interpolation along the shorter arc; `t` = 0 gives `self`, 1 gives
+    /// `other` (or its negation, the same rotation).
+    #[allow(dead_code)] // for smooth view interpolation; no caller until views animate
+    pub fn slerp(self, other: Quat, t: f32) -> Quat {
+        let mut cos = self.dot(other);
+        let mut b = other;
+        if cos < 0.0 {
+            cos = -cos;
+            b = Quat {
+                x: -b.x,
+                y: -b.y,
+                z: -b.z,
+                w: -b.w,
+            };
+        }
+        // Nearly parallel: the sine below vanishes, and a plain lerp is just as accurate.
+        let (wa, wb) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+        Quat {
+            x: self.x * wa + b.x * wb,
+            y: self.y * wa + b.y * wb,
+            z: self.z * wa + b.z * wb,
+            w: self.w * wa + b.w * wb,
+        }
+        .normalize()
+    }
+
+    /// Rotate a vector.
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let q = Vec3::new(self.x, self.y, self.z);
+        // v + 2w(q x v) + 2 q x (q x v)
+        let t = q.cross(v).mul(2.0);
+        v.add(t.mul(self.w)).add(q.cross(t))
+    }
+
+    /// Column-major rotation matrix of a unit quaternion.
+    pub fn to_mat4(self) -> Mat4 {
+        let Quat { x, y, z, w } = self;
+        let (xx, yy, zz) = (x * x, y * y, z * z);
+        let (xy, xz, yz) = (x * y, x * z, y * z);
+        let (wx, wy, wz) = (w * x, w * y, w * z);
+        Mat4 {
+            m: [
+                1.0 - 2.0 * (yy + zz),
+                2.0 * (xy + wz),
+                2.0 * (xz - wy),
+                0.0, //
+                2.0 * (xy - wz),
+                1.0 - 2.0 * (xx + zz),
+                2.0 * (yz + wx),
+                0.0, //
+                2.0 * (xz + wy),
+                2.0 * (yz - wx),
+                1.0 - 2.0 * (xx + yy),
+                0.0, //
+                0.0,
+                0.0,
+                0.0,
+                1.0, //
+            ],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -204,6 +541,76 @@ mod tests {
         assert!(approx_eq(u.length(), 1.0, 1e-6));
     }
 
+    #[test]
+    fn vec3_div_guards_near_zero() {
+        let v = Vec3::new(2.0, -4.0, 6.0);
+        assert_vec3_approx(v.div(2.0), Vec3::new(1.0, -2.0, 3.0), 0.0);
+        assert_vec3_approx(v.div(0.0), v, 0.0);
+        assert_vec3_approx(v.div(1e-9), v, 0.0);
+        assert_vec3_approx(v.div(-1e-9), v, 0.0);
+    }
+
+    #[test]
+    fn vec3_lerp_and_reflect() {
+        let a = Vec3::new(1.0, -2.0, 4.0);
+        let b = Vec3::new(3.0, 2.0, -4.0);
+        assert_vec3_approx(a.lerp(b, 0.5), Vec3::new(2.0, 0.0, 0.0), 0.0);
+        assert_vec3_approx(a.lerp(b, 0.0), a, 0.0);
+        assert_vec3_approx(a.lerp(b, 1.0), b, 0.0);
+
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let down = Vec3::new(1.0, -1.0, 0.0);
+        assert_vec3_approx(down.reflect(up), Vec3::new(1.0, 1.0, 0.0), 0.0);
+        // Vectors in the mirror plane are unchanged.
+        let level = Vec3::new(2.0, 0.0, 5.0);
+        assert_vec3_approx(level.reflect(up), level, 0.0);
+    }
+
+    #[test]
+    fn vec3_component_wise_ops() {
+        let a = Vec3::new(1.0, -2.0, 3.0);
+        let b = Vec3::new(4.0, 5.0, -6.0);
+        assert_vec3_approx(a.mul_vec(b), Vec3::new(4.0, -10.0, -18.0), 0.0);
+        assert_vec3_approx(a.min(b), Vec3::new(1.0, -2.0, -6.0), 0.0);
+        assert_vec3_approx(a.max(b), Vec3::new(4.0, 5.0, 3.0), 0.0);
+    }
+
+    fn assert_mat4_approx(a: &[f32; 16], b: &[f32; 16], eps: f32) {
+        for (i, (x, y)) in a.iter().zip(b).enumerate() {
+            assert!(approx_eq(*x, *y, eps), "index {i}: {x} vs {y}");
+        }
+    }
+
+    #[test]
+    fn quat_axis_angle_matches_rotation_matrices() {
+        let half_pi = std::f32::consts::FRAC_PI_2;
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), half_pi);
+        assert_mat4_approx(&q.to_mat4().m, &Mat4::rotation_y(half_pi).m, 1e-5);
+
+        // Unnormalized axes are accepted; products compose like the matrices.
+        let qx = Quat::from_axis_angle(Vec3::new(3.0, 0.0, 0.0), 0.7);
+        let qz = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), -1.2);
+        let expected = Mat4::rotation_x(0.7).mul(Mat4::rotation_z(-1.2));
+        assert_mat4_approx(&qx.mul(qz).to_mat4().m, &expected.m, 1e-5);
+
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        let rotated = Mat4::rotation_x(0.7).mul_point(v);
+        assert_vec3_approx(qx.rotate(v), rotated, 1e-5);
+    }
+
+    #[test]
+    fn quat_slerp_hits_endpoints_and_midpoint() {
+        let a = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 0.2);
+        let b = Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 2.0);
+        let close = |p: Quat, q: Quat| p.dot(q).abs() > 1.0 - 1e-6;
+        assert!(close(a.slerp(b, 0.0), a));
+        assert!(close(a.slerp(b, 1.0), b));
+
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let half = Quat::identity().slerp(Quat::from_axis_angle(y, 1.0), 0.5);
+        assert!(close(half, Quat::from_axis_angle(y, 0.5)));
+    }
+
     #[test]
     fn mat4_identity_mul_is_noop() {
         let i = Mat4::identity();
@@ -223,6 +630,177 @@ mod tests {
         assert!(approx_eq(t.m[15], 1.0, 0.0));
     }
 
+    #[test]
+    fn mat4_scaling_is_diagonal() {
+        let s = Mat4::scaling(Vec3::new(2.0, 3.0, 4.0));
+        assert!(approx_eq(s.m[0], 2.0, 0.0));
+        assert!(approx_eq(s.m[5], 3.0, 0.0));
+        assert!(approx_eq(s.m[10], 4.0, 0.0));
+        assert!(approx_eq(s.m[15], 1.0, 0.0));
+        // Everything off the diagonal stays zero.
+        for (i, v) in s.m.iter().enumerate() {
+            if i % 5 != 0 {
+                assert!(approx_eq(*v, 0.0, 0.0), "index {i} = {v}");
+            }
+        }
+    }
+
+    #[test]
+    fn mat4_scaling_identity_mul_is_noop() {
+        let i = Mat4::identity();
+        let s = Mat4::scaling(Vec3::new(0.5, 2.0, -1.0));
+
+        assert_eq!(i.mul(s).m, s.m);
+        assert_eq!(s.mul(i).m, s.m);
+    }
+
+    #[test]
+    fn mat4_translation_times_scaling_scales_then_translates() {
+        let t = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+        let s = Mat4::scaling(Vec3::new(2.0, 3.0, 4.0));
+        let m = t.mul(s).m;
+        // Diagonal carries the scale, translation column is untouched by it.
+        assert!(approx_eq(m[0], 2.0, 0.0));
+        assert!(approx_eq(m[5], 3.0, 0.0));
+        assert!(approx_eq(m[10], 4.0, 0.0));
+        assert!(approx_eq(m[12], 1.0, 0.0));
+        assert!(approx_eq(m[13], 2.0, 0.0));
+        assert!(approx_eq(m[14], 3.0, 0.0));
+    }
+
+    fn assert_orthonormal_3x3(m: &[f32; 16]) {
+        let c0 = Vec3::new(m[0], m[1], m[2]);
+        let c1 = Vec3::new(m[4], m[5], m[6]);
+        let c2 = Vec3::new(m[8], m[9], m[10]);
+
+        assert!(approx_eq(c0.length(), 1.0, 1e-5));
+        assert!(approx_eq(c1.length(), 1.0, 1e-5));
+        assert!(approx_eq(c2.length(), 1.0, 1e-5));
+
+        assert!(approx_eq(c0.dot(c1), 0.0, 1e-5));
+        assert!(approx_eq(c0.dot(c2), 0.0, 1e-5));
+        assert!(approx_eq(c1.dot(c2), 0.0, 1e-5));
+    }
+
+    #[test]
+    fn mat4_rotation_z_maps_x_to_y() {
+        let r = Mat4::rotation_z(std::f32::consts::FRAC_PI_2);
+        // Rotating a translation by (1,0,0) moves its column to R * (1,0,0).
+        let m = r.mul(Mat4::translation(Vec3::new(1.0, 0.0, 0.0))).m;
+        assert_vec3_approx(
+            Vec3::new(m[12], m[13], m[14]),
+            Vec3::new(0.0, 1.0, 0.0),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn mat4_rotations_are_orthonormal() {
+        for angle in [0.0f32, 0.3, 1.2, -2.5] {
+            assert_orthonormal_3x3(&Mat4::rotation_x(angle).m);
+            assert_orthonormal_3x3(&Mat4::rotation_y(angle).m);
+            assert_orthonormal_3x3(&Mat4::rotation_z(angle).m);
+        }
+    }
+
+    #[test]
+    fn mat4_mul_point_translation_moves_origin() {
+        let t = Vec3::new(3.0, -2.0, 5.0);
+        let p = Mat4::translation(t).mul_point(Vec3::new(0.0, 0.0, 0.0));
+        assert_vec3_approx(p, t, 0.0);
+    }
+
+    #[test]
+    fn mat4_mul_point_identity_is_noop() {
+        let p = Vec3::new(1.5, -4.0, 0.25);
+        assert_vec3_approx(Mat4::identity().mul_point(p), p, 0.0);
+    }
+
+    #[test]
+    fn mat4_mul_vec4_ignores_translation_for_directions() {
+        let t = Mat4::translation(Vec3::new(3.0, -2.0, 5.0));
+        assert_eq!(t.mul_vec4([1.0, 2.0, 3.0, 0.0]), [1.0, 2.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn mat4_inverse_of_look_at_round_trips() {
+        let m = Mat4::look_at(
+            Vec3::new(1.0, 2.0, 5.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let inv = m.inverse().expect("look_at should be invertible");
+        let id = Mat4::identity().m;
+        for (i, v) in m.mul(inv).m.iter().enumerate() {
+            assert!(approx_eq(*v, id[i], 1e-4), "index {i} = {v}");
+        }
+    }
+
+    #[test]
+    fn mat4_inverse_of_translation_negates_column() {
+        let inv = Mat4::translation(Vec3::new(7.0, -8.0, 9.0))
+            .inverse()
+            .unwrap();
+        assert!(approx_eq(inv.m[12], -7.0, 1e-6));
+        assert!(approx_eq(inv.m[13], 8.0, 1e-6));
+        assert!(approx_eq(inv.m[14], -9.0, 1e-6));
+        assert!(approx_eq(inv.m[15], 1.0, 1e-6));
+    }
+
+    #[test]
+    fn mat4_inverse_of_singular_is_none() {
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn mat4_transpose_swaps_rows_and_columns() {
+        let t = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)).transpose();
+        assert!(approx_eq(t.m[3], 1.0, 0.0));
+        assert!(approx_eq(t.m[7], 2.0, 0.0));
+        assert!(approx_eq(t.m[11], 3.0, 0.0));
+        assert!(approx_eq(t.m[12], 0.0, 0.0));
+        assert_eq!(
+            t.transpose().m,
+            Mat4::translation(Vec3::new(1.0, 2.0, 3.0)).m
+        );
+    }
+
+    #[test]
+    fn mat4_normal_matrix_of_rotation_is_rotation() {
+        let r = Mat4::rotation_y(0.7).mul(Mat4::rotation_x(-0.4));
+        let n = r.normal_matrix();
+        let expected = [
+            r.m[0], r.m[1], r.m[2], r.m[4], r.m[5], r.m[6], r.m[8], r.m[9], r.m[10],
+        ];
+        for i in 0..9 {
+            assert!(
+                approx_eq(n[i], expected[i], 1e-5),
+                "index {i}: {} vs {}",
+                n[i],
+                expected[i]
+            );
+        }
+    }
+
+    #[test]
+    fn mat4_normal_matrix_inverts_scale() {
+        let n = Mat4::scaling(Vec3::new(2.0, 1.0, 1.0)).normal_matrix();
+        assert!(approx_eq(n[0], 0.5, 1e-6));
+        assert!(approx_eq(n[4], 1.0, 1e-6));
+        assert!(approx_eq(n[8], 1.0, 1e-6));
+    }
+
+    #[test]
+    fn mat4_orthographic_maps_near_center_to_minus_one() {
+        let (znear, zfar) = (0.5, 20.0);
+        let o = Mat4::orthographic(-2.0, 2.0, -1.0, 1.0, znear, zfar);
+        // Camera looks down -Z, so the near plane sits at z = -znear in view space.
+        let near = o.mul_point(Vec3::new(0.0, 0.0, -znear));
+        assert_vec3_approx(near, Vec3::new(0.0, 0.0, -1.0), 1e-6);
+        let far = o.mul_point(Vec3::new(0.0, 0.0, -zfar));
+        assert_vec3_approx(far, Vec3::new(0.0, 0.0, 1.0), 1e-6);
+    }
+
     #[test]
     fn mat4_look_at_builds_orthonormal_basis() {
         let eye = Vec3::new(0.0, 0.0, 5.0);
@@ -244,5 +822,21 @@ mod tests {
         assert!(approx_eq(s.dot(neg_f), 0.0, 1e-5));
         assert!(approx_eq(u.dot(neg_f), 0.0, 1e-5));
     }
+
+    #[test]
+    fn shadow_projection_flattens_along_the_light() {
+        // Ground at y = -0.5, light travelling down and to the +X side.
+        let plane = [0.0, 1.0, 0.0, 0.5];
+        let light = Vec3::new(1.0, -2.0, 0.0);
+        let m = Mat4::shadow_projection(plane, light);
+
+        let p = Vec3::new(0.2, 1.5, -0.3);
+        let shadow = m.mul_point(p);
+        // Two units above the ground, so the shadow lands one unit further along +X.
+        assert_vec3_approx(shadow, Vec3::new(1.2, -0.5, -0.3), 1e-5);
+        // Points already on the ground stay put.
+        let on_ground = Vec3::new(-0.7, -0.5, 0.4);
+        assert_vec3_approx(m.mul_point(on_ground), on_ground, 1e-5);
+    }
 }
 
@@ -0,0 +1,109 @@
+use crate::geometry::Mesh;
+use crate::math::{Mat4, Vec3};
+
+/// Half-line used for CPU-side picking.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Ray {
+    pub origin: Vec3,
+    /// Unit direction.
+    pub dir: Vec3,
+}
+
+impl Ray {
+    /// Ray through `(ndc_x, ndc_y)`, starting on the near plane.
+    /// `inv_view_proj` maps clip space back into the space the ray should live in.
+    pub fn from_ndc(inv_view_proj: Mat4, ndc_x: f32, ndc_y: f32) -> Self {
+        let near = inv_view_proj.mul_point(Vec3::new(ndc_x, ndc_y, -1.0));
+        let far = inv_view_proj.mul_point(Vec3::new(ndc_x, ndc_y, 1.0));
+        Self {
+            origin: near,
+            dir: far.sub(near).normalize(),
+        }
+    }
+
+    /// Möller–Trumbore; both faces count as hits. Returns the distance along the ray.
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+        const EPS: f32 = 1e-7;
+        let e1 = b.sub(a);
+        let e2 = c.sub(a);
+        let p = self.dir.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < EPS {
+            return None; // parallel to the triangle plane
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin.sub(a);
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.dir.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv_det;
+        (t > EPS).then_some(t)
+    }
+
+    /// Nearest hit against every triangle of `mesh`.
+    pub fn intersect_mesh(&self, mesh: &Mesh) -> Option<f32> {
+        mesh.corner_indices()
+            .chunks_exact(3)
+            .filter_map(|tri| {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| mesh.position(i));
+                self.intersect_triangle(a, b, c)
+            })
+            .min_by(f32::total_cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::geometry::cube;
+
+    #[test]
+    fn center_ray_hits_cube_front_face() {
+        let cam = Camera::new();
+        let view_proj = cam.projection_matrix(1.0, 1.0).mul(cam.view_matrix());
+        let ray = Ray::from_ndc(view_proj.inverse().unwrap(), 0.0, 0.0);
+
+        // Camera sits on +Z at `distance`; the front face is at z = 0.5.
+        let t = ray.intersect_mesh(&cube()).expect("center ray should hit");
+        let expected = cam.distance - 0.5 - cam.znear;
+        assert!((t - expected).abs() <= 1e-3, "t={t} expected={expected}");
+    }
+
+    #[test]
+    fn ray_beside_cube_misses() {
+        let cam = Camera::new();
+        let view_proj = cam.projection_matrix(1.0, 1.0).mul(cam.view_matrix());
+        let ray = Ray::from_ndc(view_proj.inverse().unwrap(), 0.95, 0.95);
+        assert_eq!(ray.intersect_mesh(&cube()), None);
+    }
+
+    #[test]
+    fn triangle_hit_is_barycentric_inside_only() {
+        let ray = Ray {
+            origin: Vec3::new(0.2, 0.2, 1.0),
+            dir: Vec3::new(0.0, 0.0, -1.0),
+        };
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(ray.intersect_triangle(a, b, c), Some(1.0));
+
+        let outside = Ray {
+            origin: Vec3::new(0.8, 0.8, 1.0),
+            ..ray
+        };
+        assert_eq!(outside.intersect_triangle(a, b, c), None);
+        let behind = Ray {
+            origin: Vec3::new(0.2, 0.2, -1.0),
+            ..ray
+        };
+        assert_eq!(behind.intersect_triangle(a, b, c), None);
+    }
+}
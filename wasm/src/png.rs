@@ -0,0 +1,234 @@
+//! Minimal PNG writer for framebuffer captures: 8-bit RGBA, no filtering, and
+//! zlib "stored" (uncompressed) deflate blocks. Files are large but encoding is
+//! trivial and needs no dependency; browsers re-encode on upload anyway.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest payload of a single stored deflate block.
+const MAX_STORED_BLOCK: usize = 65_535;
+
+/// Reverse the row order of a tightly packed image in place. `read_pixels` returns rows
+/// bottom-up while image formats expect them top-down.
+pub(crate) fn flip_rows(pixels: &mut [u8], row_bytes: usize) {
+    if row_bytes == 0 {
+        return;
+    }
+    let rows = pixels.len() / row_bytes;
+    for top in 0..rows / 2 {
+        let bottom = rows - 1 - top;
+        let (upper, lower) = pixels.split_at_mut(bottom * row_bytes);
+        upper[top * row_bytes..(top + 1) * row_bytes].swap_with_slice(&mut lower[..row_bytes]);
+    }
+}
+
+/// Encode top-down RGBA8 pixels (`width * height * 4` bytes) as a PNG file.
+/// The output is sized up front so the pixel data is copied exactly once.
+pub(crate) fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let row_bytes = width as usize * 4;
+    assert_eq!(
+        rgba.len(),
+        row_bytes * height as usize,
+        "pixel buffer size mismatch"
+    );
+
+    // Each scanline is prefixed with a filter-type byte (0 = none).
+    let raw_len = (row_bytes + 1) * height as usize;
+    let blocks = raw_len.div_ceil(MAX_STORED_BLOCK).max(1);
+    let zlib_len = 2 + raw_len + 5 * blocks + 4;
+    let mut out = Vec::with_capacity(SIGNATURE.len() + 25 + 12 + zlib_len + 12);
+    out.extend_from_slice(&SIGNATURE);
+
+    let mut ihdr = [0u8; 13];
+    ihdr[0..4].copy_from_slice(&width.to_be_bytes());
+    ihdr[4..8].copy_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type 6 (RGBA), default compression/filter, no interlace.
+    ihdr[8..13].copy_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", |out| out.extend_from_slice(&ihdr));
+
+    write_chunk(&mut out, b"IDAT", |out| {
+        // CMF/FLG: deflate with a 32K window, no dictionary, fastest level.
+        out.extend_from_slice(&[0x78, 0x01]);
+        let mut stream = StoredDeflate {
+            out,
+            remaining: raw_len,
+            block_left: 0,
+            adler: Adler32::new(),
+        };
+        for row in rgba.chunks_exact(row_bytes.max(1)) {
+            stream.write(&[0]);
+            stream.write(row);
+        }
+        let checksum = stream.adler.finish();
+        out.extend_from_slice(&checksum.to_be_bytes());
+    });
+
+    write_chunk(&mut out, b"IEND", |_| {});
+    out
+}
+
+/// Append a length-prefixed, CRC-terminated chunk whose body `fill` writes.
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], fill: impl FnOnce(&mut Vec<u8>)) {
+    let len_at = out.len();
+    out.extend_from_slice(&[0; 4]);
+    let kind_at = out.len();
+    out.extend_from_slice(kind);
+    fill(out);
+    let len = (out.len() - kind_at - 4) as u32;
+    out[len_at..kind_at].copy_from_slice(&len.to_be_bytes());
+    let crc = crc32(&out[kind_at..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Splits a byte stream of known total length into stored deflate blocks.
+struct StoredDeflate<'a> {
+    out: &'a mut Vec<u8>,
+    /// Bytes not yet written, including the current block.
+    remaining: usize,
+    block_left: usize,
+    adler: Adler32,
+}
+
+impl StoredDeflate<'_> {
+    fn write(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.block_left == 0 {
+                let len = self.remaining.min(MAX_STORED_BLOCK);
+                let is_final = len == self.remaining;
+                self.out.push(u8::from(is_final)); // BFINAL, BTYPE = 00
+                self.out.extend_from_slice(&(len as u16).to_le_bytes());
+                self.out.extend_from_slice(&(!(len as u16)).to_le_bytes());
+                self.block_left = len;
+            }
+            let n = data.len().min(self.block_left);
+            self.out.extend_from_slice(&data[..n]);
+            self.adler.update(&data[..n]);
+            self.block_left -= n;
+            self.remaining -= n;
+            data = &data[n..];
+        }
+    }
+}
+
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MOD: u32 = 65_521;
+
+    fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        // 5552 is the largest run that cannot overflow `b` before reducing.
+        for chunk in data.chunks(5552) {
+            for &byte in chunk {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= Self::MOD;
+            self.b %= Self::MOD;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+/// CRC-32 (IEEE, reflected) as used by PNG chunks.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flip_rows_reverses_row_order() {
+        // 2x3 image, one byte per pixel: rows [1,2], [3,4], [5,6].
+        let mut pixels = vec![1, 2, 3, 4, 5, 6];
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, vec![5, 6, 3, 4, 1, 2]);
+
+        // Even row count, RGBA pixels.
+        let mut rgba: Vec<u8> = (0..16).collect();
+        flip_rows(&mut rgba, 8);
+        let expected: Vec<u8> = (8..16).chain(0..8).collect();
+        assert_eq!(rgba, expected);
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        let mut adler = Adler32::new();
+        adler.update(b"Wikipedia");
+        assert_eq!(adler.finish(), 0x11E6_0398);
+    }
+
+    /// Undo the stored blocks inside a zlib stream.
+    fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut at = 2;
+        loop {
+            let header = zlib[at];
+            let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]) as usize;
+            let nlen = u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]);
+            assert_eq!(nlen, !(len as u16));
+            data.extend_from_slice(&zlib[at + 5..at + 5 + len]);
+            at += 5 + len;
+            if header & 1 == 1 {
+                break;
+            }
+        }
+        let mut adler = Adler32::new();
+        adler.update(&data);
+        assert_eq!(zlib[at..], adler.finish().to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn encodes_valid_chunks_and_round_trips_pixels() {
+        // Wide enough that the scanlines span several stored blocks.
+        let (width, height) = (300u32, 70u32);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| (i % 251) as u8).collect();
+        let png = encode_rgba(width, height, &rgba);
+        assert_eq!(png[..8], SIGNATURE);
+
+        let mut chunks = Vec::new();
+        let mut at = 8;
+        while at < png.len() {
+            let len = u32::from_be_bytes(png[at..at + 4].try_into().unwrap()) as usize;
+            let kind = &png[at + 4..at + 8];
+            let body = &png[at + 8..at + 8 + len];
+            let crc = u32::from_be_bytes(png[at + 8 + len..at + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&png[at + 4..at + 8 + len]));
+            chunks.push((kind.to_vec(), body.to_vec()));
+            at += 12 + len;
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(k, _)| k.as_slice()).collect();
+        assert_eq!(kinds, [b"IHDR".as_slice(), b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1[..8], [0, 0, 1, 44, 0, 0, 0, 70]);
+        assert_eq!(png.len(), png.capacity());
+
+        let raw = inflate_stored(&chunks[1].1);
+        let row_bytes = width as usize * 4;
+        assert_eq!(raw.len(), (row_bytes + 1) * height as usize);
+        for (y, line) in raw.chunks_exact(row_bytes + 1).enumerate() {
+            assert_eq!(line[0], 0);
+            assert_eq!(line[1..], rgba[y * row_bytes..(y + 1) * row_bytes]);
+        }
+    }
+}
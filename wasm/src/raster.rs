@@ -0,0 +1,235 @@
+//! Headless software rasterizer so rendering math can be checked without a browser.
+//! It mirrors the vertex stage of the mesh shader (`proj * view * model * position`),
+//! then fills triangles with one flat color behind a depth test. No clipping: triangles
+//! with a corner behind the eye are skipped, so keep test scenes in front of the camera.
+
+use crate::geometry::Mesh;
+use crate::math::Mat4;
+
+/// RGBA8 color and depth buffers, rows top to bottom (unlike `read_pixels`).
+pub(crate) struct Framebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub color: Vec<[u8; 4]>,
+    /// Window-space depth in [0, 1], 1 = far plane (the cleared value).
+    pub depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    pub(crate) fn new(width: usize, height: usize, clear: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![clear; width * height],
+            depth: vec![1.0; width * height],
+        }
+    }
+
+    pub(crate) fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.color[y * self.width + x]
+    }
+
+    /// Draw the mesh's triangles (both windings) in `fill`, depth-tested against what is
+    /// already in the buffer.
+    pub(crate) fn draw_mesh(
+        &mut self,
+        mesh: &Mesh,
+        proj: &Mat4,
+        view: &Mat4,
+        model: &Mat4,
+        fill: [u8; 4],
+    ) {
+        self.draw_corners(
+            mesh,
+            &mesh.corner_indices(),
+            proj.mul(*view).mul(*model),
+            fill,
+        );
+    }
+
+    /// `draw_mesh` with the triangles read from `strip` the way `TRIANGLE_STRIP` reads
+    /// them; the zero-area joins fill nothing.
+    pub(crate) fn draw_strip(
+        &mut self,
+        mesh: &Mesh,
+        strip: &[u16],
+        proj: &Mat4,
+        view: &Mat4,
+        model: &Mat4,
+        fill: [u8; 4],
+    ) {
+        let corners: Vec<usize> = strip
+            .windows(3)
+            .enumerate()
+            .flat_map(|(k, w)| {
+                // Odd triangles swap their first two vertices to keep the winding.
+                if k.is_multiple_of(2) {
+                    [w[0], w[1], w[2]]
+                } else {
+                    [w[1], w[0], w[2]]
+                }
+            })
+            .map(usize::from)
+            .collect();
+        self.draw_corners(mesh, &corners, proj.mul(*view).mul(*model), fill);
+    }
+
+    fn draw_corners(&mut self, mesh: &Mesh, corners: &[usize], mvp: Mat4, fill: [u8; 4]) {
+        for tri in corners.chunks_exact(3) {
+            let mut window = [[0.0f32; 3]; 3];
+            let mut visible = true;
+            for (out, &i) in window.iter_mut().zip(tri) {
+                let p = &mesh.positions[i * 3..i * 3 + 3];
+                let [x, y, z, w] = mvp.mul_vec4([p[0], p[1], p[2], 1.0]);
+                if w <= 1e-6 {
+                    visible = false;
+                    break;
+                }
+                // Viewport transform with y flipped so row 0 is the top.
+                *out = [
+                    (x / w * 0.5 + 0.5) * self.width as f32,
+                    (0.5 - y / w * 0.5) * self.height as f32,
+                    z / w * 0.5 + 0.5,
+                ];
+            }
+            if visible {
+                self.fill_triangle(window, fill);
+            }
+        }
+    }
+
+    /// Fill pixels whose centers fall inside the window-space triangle (edges included).
+    fn fill_triangle(&mut self, [a, b, c]: [[f32; 3]; 3], fill: [u8; 4]) {
+        let area = edge(a, b, c);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+        let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as usize;
+        let max_x = (a[0].max(b[0]).max(c[0]).ceil().max(0.0) as usize).min(self.width);
+        let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as usize;
+        let max_y = (a[1].max(b[1]).max(c[1]).ceil().max(0.0) as usize).min(self.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+                // Barycentric weights; dividing by the signed area accepts either winding.
+                let wa = edge(b, c, p) / area;
+                let wb = edge(c, a, p) / area;
+                let wc = edge(a, b, p) / area;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let z = wa * a[2] + wb * b[2] + wc * c[2];
+                let i = y * self.width + x;
+                if (0.0..self.depth[i]).contains(&z) {
+                    self.depth[i] = z;
+                    self.color[i] = fill;
+                }
+            }
+        }
+    }
+}
+
+/// Twice the signed area of `(a, b, p)`; positive when `p` is left of `a -> b`.
+fn edge(a: [f32; 3], b: [f32; 3], p: [f32; 3]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry;
+    use crate::math::Vec3;
+
+    const CLEAR: [u8; 4] = [0, 0, 0, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    /// One character per pixel: '.' for the clear color, '#' for anything drawn.
+    fn ascii(fb: &Framebuffer) -> Vec<String> {
+        (0..fb.height)
+            .map(|y| {
+                (0..fb.width)
+                    .map(|x| if fb.pixel(x, y) == CLEAR { '.' } else { '#' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn front_facing_triangle_matches_golden_image() {
+        let mut fb = Framebuffer::new(8, 8, CLEAR);
+        let identity = Mat4::identity();
+        fb.draw_mesh(&geometry::triangle(), &identity, &identity, &identity, RED);
+        assert_eq!(
+            ascii(&fb),
+            [
+                "........", //
+                "........", //
+                "........", //
+                "...##...", //
+                "...##...", //
+                "..####..", //
+                "........", //
+                "........", //
+            ]
+        );
+        assert_eq!(fb.pixel(3, 4), RED);
+        assert_eq!(fb.depth[4 * 8 + 3], 0.5);
+    }
+
+    #[test]
+    fn nearer_triangle_wins_in_either_draw_order() {
+        let identity = Mat4::identity();
+        let near = Mat4::translation(Vec3::new(0.0, 0.0, -0.5));
+        let far = Mat4::translation(Vec3::new(0.0, 0.0, 0.5));
+        let tri = geometry::triangle();
+        for near_first in [true, false] {
+            let mut fb = Framebuffer::new(8, 8, CLEAR);
+            let passes = if near_first {
+                [(near, RED), (far, BLUE)]
+            } else {
+                [(far, BLUE), (near, RED)]
+            };
+            for (model, fill) in passes {
+                fb.draw_mesh(&tri, &identity, &identity, &model, fill);
+            }
+            assert_eq!(fb.pixel(3, 4), RED, "near first: {near_first}");
+        }
+    }
+
+    #[test]
+    fn grid_strips_draw_the_same_pixels_as_their_lists() {
+        let proj = Mat4::perspective(45_f32.to_radians(), 1.0, 0.1, 10.0);
+        let eye = Vec3::new(0.4, 1.2, 2.0);
+        let view = Mat4::look_at(eye, Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let model = Mat4::identity();
+        for mesh in [geometry::plane(1.5, 1.5, 3), geometry::sphere(0.6, 6, 4)] {
+            assert!(!mesh.strip.is_empty());
+            let mut list = Framebuffer::new(24, 24, CLEAR);
+            list.draw_mesh(&mesh, &proj, &view, &model, RED);
+            let mut strip = Framebuffer::new(24, 24, CLEAR);
+            strip.draw_strip(&mesh, &mesh.strip, &proj, &view, &model, RED);
+            assert!(list.color.contains(&RED));
+            assert_eq!(strip.color, list.color);
+            // Same surfaces; only the rounding of interpolated depth may differ.
+            let mut depths = strip.depth.iter().zip(&list.depth);
+            assert!(depths.all(|(a, b)| (a - b).abs() <= 1e-5));
+        }
+    }
+
+    #[test]
+    fn centered_cube_fills_the_middle_of_the_view() {
+        let proj = Mat4::perspective(45_f32.to_radians(), 1.0, 0.1, 10.0);
+        let eye = Vec3::new(0.0, 0.0, 3.0);
+        let view = Mat4::look_at(eye, Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let mut fb = Framebuffer::new(16, 16, CLEAR);
+        fb.draw_mesh(&geometry::cube(), &proj, &view, &Mat4::identity(), RED);
+        // The front face spans about +-0.48 NDC at this distance.
+        for (x, y) in [(8, 8), (5, 5), (10, 10)] {
+            assert_eq!(fb.pixel(x, y), RED, "({x}, {y})");
+        }
+        for (x, y) in [(0, 0), (15, 0), (0, 15), (15, 15), (8, 2), (2, 8)] {
+            assert_eq!(fb.pixel(x, y), CLEAR, "({x}, {y})");
+        }
+    }
+}
@@ -1,10 +1,454 @@
+use std::cell::Cell;
+
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
-use web_sys::{
-    WebGlBuffer, WebGlProgram, WebGlRenderingContext, WebGlUniformLocation,
+use web_sys::WebGlRenderingContext;
+
+use crate::camera::Bounds;
+use crate::geometry::{self, build_wireframe_indices, IndexBuffer, Mesh, Topology};
+use crate::gl::{check_gl_error, Gl, GraphicsBackend, InstancedDraw};
+use crate::lines::{LinePass, ThickLinePass};
+use crate::math::{Mat4, Vec3};
+use crate::shader::{
+    compile_shader, fragment_source, link_program, FloatPrecision, FLAT_FRAGMENT_SHADER_SRC,
+    FLAT_VERTEX_SHADER_SRC, FRAGMENT_SHADER_SRC, INSTANCED_VERTEX_SHADER_SRC, VERTEX_SHADER_SRC,
+};
+
+/// Light bluish gray, the original hardcoded shader color.
+const DEFAULT_BASE_COLOR: [f32; 3] = [0.8, 0.85, 0.95];
+
+/// Ambient floor; matches the previous fixed 0.15 term.
+const DEFAULT_AMBIENT: [f32; 3] = [0.15, 0.15, 0.15];
+/// Sky and ground ambient tints; both white leaves the ambient flat.
+const DEFAULT_SKY_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const DEFAULT_GROUND_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const DEFAULT_SPECULAR: [f32; 3] = [0.25, 0.25, 0.25];
+const DEFAULT_SHININESS: f32 = 32.0;
+const DEFAULT_GRID_COLOR: [f32; 3] = [0.55, 0.55, 0.55];
+/// Near-black edges for the wireframe overlay (still lit, so they keep some shape).
+const WIREFRAME_OVERLAY_COLOR: [f32; 3] = [0.08, 0.08, 0.1];
+/// Magenta stands out against the default gray material.
+const NORMAL_LINE_COLOR: [f32; 3] = [0.9, 0.2, 0.8];
+/// Selection outline defaults: an orange rim a few pixels wide.
+const DEFAULT_OUTLINE_COLOR: [f32; 3] = [1.0, 0.6, 0.1];
+const OUTLINE_WIDTH_PX: f32 = 3.0;
+const SHADOW_COLOR: [f32; 3] = [0.0, 0.0, 0.0];
+/// Stencil bit set where the ground shadow has darkened a pixel, so overlapping
+/// flattened triangles darken it only once. The outline uses the low bits.
+const SHADOW_STENCIL_BIT: u32 = 0x80;
+
+/// Texture units the mesh's textures are bound to while drawing it.
+const MATCAP_TEXTURE_UNIT: u32 = 0;
+const BASE_TEXTURE_UNIT: u32 = 1;
+const ENVIRONMENT_TEXTURE_UNIT: u32 = 2;
+/// How much of the environment reflection covers the shaded color.
+const DEFAULT_REFLECTIVITY: f32 = 0.5;
+
+/// Light pointing from camera toward the scene with slight tilt (view space).
+const DEFAULT_LIGHT_DIR: Vec3 = Vec3 {
+    x: -0.3,
+    y: -0.5,
+    z: -1.0,
 };
 
-use crate::geometry::Mesh;
-use crate::shader::{compile_shader, link_program, FRAGMENT_SHADER_SRC, VERTEX_SHADER_SRC};
+/// Fragment shader array size (WebGL1 uniform budgets are small).
+pub(crate) const MAX_LIGHTS: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Light {
+    /// Unit direction the light travels, in view space.
+    pub dir: Vec3,
+    pub color: [f32; 3],
+}
+
+impl Light {
+    fn default_key() -> Self {
+        Self {
+            dir: DEFAULT_LIGHT_DIR.normalize(),
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// Work submitted by the last `Renderer::draw`, across every pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct RenderStats {
+    pub draw_calls: u32,
+    pub triangles: u32,
+    /// Distinct vertices fed to the draws (indexed geometry counts each vertex once).
+    pub vertices: u32,
+    /// CPU time spent issuing the frame; the GPU may finish later.
+    pub last_frame_ms: f64,
+}
+
+impl RenderStats {
+    /// Count one draw call of `count` elements in primitive `mode` over `vertices` vertices.
+    fn record(&mut self, mode: u32, count: i32, vertices: i32) {
+        self.draw_calls += 1;
+        if mode == WebGlRenderingContext::TRIANGLES {
+            self.triangles += count.max(0) as u32 / 3;
+        }
+        self.vertices += vertices.max(0) as u32;
+    }
+
+    /// `record` for one draw repeated over `instances` instances.
+    fn record_instanced(&mut self, mode: u32, count: i32, vertices: i32, instances: i32) {
+        self.record(
+            mode,
+            count.saturating_mul(instances),
+            vertices.saturating_mul(instances),
+        );
+    }
+}
+
+/// Fixed-function state for the mesh pass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PassState {
+    blend: bool,
+    depth_write: bool,
+    opacity: f32,
+}
+
+impl PassState {
+    /// Fully opaque "transparent" rendering takes the opaque path, so raising opacity
+    /// back to 1 restores depth writes and draw-order independence.
+    fn new(transparent: bool, opacity: f32) -> Self {
+        if transparent && opacity < 1.0 {
+            Self {
+                blend: true,
+                depth_write: false,
+                opacity,
+            }
+        } else {
+            Self {
+                blend: false,
+                depth_write: true,
+                opacity: 1.0,
+            }
+        }
+    }
+}
+
+/// How many copies of the mesh `Renderer::draw` puts in the scene, and where.
+pub(crate) enum Placement<'a> {
+    /// One copy with this model matrix and its view-space normal matrix.
+    Single {
+        model: &'a [f32; 16],
+        normal_matrix: &'a [f32; 9],
+    },
+    /// `count` copies, one per column-major 4x4 matrix in `models`.
+    Instances { models: &'a [f32], count: i32 },
+}
+
+/// Uniform locations the mesh programs share (all but the model and normal matrices,
+/// which the instanced program reads per instance).
+struct MeshUniforms<B: GraphicsBackend> {
+    view: B::UniformLocation,
+    proj: B::UniformLocation,
+    light_dirs: B::UniformLocation,
+    light_colors: B::UniformLocation,
+    light_count: B::UniformLocation,
+    base_color: B::UniformLocation,
+    ambient: B::UniformLocation,
+    sky_color: B::UniformLocation,
+    ground_color: B::UniformLocation,
+    up_vs: B::UniformLocation,
+    specular_color: B::UniformLocation,
+    shininess: B::UniformLocation,
+    opacity: B::UniformLocation,
+    use_vertex_color: B::UniformLocation,
+    matcap: B::UniformLocation,
+    use_matcap: B::UniformLocation,
+    base_texture: B::UniformLocation,
+    use_texture: B::UniformLocation,
+    environment: B::UniformLocation,
+    use_environment: B::UniformLocation,
+    reflectivity: B::UniformLocation,
+    view_to_world: B::UniformLocation,
+    gamma: B::UniformLocation,
+    shading_model: B::UniformLocation,
+    fog_color: B::UniformLocation,
+    fog_near: B::UniformLocation,
+    fog_far: B::UniformLocation,
+}
+
+impl<B: GraphicsBackend> MeshUniforms<B> {
+    fn new(gl: &B, program: &B::Program) -> Result<Self, JsValue> {
+        let get = |name: &str| {
+            gl.get_uniform_location(program, name)
+                .ok_or_else(|| js_error(&format!("missing uniform {name}")))
+        };
+        Ok(Self {
+            view: get("u_view")?,
+            proj: get("u_proj")?,
+            light_dirs: get("u_light_dirs")?,
+            light_colors: get("u_light_colors")?,
+            light_count: get("u_light_count")?,
+            base_color: get("u_base_color")?,
+            ambient: get("u_ambient")?,
+            sky_color: get("u_sky_color")?,
+            ground_color: get("u_ground_color")?,
+            up_vs: get("u_up_vs")?,
+            specular_color: get("u_specular_color")?,
+            shininess: get("u_shininess")?,
+            opacity: get("u_opacity")?,
+            use_vertex_color: get("u_use_vertex_color")?,
+            matcap: get("u_matcap")?,
+            use_matcap: get("u_use_matcap")?,
+            base_texture: get("u_base_texture")?,
+            use_texture: get("u_use_texture")?,
+            environment: get("u_environment")?,
+            use_environment: get("u_use_environment")?,
+            reflectivity: get("u_reflectivity")?,
+            view_to_world: get("u_view_to_world")?,
+            gamma: get("u_gamma")?,
+            shading_model: get("u_shading_model")?,
+            fog_color: get("u_fog_color")?,
+            fog_near: get("u_fog_near")?,
+            fog_far: get("u_fog_far")?,
+        })
+    }
+}
+
+/// The mesh program variant that takes its model matrix per instance. It reads the
+/// renderer's vertex and index buffers.
+struct InstancedProgram<B: GraphicsBackend> {
+    program: B::Program,
+    shaders: [B::Shader; 2],
+    uniforms: MeshUniforms<B>,
+    /// Separate from `Renderer::vao`: attribute locations differ between the programs.
+    vao: Option<B::VertexArray>,
+    /// Model matrices for the current draw, 16 floats per instance.
+    instance_bo: B::Buffer,
+    /// -1 when the compiler stripped the attribute.
+    position_location: i32,
+    normal_location: i32,
+    uv_location: i32,
+    color_location: i32,
+    /// First of four consecutive locations, one per matrix column.
+    model_location: i32,
+}
+
+impl<B: GraphicsBackend> InstancedProgram<B> {
+    fn new(gl: &B, precision: FloatPrecision) -> Result<Self, JsValue> {
+        let vs = compile_shader(
+            gl,
+            WebGlRenderingContext::VERTEX_SHADER,
+            INSTANCED_VERTEX_SHADER_SRC,
+        )?;
+        let fs = compile_shader(
+            gl,
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            &fragment_source(FRAGMENT_SHADER_SRC, precision),
+        )?;
+        let program = link_program(gl, &vs, &fs)?;
+        let model_location = gl.get_attrib_location(&program, "a_model");
+        if model_location < 0 {
+            return Err(js_error("missing attribute a_model"));
+        }
+        let instance_bo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create instance buffer"))?;
+        Ok(Self {
+            uniforms: MeshUniforms::new(gl, &program)?,
+            vao: gl.create_vertex_array(),
+            instance_bo,
+            position_location: gl.get_attrib_location(&program, "position"),
+            normal_location: gl.get_attrib_location(&program, "normal"),
+            uv_location: gl.get_attrib_location(&program, "a_uv"),
+            color_location: gl.get_attrib_location(&program, "a_color"),
+            model_location,
+            program,
+            shaders: [vs, fs],
+        })
+    }
+
+    fn delete(&self, gl: &B) {
+        gl.delete_buffer(Some(&self.instance_bo));
+        gl.delete_vertex_array(self.vao.as_ref());
+        gl.delete_program(Some(&self.program));
+        for shader in &self.shaders {
+            gl.delete_shader(Some(shader));
+        }
+    }
+}
+
+/// Unlit one-color program for the selection outline and the ground shadow. Like
+/// `InstancedProgram` it reads the renderer's vertex and index buffers.
+struct FlatProgram<B: GraphicsBackend> {
+    program: B::Program,
+    shaders: [B::Shader; 2],
+    /// Separate from `Renderer::vao`: attribute locations differ between the programs.
+    vao: Option<B::VertexArray>,
+    /// -1 when the compiler stripped the attribute.
+    position_location: i32,
+    normal_location: i32,
+    model: B::UniformLocation,
+    view: B::UniformLocation,
+    proj: B::UniformLocation,
+    normal_matrix: B::UniformLocation,
+    viewport: B::UniformLocation,
+    grow: B::UniformLocation,
+    color: B::UniformLocation,
+    alpha: B::UniformLocation,
+}
+
+/// What `Renderer::draw_flat` paints: a color and alpha, with the mesh grown by
+/// `grow_px` pixels along its screen-space normals.
+struct FlatStyle {
+    color: [f32; 3],
+    alpha: f32,
+    grow_px: f32,
+}
+
+/// RGBA8 pixels for a texture, rows top to bottom as in a canvas `ImageData`.
+#[derive(Clone)]
+pub(crate) struct TextureImage {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl TextureImage {
+    /// Fails unless `rgba` holds exactly `width * height` pixels and neither is zero.
+    pub(crate) fn new(rgba: &[u8], width: u32, height: u32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("texture must not be empty, got {width}x{height}"));
+        }
+        let expected = width as usize * height as usize * 4;
+        if rgba.len() != expected {
+            return Err(format!(
+                "a {width}x{height} RGBA texture needs {expected} bytes, got {}",
+                rgba.len()
+            ));
+        }
+        Ok(Self {
+            rgba: rgba.to_vec(),
+            width,
+            height,
+        })
+    }
+}
+
+/// Six square RGBA8 cube map faces, one after another in WebGL's face order (+X, -X,
+/// +Y, -Y, +Z, -Z), each top row first.
+#[derive(Clone)]
+pub(crate) struct CubemapImage {
+    faces: Vec<u8>,
+    size: u32,
+}
+
+impl CubemapImage {
+    /// Fails unless `faces` holds exactly six `size` x `size` faces and `size` isn't zero.
+    pub(crate) fn new(faces: &[u8], size: u32) -> Result<Self, String> {
+        if size == 0 {
+            return Err("cube map faces must not be empty".to_string());
+        }
+        let expected = size as usize * size as usize * 4 * 6;
+        if faces.len() != expected {
+            return Err(format!(
+                "six {size}x{size} RGBA faces need {expected} bytes, got {}",
+                faces.len()
+            ));
+        }
+        Ok(Self {
+            faces: faces.to_vec(),
+            size,
+        })
+    }
+}
+
+/// Sampler state for a texture, from `texture_sampling`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Sampling {
+    min_filter: u32,
+    /// Used for both axes.
+    wrap: u32,
+    mipmaps: bool,
+}
+
+/// An uploaded texture and the pixels it came from, kept so a rebuilt renderer can
+/// upload it again.
+struct Texture<B: GraphicsBackend, I = TextureImage> {
+    handle: B::Texture,
+    image: I,
+}
+
+impl<B: GraphicsBackend> FlatProgram<B> {
+    fn new(gl: &B) -> Result<Self, JsValue> {
+        let vs = compile_shader(
+            gl,
+            WebGlRenderingContext::VERTEX_SHADER,
+            FLAT_VERTEX_SHADER_SRC,
+        )?;
+        let fs = compile_shader(
+            gl,
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            FLAT_FRAGMENT_SHADER_SRC,
+        )?;
+        let program = link_program(gl, &vs, &fs)?;
+        let uniform = |name: &str| {
+            gl.get_uniform_location(&program, name)
+                .ok_or_else(|| js_error(&format!("missing uniform {name}")))
+        };
+        Ok(Self {
+            model: uniform("u_model")?,
+            view: uniform("u_view")?,
+            proj: uniform("u_proj")?,
+            normal_matrix: uniform("u_normal_matrix")?,
+            viewport: uniform("u_viewport")?,
+            grow: uniform("u_grow")?,
+            color: uniform("u_color")?,
+            alpha: uniform("u_alpha")?,
+            vao: gl.create_vertex_array(),
+            position_location: gl.get_attrib_location(&program, "position"),
+            normal_location: gl.get_attrib_location(&program, "normal"),
+            program,
+            shaders: [vs, fs],
+        })
+    }
+
+    fn delete(&self, gl: &B) {
+        gl.delete_vertex_array(self.vao.as_ref());
+        gl.delete_program(Some(&self.program));
+        for shader in &self.shaders {
+            gl.delete_shader(Some(shader));
+        }
+    }
+}
+
+/// Lighting model of the mesh fragment shader (`u_shading_model`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ShadingModel {
+    /// Smooth Lambert diffuse with a Blinn-Phong highlight; the original look.
+    #[default]
+    Lambert,
+    /// Classic Phong highlight from the reflected light vector (tighter, more
+    /// view-dependent than Blinn's at the same shininess).
+    Phong,
+    /// Cel shading: diffuse quantized into a few flat bands, hard-edged highlight.
+    Toon,
+}
+
+impl ShadingModel {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "lambert" => Some(Self::Lambert),
+            "phong" => Some(Self::Phong),
+            "toon" => Some(Self::Toon),
+            _ => None,
+        }
+    }
+
+    /// Value of the `u_shading_model` uniform.
+    fn uniform_value(self) -> i32 {
+        match self {
+            Self::Lambert => 0,
+            Self::Phong => 1,
+            Self::Toon => 2,
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 pub(crate) enum RenderMode {
@@ -12,177 +456,859 @@ pub(crate) enum RenderMode {
     Wireframe,
 }
 
-pub(crate) struct Renderer {
-    gl: WebGlRenderingContext,
-    program: WebGlProgram,
-    vbo: WebGlBuffer,
-    nbo: WebGlBuffer,
-    ibo: Option<WebGlBuffer>,
-    wireframe_ibo: Option<WebGlBuffer>,
+/// Draws meshes and overlays through a `GraphicsBackend`: a WebGL context (`Gl`) in the
+/// viewer, a recording stand-in in tests.
+pub(crate) struct Renderer<B: GraphicsBackend = Gl> {
+    gl: B,
+    program: B::Program,
+    /// Vertex and fragment shader, kept to be deleted with the program.
+    shaders: [B::Shader; 2],
+    /// WebGL2 only: holds the attribute setup and index binding so draws skip rebinding.
+    /// On WebGL1 the same state simply lives in the global context.
+    vao: Option<B::VertexArray>,
+    vbo: B::Buffer,
+    nbo: B::Buffer,
+    uv_bo: B::Buffer,
+    tangent_bo: B::Buffer,
+    color_bo: B::Buffer,
+    ibo: Option<B::Buffer>,
+    wireframe_ibo: Option<B::Buffer>,
     position_location: u32,
     normal_location: u32,
-    model_location: WebGlUniformLocation,
-    view_location: WebGlUniformLocation,
-    proj_location: WebGlUniformLocation,
-    light_dir_location: WebGlUniformLocation,
+    /// -1 when the linked program doesn't consume UVs (compilers strip unused attributes).
+    uv_location: i32,
+    tangent_location: i32,
+    color_location: i32,
+    model_location: B::UniformLocation,
+    normal_matrix_location: B::UniformLocation,
+    uniforms: MeshUniforms<B>,
+    /// `None` on WebGL1 without `ANGLE_instanced_arrays`; instances are then drawn one
+    /// call at a time with the main program.
+    instancing: Option<B::Instancing>,
+    /// Built only when `instancing` is available.
+    instanced: Option<InstancedProgram<B>>,
+    flat: FlatProgram<B>,
     index_count: i32,
+    /// `TRIANGLES`, or `TRIANGLE_STRIP` when `ibo` holds the mesh's strip.
+    index_mode: u32,
+    /// Triangles in the mesh, without a strip's degenerate joins.
+    triangle_count: i32,
     wireframe_index_count: i32,
+    /// `UNSIGNED_SHORT` or `UNSIGNED_INT`, matching the uploaded index buffers.
+    index_type: u32,
+    /// Whether `OES_element_index_uint` was enabled (32-bit index draws).
+    u32_indices: bool,
     vertex_count: i32,
+    topology: Topology,
+    /// Whether the current mesh uploaded per-vertex colors.
+    has_vertex_colors: bool,
     render_mode: RenderMode,
+    /// In solid mode, also draw the wireframe over the filled triangles.
+    wireframe_overlay: bool,
+    /// Skip triangles facing away from the camera (`CULL_FACE`).
+    cull_backfaces: bool,
+    /// Straight (non-premultiplied) RGBA clear color.
+    clear_color: [f32; 4],
+    /// At most `MAX_LIGHTS` directional lights.
+    lights: Vec<Light>,
+    /// Diffuse albedo, components in [0,1]. Used as-is by the lighting math; with the
+    /// default gamma of 1.0 it is written uncorrected, so values read as display colors.
+    base_color: [f32; 3],
+    ambient: [f32; 3],
+    /// Ambient tint for surfaces facing up (`sky_color`) and down (`ground_color`).
+    sky_color: [f32; 3],
+    ground_color: [f32; 3],
+    /// World axis the sky is above, unit length.
+    world_up: Vec3,
+    specular_color: [f32; 3],
+    /// Specular exponent; larger is a tighter highlight.
+    shininess: f32,
+    shading_model: ShadingModel,
+    /// Alpha-blend the mesh with depth writes off. Triangles are not sorted, so concave
+    /// meshes can show ordering artifacts.
+    transparent: bool,
+    /// Mesh alpha in [0,1], used while `transparent` is on.
+    opacity: f32,
+    /// Output gamma applied to the shaded mesh color; 1.0 is off.
+    gamma: f32,
+    /// Linear fog over view-space depth `fog_near..fog_far`; off when the range is empty.
+    fog_color: [f32; 3],
+    fog_near: f32,
+    fog_far: f32,
+    /// Ground grid, drawn before the mesh when `grid_visible`.
+    grid: LinePass<B>,
+    grid_visible: bool,
+    grid_color: [f32; 3],
+    /// Orientation gizmo drawn in the bottom-left corner when `axes_visible`.
+    axes: LinePass<B>,
+    axes_visible: bool,
+    /// Per-vertex normal segments in model space, drawn over the mesh when
+    /// `normals_visible`.
+    normals: LinePass<B>,
+    normals_visible: bool,
+    /// Quads for the current mesh's line segments, drawn instead of `LINES` while
+    /// `line_width` is above 1 pixel.
+    thick_lines: ThickLinePass<B>,
+    line_width: f32,
+    /// Outline the solid mesh in `outline_color`, through the stencil buffer.
+    selected: bool,
+    outline_color: [f32; 3],
+    /// Alpha of the mesh's shadow projected onto the ground below it; `None` draws none.
+    ground_shadow: Option<f32>,
+    /// Model-space bounds of the current mesh; the ground sits at their lowest point.
+    mesh_bounds: Bounds,
+    /// Shade the mesh from this material capture instead of the lights.
+    matcap: Option<Texture<B>>,
+    /// Tints the base color, read at the mesh's texture coordinates.
+    base_texture: Option<Texture<B>>,
+    /// Reflected in the mesh, blended over its shading by `reflectivity` in [0,1].
+    environment: Option<Texture<B, CubemapImage>>,
+    reflectivity: f32,
+    /// Counters from the most recent `draw` (interior so drawing stays `&self`).
+    stats: Cell<RenderStats>,
+    /// Forced by `set_precision`; `None` picks per context.
+    precision_override: Option<FloatPrecision>,
+    /// Set once the GL objects are deleted (or gone with a lost context), so `Drop`
+    /// doesn't delete them again.
+    released: bool,
 }
 
-impl Renderer {
-    pub(crate) fn new(gl: WebGlRenderingContext) -> Result<Self, JsValue> {
+impl<B: GraphicsBackend> Renderer<B> {
+    pub(crate) fn new(gl: B) -> Result<Self, JsValue> {
+        Self::with_precision(gl, None)
+    }
+
+    /// Compile the mesh programs with `precision`, or with the best the context supports
+    /// when `None`.
+    fn with_precision(gl: B, precision_override: Option<FloatPrecision>) -> Result<Self, JsValue> {
+        let precision = precision_override.unwrap_or_else(|| FloatPrecision::best_supported(&gl));
         let vs = compile_shader(&gl, WebGlRenderingContext::VERTEX_SHADER, VERTEX_SHADER_SRC)?;
         let fs = compile_shader(
             &gl,
             WebGlRenderingContext::FRAGMENT_SHADER,
-            FRAGMENT_SHADER_SRC,
+            &fragment_source(FRAGMENT_SHADER_SRC, precision),
         )?;
 
         let program = link_program(&gl, &vs, &fs)?;
         gl.use_program(Some(&program));
 
         gl.enable(WebGlRenderingContext::DEPTH_TEST);
+        // Primitives wind counter-clockwise when seen from outside. Culling starts off.
+        gl.front_face(WebGlRenderingContext::CCW);
+
+        // Enabling the extension is all WebGL1 needs to accept UNSIGNED_INT indices;
+        // WebGL2 supports them natively.
+        let u32_indices = gl.is_webgl2() || gl.enable_extension("OES_element_index_uint");
 
+        let vao = gl.create_vertex_array();
+        let grid = LinePass::new(gl.clone())?;
+        let mut axes = LinePass::new(gl.clone())?;
+        axes.set_lines(&geometry::axes(1.0));
+        let normals = LinePass::new(gl.clone())?;
+        let thick_lines = ThickLinePass::new(gl.clone())?;
         let vbo = gl
             .create_buffer()
             .ok_or_else(|| js_error("failed to create position buffer"))?;
-        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&vbo));
-
-        let position_location = gl.get_attrib_location(&program, "position") as u32;
-        gl.vertex_attrib_pointer_with_i32(
-            position_location,
-            3,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
-        gl.enable_vertex_attrib_array(position_location);
-
         let nbo = gl
             .create_buffer()
             .ok_or_else(|| js_error("failed to create normal buffer"))?;
-        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&nbo));
+        let uv_bo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create uv buffer"))?;
+        let tangent_bo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create tangent buffer"))?;
+        let color_bo = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create color buffer"))?;
+
+        let position_location = gl.get_attrib_location(&program, "position") as u32;
         let normal_location = gl.get_attrib_location(&program, "normal") as u32;
-        gl.vertex_attrib_pointer_with_i32(
-            normal_location,
-            3,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
-        gl.enable_vertex_attrib_array(normal_location);
+        // -1 when the compiler stripped the attribute.
+        let uv_location = gl.get_attrib_location(&program, "a_uv");
+        let tangent_location = gl.get_attrib_location(&program, "a_tangent");
+        let color_location = gl.get_attrib_location(&program, "a_color");
 
         let model_location = gl
             .get_uniform_location(&program, "u_model")
             .ok_or_else(|| js_error("missing uniform u_model"))?;
-        let view_location = gl
-            .get_uniform_location(&program, "u_view")
-            .ok_or_else(|| js_error("missing uniform u_view"))?;
-        let proj_location = gl
-            .get_uniform_location(&program, "u_proj")
-            .ok_or_else(|| js_error("missing uniform u_proj"))?;
-        let light_dir_location = gl
-            .get_uniform_location(&program, "u_light_dir_vs")
-            .ok_or_else(|| js_error("missing uniform u_light_dir_vs"))?;
+        let normal_matrix_location = gl
+            .get_uniform_location(&program, "u_normal_matrix")
+            .ok_or_else(|| js_error("missing uniform u_normal_matrix"))?;
+        let uniforms = MeshUniforms::new(&gl, &program)?;
 
-        Ok(Self {
+        let instancing = gl.instancing();
+        let instanced = match instancing {
+            Some(_) => Some(InstancedProgram::new(&gl, precision)?),
+            None => None,
+        };
+        let flat = FlatProgram::new(&gl)?;
+
+        let renderer = Self {
             gl,
             program,
+            shaders: [vs, fs],
+            vao,
             vbo,
             nbo,
+            uv_bo,
+            tangent_bo,
+            color_bo,
             ibo: None,
             wireframe_ibo: None,
             position_location,
             normal_location,
+            uv_location,
+            tangent_location,
+            color_location,
             model_location,
-            view_location,
-            proj_location,
-            light_dir_location,
+            normal_matrix_location,
+            uniforms,
+            instancing,
+            instanced,
+            flat,
             index_count: 0,
+            index_mode: WebGlRenderingContext::TRIANGLES,
+            triangle_count: 0,
             wireframe_index_count: 0,
+            index_type: WebGlRenderingContext::UNSIGNED_SHORT,
+            u32_indices,
             vertex_count: 0,
+            topology: Topology::Triangles,
+            has_vertex_colors: false,
             render_mode: RenderMode::Solid,
-        })
+            wireframe_overlay: false,
+            cull_backfaces: false,
+            clear_color: [211.0 / 255.0, 211.0 / 255.0, 211.0 / 255.0, 1.0],
+            lights: vec![Light::default_key()],
+            base_color: DEFAULT_BASE_COLOR,
+            ambient: DEFAULT_AMBIENT,
+            sky_color: DEFAULT_SKY_COLOR,
+            ground_color: DEFAULT_GROUND_COLOR,
+            world_up: Vec3::new(0.0, 1.0, 0.0),
+            specular_color: DEFAULT_SPECULAR,
+            shininess: DEFAULT_SHININESS,
+            shading_model: ShadingModel::default(),
+            transparent: false,
+            opacity: 1.0,
+            gamma: 1.0,
+            fog_color: [1.0, 1.0, 1.0],
+            fog_near: 0.0,
+            fog_far: 0.0,
+            grid,
+            grid_visible: false,
+            grid_color: DEFAULT_GRID_COLOR,
+            axes,
+            axes_visible: false,
+            normals,
+            normals_visible: false,
+            thick_lines,
+            line_width: 1.0,
+            selected: false,
+            outline_color: DEFAULT_OUTLINE_COLOR,
+            ground_shadow: None,
+            mesh_bounds: Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)),
+            matcap: None,
+            base_texture: None,
+            environment: None,
+            reflectivity: DEFAULT_REFLECTIVITY,
+            stats: Cell::new(RenderStats::default()),
+            precision_override,
+            released: false,
+        };
+        // Record the attribute setup once; WebGL1 (no VAO) redoes it around every draw.
+        if renderer.vao.is_some() {
+            renderer.gl.bind_vertex_array(renderer.vao.as_ref());
+            renderer.enable_attributes();
+        }
+        Ok(renderer)
+    }
+
+    /// Point each attribute at its buffer and enable the arrays the current mesh fills.
+    fn enable_attributes(&self) {
+        let attributes = [
+            (self.position_location as i32, &self.vbo, 3, true),
+            (self.normal_location as i32, &self.nbo, 3, true),
+            (self.uv_location, &self.uv_bo, 2, true),
+            (self.tangent_location, &self.tangent_bo, 4, true),
+            (
+                self.color_location,
+                &self.color_bo,
+                3,
+                self.has_vertex_colors,
+            ),
+        ];
+        for (location, buffer, size, used) in attributes {
+            if location < 0 {
+                continue;
+            }
+            if used {
+                self.gl
+                    .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+                self.gl.vertex_attrib_pointer_with_i32(
+                    location as u32,
+                    size,
+                    WebGlRenderingContext::FLOAT,
+                    false,
+                    0,
+                    0,
+                );
+                self.gl.enable_vertex_attrib_array(location as u32);
+            } else {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+        }
+    }
+
+    /// WebGL1 only: leave no array enabled that another pass would validate against.
+    fn disable_attributes(&self) {
+        let locations = [
+            self.position_location as i32,
+            self.normal_location as i32,
+            self.uv_location,
+            self.tangent_location,
+            self.color_location,
+        ];
+        for location in locations.into_iter().filter(|&l| l >= 0) {
+            self.gl.disable_vertex_attrib_array(location as u32);
+        }
+    }
+
+    /// Recreate the program and buffers on the same context after `webglcontextrestored`.
+    /// Colors, lights, material, gamma, fog, render mode, line width, culling, selection,
+    /// textures and the precision override carry over; the mesh, grid and normal lines
+    /// must be re-uploaded.
+    pub(crate) fn rebuild(&mut self) -> Result<(), JsValue> {
+        if self.gl.is_context_lost() {
+            return Err(js_error("WebGL context is still lost"));
+        }
+        let fresh = self.rebuilt(self.precision_override)?;
+        // The old objects died with the lost context; there is nothing left to delete.
+        self.released = true;
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Recompile the mesh programs with a forced fragment precision, or with the best the
+    /// context supports when `None`. Replaces every GL object like `rebuild`, so the mesh,
+    /// grid and normal lines must be re-uploaded.
+    pub(crate) fn set_precision(
+        &mut self,
+        precision: Option<FloatPrecision>,
+    ) -> Result<(), JsValue> {
+        let fresh = self.rebuilt(precision)?;
+        self.release();
+        *self = fresh;
+        Ok(())
+    }
+
+    /// A new renderer on the same context with this one's settings.
+    fn rebuilt(&mut self, precision: Option<FloatPrecision>) -> Result<Self, JsValue> {
+        let mut fresh = Self::with_precision(self.gl.clone(), precision)?;
+        fresh.render_mode = self.render_mode;
+        fresh.wireframe_overlay = self.wireframe_overlay;
+        fresh.clear_color = self.clear_color;
+        fresh.lights = self.lights.clone();
+        fresh.base_color = self.base_color;
+        fresh.ambient = self.ambient;
+        fresh.sky_color = self.sky_color;
+        fresh.ground_color = self.ground_color;
+        fresh.world_up = self.world_up;
+        fresh.specular_color = self.specular_color;
+        fresh.shininess = self.shininess;
+        fresh.shading_model = self.shading_model;
+        fresh.transparent = self.transparent;
+        fresh.opacity = self.opacity;
+        fresh.gamma = self.gamma;
+        fresh.fog_color = self.fog_color;
+        fresh.fog_near = self.fog_near;
+        fresh.fog_far = self.fog_far;
+        fresh.grid_color = self.grid_color;
+        fresh.axes_visible = self.axes_visible;
+        fresh.line_width = self.line_width;
+        fresh.selected = self.selected;
+        fresh.outline_color = self.outline_color;
+        fresh.ground_shadow = self.ground_shadow;
+        let uploaded = fresh
+            .set_matcap(self.matcap.as_ref().map(|t| t.image.clone()))
+            .and_then(|()| fresh.set_texture(self.base_texture.as_ref().map(|t| t.image.clone())))
+            .and_then(|()| {
+                fresh.set_environment(self.environment.as_ref().map(|t| t.image.clone()))
+            });
+        if let Err(err) = uploaded {
+            // This renderer keeps drawing; only the half-built one goes.
+            fresh.release();
+            return Err(err);
+        }
+        fresh.reflectivity = self.reflectivity;
+        // Capabilities are context state, reset along with everything else.
+        fresh.set_cull(self.cull_backfaces);
+        Ok(fresh)
+    }
+
+    /// Delete every GL object the renderer owns. Safe to call more than once; the
+    /// renderer must not draw or upload afterwards.
+    pub(crate) fn release(&mut self) {
+        if std::mem::replace(&mut self.released, true) {
+            return;
+        }
+        let gl = &self.gl;
+        let buffers = [
+            &self.vbo,
+            &self.nbo,
+            &self.uv_bo,
+            &self.tangent_bo,
+            &self.color_bo,
+        ];
+        for buffer in buffers
+            .into_iter()
+            .chain(&self.ibo)
+            .chain(&self.wireframe_ibo)
+        {
+            gl.delete_buffer(Some(buffer));
+        }
+        gl.delete_vertex_array(self.vao.as_ref());
+        gl.delete_program(Some(&self.program));
+        for shader in &self.shaders {
+            gl.delete_shader(Some(shader));
+        }
+        if let Some(instanced) = &self.instanced {
+            instanced.delete(gl);
+        }
+        self.flat.delete(gl);
+        for texture in self.matcap.iter().chain(&self.base_texture) {
+            gl.delete_texture(Some(&texture.handle));
+        }
+        if let Some(environment) = &self.environment {
+            gl.delete_texture(Some(&environment.handle));
+        }
+        self.grid.delete();
+        self.axes.delete();
+        self.normals.delete();
+        self.thick_lines.delete();
+    }
+
+    /// Read the drawing buffer as RGBA8, bottom row first. Only meaningful right after a
+    /// draw in the same task: without `preserveDrawingBuffer` the browser clears it once
+    /// the frame is composited.
+    pub(crate) fn read_pixels(&self, width: i32, height: i32) -> Result<Vec<u8>, JsValue> {
+        let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * 4];
+        self.gl.read_pixels(0, 0, width, height, &mut pixels)?;
+        Ok(pixels)
+    }
+
+    pub(crate) fn supports_u32_indices(&self) -> bool {
+        self.u32_indices
+    }
+
+    pub(crate) fn clear_color(&self) -> [f32; 4] {
+        self.clear_color
+    }
+
+    pub(crate) fn set_clear_color(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.clear_color = [r, g, b, a].map(|c| c.clamp(0.0, 1.0));
+    }
+
+    /// Direction of the first light (the default key light when none are set).
+    pub(crate) fn light_direction(&self) -> Vec3 {
+        self.lights
+            .first()
+            .map_or_else(|| Light::default_key().dir, |l| l.dir)
+    }
+
+    /// Re-aim the first light, adding it back if every light was removed.
+    pub(crate) fn set_light_direction(&mut self, dir: Vec3) {
+        let dir = light_direction_or_default(dir);
+        match self.lights.first_mut() {
+            Some(light) => light.dir = dir,
+            None => self.lights.push(Light {
+                dir,
+                ..Light::default_key()
+            }),
+        }
+    }
+
+    pub(crate) fn set_lights(&mut self, lights: Vec<Light>) {
+        self.lights = lights;
+        self.lights.truncate(MAX_LIGHTS);
+    }
+
+    pub(crate) fn set_base_color(&mut self, r: f32, g: f32, b: f32) {
+        self.base_color = [r, g, b].map(|c| c.clamp(0.0, 1.0));
+    }
+
+    pub(crate) fn set_material(&mut self, ambient: [f32; 3], specular: [f32; 3], shininess: f32) {
+        self.ambient = ambient.map(|c| c.clamp(0.0, 1.0));
+        self.specular_color = specular.map(|c| c.clamp(0.0, 1.0));
+        // pow(0, 0) is undefined in GLSL; keep the exponent positive.
+        self.shininess = shininess.max(1.0);
+    }
+
+    /// Tint the ambient term from `ground` on surfaces facing down to `sky` on surfaces
+    /// facing up; components in [0,1].
+    pub(crate) fn set_hemisphere_light(&mut self, sky: [f32; 3], ground: [f32; 3]) {
+        self.sky_color = sky.map(|c| c.clamp(0.0, 1.0));
+        self.ground_color = ground.map(|c| c.clamp(0.0, 1.0));
+    }
+
+    /// The world axis the hemisphere light's sky is above. Zero vectors are ignored.
+    pub(crate) fn set_world_up(&mut self, up: Vec3) {
+        if up.length() > 0.0 {
+            self.world_up = up.normalize();
+        }
+    }
+
+    /// Shade the mesh from a material capture image instead of the lights, or go back to
+    /// lighting with `None`. Replaces the previous matcap, which stays if the upload fails.
+    pub(crate) fn set_matcap(&mut self, image: Option<TextureImage>) -> Result<(), JsValue> {
+        let texture = image
+            .map(|image| self.upload_texture(image, false))
+            .transpose()?;
+        if let Some(old) = std::mem::replace(&mut self.matcap, texture) {
+            self.gl.delete_texture(Some(&old.handle));
+        }
+        Ok(())
+    }
+
+    /// Tint the base color by `image`, read at the mesh's texture coordinates and repeated
+    /// outside [0,1], or remove the texture with `None`. A failed upload keeps the old one.
+    pub(crate) fn set_texture(&mut self, image: Option<TextureImage>) -> Result<(), JsValue> {
+        let texture = image
+            .map(|image| self.upload_texture(image, true))
+            .transpose()?;
+        if let Some(old) = std::mem::replace(&mut self.base_texture, texture) {
+            self.gl.delete_texture(Some(&old.handle));
+        }
+        Ok(())
+    }
+
+    /// Reflect a cube map in the mesh, or stop reflecting with `None`. A failed upload
+    /// keeps the old one.
+    pub(crate) fn set_environment(&mut self, image: Option<CubemapImage>) -> Result<(), JsValue> {
+        let texture = image.map(|image| self.upload_cubemap(image)).transpose()?;
+        if let Some(old) = std::mem::replace(&mut self.environment, texture) {
+            self.gl.delete_texture(Some(&old.handle));
+        }
+        Ok(())
+    }
+
+    /// How much the environment covers the shaded color, in [0,1].
+    pub(crate) fn set_reflectivity(&mut self, amount: f32) {
+        if amount.is_finite() {
+            self.reflectivity = amount.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Create a cube map from `image`'s six faces, sampled like a 2D texture of their size.
+    fn upload_cubemap(&self, image: CubemapImage) -> Result<Texture<B, CubemapImage>, JsValue> {
+        let target = WebGlRenderingContext::TEXTURE_CUBE_MAP;
+        let handle = self
+            .gl
+            .create_texture()
+            .ok_or_else(|| js_error("failed to create cube map"))?;
+        self.gl.bind_texture(target, Some(&handle));
+        let sampling = texture_sampling(image.size, image.size, false, self.gl.is_webgl2());
+        let parameters = [
+            (
+                WebGlRenderingContext::TEXTURE_MIN_FILTER,
+                sampling.min_filter,
+            ),
+            (
+                WebGlRenderingContext::TEXTURE_MAG_FILTER,
+                WebGlRenderingContext::LINEAR,
+            ),
+            (WebGlRenderingContext::TEXTURE_WRAP_S, sampling.wrap),
+            (WebGlRenderingContext::TEXTURE_WRAP_T, sampling.wrap),
+        ];
+        for (name, value) in parameters {
+            self.gl.tex_parameteri(target, name, value as i32);
+        }
+        let size = image.size as i32;
+        let face_len = image.faces.len() / 6;
+        let uploaded = image
+            .faces
+            .chunks_exact(face_len)
+            .zip(WebGlRenderingContext::TEXTURE_CUBE_MAP_POSITIVE_X..)
+            .try_for_each(|(face, face_target)| {
+                self.gl.tex_image_2d_rgba(face_target, size, size, face)
+            });
+        if uploaded.is_ok() && sampling.mipmaps {
+            self.gl.generate_mipmap(target);
+        }
+        self.gl.bind_texture(target, None);
+        if let Err(err) = uploaded.and_then(|()| check_gl_error(&self.gl, "uploading cube map")) {
+            self.gl.delete_texture(Some(&handle));
+            return Err(err);
+        }
+        Ok(Texture { handle, image })
+    }
+
+    /// Create a 2D texture from `image`, sampled as `texture_sampling` picks for its size;
+    /// `repeat` tiles it outside [0,1] where the context allows.
+    fn upload_texture(&self, image: TextureImage, repeat: bool) -> Result<Texture<B>, JsValue> {
+        let target = WebGlRenderingContext::TEXTURE_2D;
+        let handle = self
+            .gl
+            .create_texture()
+            .ok_or_else(|| js_error("failed to create texture"))?;
+        self.gl.bind_texture(target, Some(&handle));
+        let sampling = texture_sampling(image.width, image.height, repeat, self.gl.is_webgl2());
+        let parameters = [
+            (
+                WebGlRenderingContext::TEXTURE_MIN_FILTER,
+                sampling.min_filter,
+            ),
+            (
+                WebGlRenderingContext::TEXTURE_MAG_FILTER,
+                WebGlRenderingContext::LINEAR,
+            ),
+            (WebGlRenderingContext::TEXTURE_WRAP_S, sampling.wrap),
+            (WebGlRenderingContext::TEXTURE_WRAP_T, sampling.wrap),
+        ];
+        for (name, value) in parameters {
+            self.gl.tex_parameteri(target, name, value as i32);
+        }
+        let uploaded =
+            self.gl
+                .tex_image_2d_rgba(target, image.width as i32, image.height as i32, &image.rgba);
+        if uploaded.is_ok() && sampling.mipmaps {
+            self.gl.generate_mipmap(target);
+        }
+        self.gl.bind_texture(target, None);
+        if let Err(err) = uploaded.and_then(|()| check_gl_error(&self.gl, "uploading texture")) {
+            self.gl.delete_texture(Some(&handle));
+            return Err(err);
+        }
+        Ok(Texture { handle, image })
+    }
+
+    pub(crate) fn set_shading_model(&mut self, model: ShadingModel) {
+        self.shading_model = model;
+    }
+
+    pub(crate) fn set_transparent(&mut self, on: bool) {
+        self.transparent = on;
+    }
+
+    pub(crate) fn set_opacity(&mut self, alpha: f32) {
+        self.opacity = if alpha.is_finite() {
+            alpha.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+    }
+
+    /// Output gamma for the mesh (2.2 for sRGB displays, 1.0 = off). Invalid values turn
+    /// it off; the rest are clamped to 0.1..=5.0.
+    pub(crate) fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma_or_default(gamma);
+    }
+
+    /// Fade the mesh toward `color` between view-space depths `near` and `far`. `None`
+    /// turns fog off.
+    pub(crate) fn set_fog(&mut self, fog: Option<([f32; 3], f32, f32)>) {
+        match fog {
+            Some((color, near, far)) if near.is_finite() && far.is_finite() && far > near => {
+                self.fog_color = color.map(|c| c.clamp(0.0, 1.0));
+                self.fog_near = near;
+                self.fog_far = far;
+            }
+            _ => {
+                self.fog_near = 0.0;
+                self.fog_far = 0.0;
+            }
+        }
+    }
+
+    /// Upload grid lines to draw under the mesh, or hide the grid with `None`.
+    pub(crate) fn set_grid(&mut self, grid: Option<&Mesh>) {
+        self.grid_visible = grid.is_some();
+        if let Some(mesh) = grid {
+            self.grid.set_lines(mesh);
+        }
+    }
+
+    pub(crate) fn set_grid_color(&mut self, r: f32, g: f32, b: f32) {
+        self.grid_color = [r, g, b].map(|c| c.clamp(0.0, 1.0));
+    }
+
+    /// Upload normal segments (model space) to draw over the mesh, or hide them with `None`.
+    pub(crate) fn set_normal_lines(&mut self, lines: Option<&Mesh>) {
+        self.normals_visible = lines.is_some();
+        if let Some(mesh) = lines {
+            self.normals.set_lines(mesh);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> RenderStats {
+        self.stats.get()
+    }
+
+    pub(crate) fn set_axes_visible(&mut self, on: bool) {
+        self.axes_visible = on;
+    }
+
+    /// Width of line meshes in pixels. Above 1 they are drawn as screen-facing quads
+    /// (single placement only; instanced lines stay 1 pixel). Clamped to 1..=64.
+    pub(crate) fn set_line_width(&mut self, width: f32) {
+        self.line_width = if width.is_finite() {
+            width.clamp(1.0, 64.0)
+        } else {
+            1.0
+        };
     }
 
     pub(crate) fn set_render_mode(&mut self, mode: RenderMode) {
         self.render_mode = mode;
     }
 
-    pub(crate) fn set_mesh(&mut self, mesh: &Mesh) {
+    /// Outline the mesh as selected. Only solid triangle meshes drawn singly get one.
+    pub(crate) fn set_selected(&mut self, on: bool) {
+        self.selected = on;
+    }
+
+    pub(crate) fn set_outline_color(&mut self, r: f32, g: f32, b: f32) {
+        self.outline_color = [r, g, b];
+    }
+
+    /// Darken the ground under the mesh with its shadow from the first light, at
+    /// `alpha` in [0,1]; `None` turns it off. Only triangle meshes drawn singly cast one.
+    /// A non-finite alpha is ignored.
+    pub(crate) fn set_ground_shadow(&mut self, alpha: Option<f32>) {
+        match alpha {
+            Some(a) if !a.is_finite() => {}
+            _ => self.ground_shadow = alpha.map(|a| a.clamp(0.0, 1.0)),
+        }
+    }
+
+    pub(crate) fn set_wireframe_overlay(&mut self, on: bool) {
+        self.wireframe_overlay = on;
+    }
+
+    /// Enable or disable backface culling. Meshes whose winding is inverted (some imports)
+    /// disappear with culling on until their winding is flipped.
+    pub(crate) fn set_cull(&mut self, on: bool) {
+        self.cull_backfaces = on;
+        if on {
+            self.gl.enable(WebGlRenderingContext::CULL_FACE);
+        } else {
+            self.gl.disable(WebGlRenderingContext::CULL_FACE);
+        }
+    }
+
+    /// Upload `mesh` into the vertex and index buffers. Fails if a buffer can't be created
+    /// or the context reports an error (typically `OUT_OF_MEMORY` for huge meshes).
+    pub(crate) fn set_mesh(&mut self, mesh: &Mesh) -> Result<(), JsValue> {
+        // The index buffer binding below is vertex-array state.
+        self.gl.bind_vertex_array(self.vao.as_ref());
         self.gl
             .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.vbo));
-        upload_f32_slice(
-            &self.gl,
+        self.gl.buffer_data_f32(
             WebGlRenderingContext::ARRAY_BUFFER,
             &mesh.positions,
             WebGlRenderingContext::STATIC_DRAW,
         );
 
         self.vertex_count = (mesh.positions.len() / 3) as i32;
+        self.topology = mesh.topology;
+        self.mesh_bounds = mesh.bounds;
+        // Empty unless the mesh is made of lines.
+        self.thick_lines.set_quads(&geometry::line_quads(mesh));
 
         // Upload normals.
         self.gl
             .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.nbo));
-        upload_f32_slice(
-            &self.gl,
+        self.gl.buffer_data_f32(
             WebGlRenderingContext::ARRAY_BUFFER,
             &mesh.normals,
             WebGlRenderingContext::STATIC_DRAW,
         );
 
-        if mesh.indices.is_empty() {
-            self.ibo = None;
-            self.wireframe_ibo = None;
-            self.index_count = 0;
-            self.wireframe_index_count = 0;
-            return;
-        }
+        // Upload texture coordinates.
+        self.gl
+            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.uv_bo));
+        self.gl.buffer_data_f32(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            &mesh.uvs,
+            WebGlRenderingContext::STATIC_DRAW,
+        );
 
-        // Upload triangle indices
-        let ibo = self
-            .gl
-            .create_buffer()
-            .ok_or_else(|| js_error("failed to create index buffer"))
-            .unwrap();
+        // Upload tangents.
+        self.gl
+            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.tangent_bo));
+        self.gl.buffer_data_f32(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            &mesh.tangents,
+            WebGlRenderingContext::STATIC_DRAW,
+        );
+
+        // Without colors the array stays disabled so no buffer needs to cover the draw.
+        self.has_vertex_colors = !mesh.colors.is_empty() && self.color_location >= 0;
+        if self.has_vertex_colors {
+            self.gl
+                .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.color_bo));
+            self.gl.buffer_data_f32(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &mesh.colors,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+        if self.vao.is_some() {
+            self.enable_attributes();
+        }
+
+        // Index buffers are created per mesh; free the previous mesh's.
+        self.gl.delete_buffer(self.ibo.take().as_ref());
+        self.gl.delete_buffer(self.wireframe_ibo.take().as_ref());
+        if mesh.indices.is_empty()
+            || (matches!(mesh.indices, IndexBuffer::U32(_)) && !self.u32_indices)
+        {
+            self.index_count = 0;
+            self.wireframe_index_count = 0;
+            return check_gl_error(&self.gl, "uploading mesh");
+        }
+
+        // Upload triangle indices, as a strip when the mesh has one (fewer indices to fetch).
+        let strip = (!mesh.strip.is_empty()).then(|| IndexBuffer::U16(mesh.strip.clone()));
+        let solid_indices = strip.as_ref().unwrap_or(&mesh.indices);
+        let ibo = self
+            .gl
+            .create_buffer()
+            .ok_or_else(|| js_error("failed to create index buffer"))?;
         self.gl
             .bind_buffer(WebGlRenderingContext::ELEMENT_ARRAY_BUFFER, Some(&ibo));
-        upload_u16_slice(
+        upload_indices(
             &self.gl,
             WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
-            &mesh.indices,
+            solid_indices,
             WebGlRenderingContext::STATIC_DRAW,
         );
         self.ibo = Some(ibo);
-        self.index_count = mesh.indices.len() as i32;
-
-        // Generate wireframe edge indices from triangle indices
-        // Each triangle (i0, i1, i2) becomes 3 edges: (i0,i1), (i1,i2), (i2,i0)
-        let mut wireframe_indices = Vec::new();
-        for chunk in mesh.indices.chunks_exact(3) {
-            let i0 = chunk[0];
-            let i1 = chunk[1];
-            let i2 = chunk[2];
-            wireframe_indices.push(i0);
-            wireframe_indices.push(i1);
-            wireframe_indices.push(i1);
-            wireframe_indices.push(i2);
-            wireframe_indices.push(i2);
-            wireframe_indices.push(i0);
-        }
+        self.index_count = solid_indices.len() as i32;
+        self.index_mode = match strip {
+            Some(_) => WebGlRenderingContext::TRIANGLE_STRIP,
+            None => WebGlRenderingContext::TRIANGLES,
+        };
+        self.triangle_count = (mesh.indices.len() / 3) as i32;
+        // A strip is always 16-bit, and only built for meshes whose list is too.
+        self.index_type = match mesh.indices {
+            IndexBuffer::U16(_) => WebGlRenderingContext::UNSIGNED_SHORT,
+            IndexBuffer::U32(_) => WebGlRenderingContext::UNSIGNED_INT,
+        };
+
+        // Generate wireframe edge indices from triangle indices (shared edges once)
+        let wireframe_indices = match &mesh.indices {
+            IndexBuffer::U16(ix) => IndexBuffer::U16(build_wireframe_indices(ix)),
+            IndexBuffer::U32(ix) => IndexBuffer::U32(build_wireframe_indices(ix)),
+        };
 
         let wireframe_ibo = self
             .gl
             .create_buffer()
-            .ok_or_else(|| js_error("failed to create wireframe index buffer"))
-            .unwrap();
+            .ok_or_else(|| js_error("failed to create wireframe index buffer"))?;
         self.gl
             .bind_buffer(WebGlRenderingContext::ELEMENT_ARRAY_BUFFER, Some(&wireframe_ibo));
-        upload_u16_slice(
+        upload_indices(
             &self.gl,
             WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
             &wireframe_indices,
@@ -190,6 +1316,7 @@ impl Renderer {
         );
         self.wireframe_ibo = Some(wireframe_ibo);
         self.wireframe_index_count = wireframe_indices.len() as i32;
+        check_gl_error(&self.gl, "uploading mesh")
     }
 
     pub(crate) fn draw(
@@ -198,63 +1325,664 @@ impl Renderer {
         height: i32,
         proj: &[f32; 16],
         view: &[f32; 16],
-        model: &[f32; 16],
+        placement: Placement,
     ) {
-        // Keep `program`/buffers fields alive; WebGL resources are tied to JS GC.
-        let _ = (
-            &self.program,
-            &self.vbo,
-            &self.nbo,
-            &self.ibo,
-            self.position_location,
-            self.normal_location,
-            &self.model_location,
-            &self.view_location,
-            &self.proj_location,
-            &self.light_dir_location,
-        );
-
+        let started = self.gl.now_ms();
+        let mut stats = RenderStats::default();
         self.gl.viewport(0, 0, width, height);
+
+        // The default WebGL drawing buffer is premultiplied-alpha, so a translucent
+        // background over the page needs premultiplied RGB.
+        let [r, g, b, a] = self.clear_color;
+        self.gl.clear_color(r * a, g * a, b * a, a);
+        // Clearing honours the depth mask, which a transparent frame leaves off.
+        self.gl.depth_mask(true);
+        let triangles = self.topology == Topology::Triangles;
+        let outline = self.selected && triangles && matches!(self.render_mode, RenderMode::Solid);
+        let shadow = self.ground_shadow.is_some() && triangles;
+        let mut buffers =
+            WebGlRenderingContext::COLOR_BUFFER_BIT | WebGlRenderingContext::DEPTH_BUFFER_BIT;
+        if outline || shadow {
+            buffers |= WebGlRenderingContext::STENCIL_BUFFER_BIT;
+        }
+        self.gl.clear(buffers);
+        let viewport = [width.max(1) as f32, height.max(1) as f32];
+
+        // The grid is opaque and drawn first so the mesh (even a transparent one) covers it.
+        if self.grid_visible {
+            self.gl.disable(WebGlRenderingContext::BLEND);
+            let n = self.grid.draw(proj, view, self.grid_color);
+            if n > 0 {
+                stats.record(WebGlRenderingContext::LINES, n, n);
+            }
+        }
+        // The shadow lies on the ground, under the mesh drawn next.
+        if let (
+            true,
+            Placement::Single {
+                model,
+                normal_matrix,
+            },
+        ) = (shadow, &placement)
+        {
+            self.draw_ground_shadow(&mut stats, viewport, proj, view, model, normal_matrix);
+        }
+
+        // Depth testing stays on either way so the mesh still occludes itself against
+        // what is already drawn. Destination alpha is accumulated separately so the
+        // canvas stays opaque where the clear color is.
+        let pass = PassState::new(self.transparent, self.opacity);
+        if pass.blend {
+            self.gl.enable(WebGlRenderingContext::BLEND);
+            self.gl.blend_func_separate(
+                WebGlRenderingContext::SRC_ALPHA,
+                WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+                WebGlRenderingContext::ONE,
+                WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+            );
+        } else {
+            self.gl.disable(WebGlRenderingContext::BLEND);
+        }
+        self.gl.depth_mask(pass.depth_write);
+
+        match placement {
+            Placement::Single {
+                model,
+                normal_matrix,
+            } => {
+                if self.line_width > 1.0
+                    && matches!(self.topology, Topology::Lines | Topology::LineStrip)
+                {
+                    self.draw_thick_lines(&mut stats, width, height, proj, view, model);
+                } else if outline {
+                    // Mark the whole silhouette, hidden parts included, so the rim drawn
+                    // outside it is never cut by what covers the mesh.
+                    self.gl.enable(WebGlRenderingContext::STENCIL_TEST);
+                    self.gl.stencil_mask(0xFF);
+                    self.gl.stencil_func(WebGlRenderingContext::ALWAYS, 1, 0xFF);
+                    self.gl.stencil_op(
+                        WebGlRenderingContext::KEEP,
+                        WebGlRenderingContext::REPLACE,
+                        WebGlRenderingContext::REPLACE,
+                    );
+                    self.draw_models(&mut stats, proj, view, &pass, [(*model, *normal_matrix)]);
+                    self.draw_outline(&mut stats, viewport, proj, view, model, normal_matrix);
+                } else {
+                    self.draw_models(&mut stats, proj, view, &pass, [(*model, *normal_matrix)]);
+                }
+                if self.normals_visible {
+                    self.gl.disable(WebGlRenderingContext::BLEND);
+                    self.gl.depth_mask(true);
+                    let model_view = Mat4 { m: *view }.mul(Mat4 { m: *model });
+                    let n = self.normals.draw(proj, &model_view.m, NORMAL_LINE_COLOR);
+                    if n > 0 {
+                        stats.record(WebGlRenderingContext::LINES, n, n);
+                    }
+                }
+            }
+            Placement::Instances { count: 0, .. } => {}
+            Placement::Instances { models, count } => match (&self.instancing, &self.instanced) {
+                (Some(instancing), Some(instanced)) => self.draw_instances(
+                    &mut stats, instancing, instanced, proj, view, &pass, models, count,
+                ),
+                _ => {
+                    // No instancing support: one draw per matrix with the main program.
+                    let view = Mat4 { m: *view };
+                    let placements = models.chunks_exact(16).map(|m| {
+                        let model = Mat4 {
+                            m: m.try_into().expect("chunks of 16"),
+                        };
+                        (model.m, view.mul(model).normal_matrix())
+                    });
+                    self.draw_models(&mut stats, proj, &view.m, &pass, placements);
+                }
+            },
+        }
+
+        if self.axes_visible {
+            let n = self.draw_axes_gizmo(width, height, view);
+            stats.record(WebGlRenderingContext::LINES, n, n);
+        }
+
+        stats.last_frame_ms = self.gl.now_ms() - started;
+        self.stats.set(stats);
+    }
+
+    /// Point `uniforms` (of the program in use) at the camera, lights and material.
+    fn set_mesh_uniforms(
+        &self,
+        uniforms: &MeshUniforms<B>,
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        pass: &PassState,
+    ) {
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&uniforms.view), false, view);
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&uniforms.proj), false, proj);
+        if !self.lights.is_empty() {
+            let dirs: Vec<f32> = self
+                .lights
+                .iter()
+                .flat_map(|l| [l.dir.x, l.dir.y, l.dir.z])
+                .collect();
+            let colors: Vec<f32> = self.lights.iter().flat_map(|l| l.color).collect();
+            self.gl
+                .uniform3fv_with_f32_array(Some(&uniforms.light_dirs), &dirs);
+            self.gl
+                .uniform3fv_with_f32_array(Some(&uniforms.light_colors), &colors);
+        }
+        self.gl
+            .uniform1i(Some(&uniforms.light_count), self.lights.len() as i32);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.base_color), &self.base_color);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.ambient), &self.ambient);
         self.gl
-            .uniform_matrix4fv_with_f32_array(Some(&self.model_location), false, model);
+            .uniform3fv_with_f32_array(Some(&uniforms.sky_color), &self.sky_color);
         self.gl
-            .uniform_matrix4fv_with_f32_array(Some(&self.view_location), false, view);
+            .uniform3fv_with_f32_array(Some(&uniforms.ground_color), &self.ground_color);
+        // The view's rotation part takes the up axis into view space, like the lights.
+        let up = self.world_up;
+        let up_vs: [f32; 3] =
+            std::array::from_fn(|r| view[r] * up.x + view[4 + r] * up.y + view[8 + r] * up.z);
         self.gl
-            .uniform_matrix4fv_with_f32_array(Some(&self.proj_location), false, proj);
-        // Light pointing from camera toward the scene with slight tilt.
-        self.gl.uniform3f(Some(&self.light_dir_location), -0.3, -0.5, -1.0);
+            .uniform3fv_with_f32_array(Some(&uniforms.up_vs), &up_vs);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.specular_color), &self.specular_color);
+        self.gl.uniform1f(Some(&uniforms.shininess), self.shininess);
+        self.gl.uniform1f(Some(&uniforms.opacity), pass.opacity);
+        self.gl.uniform1f(Some(&uniforms.gamma), self.gamma);
+        self.gl.uniform1i(
+            Some(&uniforms.shading_model),
+            self.shading_model.uniform_value(),
+        );
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.fog_color), &self.fog_color);
+        self.gl.uniform1f(Some(&uniforms.fog_near), self.fog_near);
+        self.gl.uniform1f(Some(&uniforms.fog_far), self.fog_far);
+        self.gl.uniform1i(
+            Some(&uniforms.use_vertex_color),
+            self.has_vertex_colors as i32,
+        );
 
+        // Every sampler gets its own unit even while unused: a 2D and a cube sampler left
+        // on the same unit fail the draw.
+        let textures = [
+            (
+                &uniforms.matcap,
+                &uniforms.use_matcap,
+                MATCAP_TEXTURE_UNIT,
+                &self.matcap,
+            ),
+            (
+                &uniforms.base_texture,
+                &uniforms.use_texture,
+                BASE_TEXTURE_UNIT,
+                &self.base_texture,
+            ),
+        ];
+        for (sampler, used, unit, texture) in textures {
+            self.gl.uniform1i(Some(sampler), unit as i32);
+            self.gl.uniform1i(Some(used), texture.is_some() as i32);
+            if let Some(texture) = texture {
+                self.gl
+                    .active_texture(WebGlRenderingContext::TEXTURE0 + unit);
+                self.gl
+                    .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture.handle));
+            }
+        }
         self.gl
-            .clear_color(211.0 / 255.0, 211.0 / 255.0, 211.0 / 255.0, 1.0);
-        self.gl.clear(
-            WebGlRenderingContext::COLOR_BUFFER_BIT | WebGlRenderingContext::DEPTH_BUFFER_BIT,
+            .uniform1i(Some(&uniforms.environment), ENVIRONMENT_TEXTURE_UNIT as i32);
+        self.gl.uniform1i(
+            Some(&uniforms.use_environment),
+            self.environment.is_some() as i32,
+        );
+        if let Some(environment) = &self.environment {
+            self.gl
+                .active_texture(WebGlRenderingContext::TEXTURE0 + ENVIRONMENT_TEXTURE_UNIT);
+            self.gl.bind_texture(
+                WebGlRenderingContext::TEXTURE_CUBE_MAP,
+                Some(&environment.handle),
+            );
+            self.gl
+                .uniform1f(Some(&uniforms.reflectivity), self.reflectivity);
+            self.gl.uniform_matrix3fv_with_f32_array(
+                Some(&uniforms.view_to_world),
+                false,
+                &view_to_world(view),
+            );
+        }
+    }
+
+    /// Draw the mesh once per (model, normal matrix) pair with the main program.
+    fn draw_models(
+        &self,
+        stats: &mut RenderStats,
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        pass: &PassState,
+        placements: impl IntoIterator<Item = ([f32; 16], [f32; 9])>,
+    ) {
+        self.gl.use_program(Some(&self.program));
+        self.set_mesh_uniforms(&self.uniforms, proj, view, pass);
+
+        // With a VAO the attribute setup is already recorded; WebGL1 redoes it per draw and
+        // disables the arrays afterwards so other passes don't validate against them.
+        self.gl.bind_vertex_array(self.vao.as_ref());
+        if self.vao.is_none() {
+            self.enable_attributes();
+        }
+        for (model, normal_matrix) in placements {
+            self.gl
+                .uniform_matrix4fv_with_f32_array(Some(&self.model_location), false, &model);
+            self.gl.uniform_matrix3fv_with_f32_array(
+                Some(&self.normal_matrix_location),
+                false,
+                &normal_matrix,
+            );
+            self.draw_passes(stats, &self.uniforms, None);
+        }
+        if self.vao.is_none() {
+            self.disable_attributes();
+        }
+    }
+
+    /// Draw `count` copies of the mesh in one call, reading model matrices per instance.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_instances(
+        &self,
+        stats: &mut RenderStats,
+        instancing: &B::Instancing,
+        instanced: &InstancedProgram<B>,
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        pass: &PassState,
+        models: &[f32],
+        count: i32,
+    ) {
+        self.gl.use_program(Some(&instanced.program));
+        self.set_mesh_uniforms(&instanced.uniforms, proj, view, pass);
+
+        // The vertex colors can change with every mesh upload, so the attribute setup is
+        // specified per draw even when the VAO could keep it.
+        self.gl.bind_vertex_array(instanced.vao.as_ref());
+        let attributes = [
+            (instanced.position_location, &self.vbo, 3, true),
+            (instanced.normal_location, &self.nbo, 3, true),
+            (instanced.uv_location, &self.uv_bo, 2, true),
+            (
+                instanced.color_location,
+                &self.color_bo,
+                3,
+                self.has_vertex_colors,
+            ),
+        ];
+        for (location, buffer, size, used) in attributes {
+            if location < 0 {
+                continue;
+            }
+            if used {
+                self.gl
+                    .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+                self.gl.vertex_attrib_pointer_with_i32(
+                    location as u32,
+                    size,
+                    WebGlRenderingContext::FLOAT,
+                    false,
+                    0,
+                    0,
+                );
+                self.gl.enable_vertex_attrib_array(location as u32);
+            } else {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+        }
+
+        // A mat4 attribute occupies four vec4 locations, each advancing once per instance.
+        self.gl.bind_buffer(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            Some(&instanced.instance_bo),
         );
+        self.gl.buffer_data_f32(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            models,
+            WebGlRenderingContext::DYNAMIC_DRAW,
+        );
+        let columns = (0..4).map(|col| (instanced.model_location as u32 + col, col as i32 * 16));
+        for (location, offset) in columns.clone() {
+            self.gl.vertex_attrib_pointer_with_i32(
+                location,
+                4,
+                WebGlRenderingContext::FLOAT,
+                false,
+                64,
+                offset,
+            );
+            self.gl.enable_vertex_attrib_array(location);
+            instancing.vertex_attrib_divisor(location, 1);
+        }
 
-        // Ensure buffers are bound at draw time.
+        self.draw_passes(stats, &instanced.uniforms, Some((instancing, count)));
+
+        // Without a VAO, divisors are global state the main pass would inherit.
+        if instanced.vao.is_none() {
+            let used = [
+                instanced.position_location,
+                instanced.normal_location,
+                instanced.uv_location,
+                instanced.color_location,
+            ];
+            for location in used.into_iter().filter(|&l| l >= 0) {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+            for (location, _) in columns {
+                instancing.vertex_attrib_divisor(location, 0);
+                self.gl.disable_vertex_attrib_array(location);
+            }
+        }
+    }
+
+    /// The line mesh as `line_width`-pixel quads in the base color.
+    fn draw_thick_lines(
+        &self,
+        stats: &mut RenderStats,
+        width: i32,
+        height: i32,
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        model: &[f32; 16],
+    ) {
+        // Quad winding follows the segment's screen direction, so neither side is a back.
+        if self.cull_backfaces {
+            self.gl.disable(WebGlRenderingContext::CULL_FACE);
+        }
+        let model_view = Mat4 { m: *view }.mul(Mat4 { m: *model });
+        let viewport = [width.max(1) as f32, height.max(1) as f32];
+        let n = self.thick_lines.draw(
+            proj,
+            &model_view.m,
+            viewport,
+            self.line_width,
+            self.base_color,
+        );
+        if n > 0 {
+            stats.record(WebGlRenderingContext::TRIANGLES, n, n);
+        }
+        if self.cull_backfaces {
+            self.gl.enable(WebGlRenderingContext::CULL_FACE);
+        }
+    }
+
+    /// The selection rim: the mesh grown by `OUTLINE_WIDTH_PX` in the outline color,
+    /// drawn over everything but only outside the silhouette the stencil marked. Turns
+    /// the stencil test off again.
+    fn draw_outline(
+        &self,
+        stats: &mut RenderStats,
+        viewport: [f32; 2],
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        model: &[f32; 16],
+        normal_matrix: &[f32; 9],
+    ) {
         self.gl
-            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.vbo));
+            .stencil_func(WebGlRenderingContext::NOTEQUAL, 1, 0xFF);
+        self.gl.stencil_mask(0x00);
+        self.gl.disable(WebGlRenderingContext::DEPTH_TEST);
+        self.gl.disable(WebGlRenderingContext::BLEND);
+        let style = FlatStyle {
+            color: self.outline_color,
+            alpha: 1.0,
+            grow_px: OUTLINE_WIDTH_PX,
+        };
+        self.draw_flat(stats, viewport, proj, view, model, normal_matrix, style);
+        self.gl.enable(WebGlRenderingContext::DEPTH_TEST);
+        self.gl.stencil_mask(0xFF);
+        self.gl.disable(WebGlRenderingContext::STENCIL_TEST);
+    }
+
+    /// The mesh flattened onto the ground plane through its lowest point, along the first
+    /// light, blended in `SHADOW_COLOR`. Nothing is drawn while that light doesn't shine
+    /// downward.
+    fn draw_ground_shadow(
+        &self,
+        stats: &mut RenderStats,
+        viewport: [f32; 2],
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        model: &[f32; 16],
+        normal_matrix: &[f32; 9],
+    ) {
+        let (Some(alpha), Some(light)) = (self.ground_shadow, self.lights.first()) else {
+            return;
+        };
+        let model = Mat4 { m: *model };
+        let bounds = &self.mesh_bounds;
+        let Some(shadow) = ground_shadow_matrix(bounds, &model, view, light.dir, self.world_up)
+        else {
+            return;
+        };
+
+        // Pulled toward the camera so a grid on the same plane doesn't z-fight with it;
+        // depth writes stay off so the mesh still draws over it.
+        self.gl.enable(WebGlRenderingContext::POLYGON_OFFSET_FILL);
+        self.gl.polygon_offset(-1.0, -1.0);
+        self.gl.depth_mask(false);
+        self.gl.enable(WebGlRenderingContext::BLEND);
+        self.gl.blend_func_separate(
+            WebGlRenderingContext::SRC_ALPHA,
+            WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+            WebGlRenderingContext::ZERO,
+            WebGlRenderingContext::ONE,
+        );
+        self.gl.enable(WebGlRenderingContext::STENCIL_TEST);
+        self.gl.stencil_mask(SHADOW_STENCIL_BIT);
+        let bit = SHADOW_STENCIL_BIT as i32;
         self.gl
-            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.nbo));
+            .stencil_func(WebGlRenderingContext::NOTEQUAL, bit, SHADOW_STENCIL_BIT);
+        self.gl.stencil_op(
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::REPLACE,
+        );
+
+        let style = FlatStyle {
+            color: SHADOW_COLOR,
+            alpha,
+            grow_px: 0.0,
+        };
+        let model = shadow.mul(model);
+        self.draw_flat(stats, viewport, proj, view, &model.m, normal_matrix, style);
 
-        match self.render_mode {
+        self.gl.disable(WebGlRenderingContext::STENCIL_TEST);
+        self.gl.stencil_mask(0xFF);
+        self.gl.disable(WebGlRenderingContext::POLYGON_OFFSET_FILL);
+    }
+
+    /// Draw the mesh unlit in `style` with the flat program, under the current depth,
+    /// blend and stencil state.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_flat(
+        &self,
+        stats: &mut RenderStats,
+        viewport: [f32; 2],
+        proj: &[f32; 16],
+        view: &[f32; 16],
+        model: &[f32; 16],
+        normal_matrix: &[f32; 9],
+        style: FlatStyle,
+    ) {
+        let flat = &self.flat;
+        self.gl.use_program(Some(&flat.program));
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&flat.model), false, model);
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&flat.view), false, view);
+        self.gl
+            .uniform_matrix4fv_with_f32_array(Some(&flat.proj), false, proj);
+        self.gl
+            .uniform_matrix3fv_with_f32_array(Some(&flat.normal_matrix), false, normal_matrix);
+        self.gl
+            .uniform2fv_with_f32_array(Some(&flat.viewport), &viewport);
+        self.gl.uniform1f(Some(&flat.grow), style.grow_px);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&flat.color), &style.color);
+        self.gl.uniform1f(Some(&flat.alpha), style.alpha);
+
+        // The buffers belong to the renderer, so the attribute setup is specified per draw.
+        self.gl.bind_vertex_array(flat.vao.as_ref());
+        let attributes = [
+            (flat.position_location, &self.vbo),
+            (flat.normal_location, &self.nbo),
+        ];
+        for (location, buffer) in attributes.into_iter().filter(|&(l, _)| l >= 0) {
+            self.gl
+                .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+            self.gl.vertex_attrib_pointer_with_i32(
+                location as u32,
+                3,
+                WebGlRenderingContext::FLOAT,
+                false,
+                0,
+                0,
+            );
+            self.gl.enable_vertex_attrib_array(location as u32);
+        }
+        self.draw_geometry(stats, RenderMode::Solid, None);
+        if flat.vao.is_none() {
+            for (location, _) in attributes.into_iter().filter(|&(l, _)| l >= 0) {
+                self.gl.disable_vertex_attrib_array(location as u32);
+            }
+        }
+    }
+
+    /// Camera-aligned axes in a corner viewport, on top of everything else.
+    fn draw_axes_gizmo(&self, width: i32, height: i32, view: &[f32; 16]) -> i32 {
+        let size = (width.min(height) / 5).clamp(48, 160);
+        self.gl.viewport(0, 0, size, size);
+        self.gl.disable(WebGlRenderingContext::DEPTH_TEST);
+        self.gl.disable(WebGlRenderingContext::BLEND);
+        let proj = Mat4::orthographic(-1.3, 1.3, -1.3, 1.3, -2.0, 2.0);
+        let drawn = self
+            .axes
+            .draw(&proj.m, &rotation_only(view), [1.0, 1.0, 1.0]);
+        self.gl.enable(WebGlRenderingContext::DEPTH_TEST);
+        self.gl.viewport(0, 0, width, height);
+        drawn
+    }
+
+    /// Draw the mesh in the current render mode, plus the wireframe overlay when it is
+    /// on. `uniforms` belong to the program in use.
+    fn draw_passes(
+        &self,
+        stats: &mut RenderStats,
+        uniforms: &MeshUniforms<B>,
+        instances: Option<(&B::Instancing, i32)>,
+    ) {
+        let overlay = self.wireframe_overlay
+            && self.topology == Topology::Triangles
+            && matches!(self.render_mode, RenderMode::Solid);
+        if !overlay {
+            self.draw_geometry(stats, self.render_mode, instances);
+            return;
+        }
+        // Push the fill back rather than pulling the edges forward, so edges never show
+        // through geometry in front of them. Factor 1 scales with each triangle's depth
+        // slope (grazing faces need more room); units 1 adds the smallest resolvable depth
+        // step for faces seen head-on. Together they clear typical 16- and 24-bit depth
+        // buffers without visibly detaching the edges.
+        self.gl.enable(WebGlRenderingContext::POLYGON_OFFSET_FILL);
+        self.gl.polygon_offset(1.0, 1.0);
+        self.draw_geometry(stats, RenderMode::Solid, instances);
+        self.gl.disable(WebGlRenderingContext::POLYGON_OFFSET_FILL);
+
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.base_color), &WIREFRAME_OVERLAY_COLOR);
+        self.gl.uniform1i(Some(&uniforms.use_vertex_color), 0);
+        self.gl.uniform1i(Some(&uniforms.use_matcap), 0);
+        self.gl.uniform1i(Some(&uniforms.use_texture), 0);
+        self.gl.uniform1i(Some(&uniforms.use_environment), 0);
+        self.draw_geometry(stats, RenderMode::Wireframe, instances);
+        self.gl
+            .uniform3fv_with_f32_array(Some(&uniforms.base_color), &self.base_color);
+        self.gl.uniform1i(
+            Some(&uniforms.use_vertex_color),
+            self.has_vertex_colors as i32,
+        );
+        self.gl
+            .uniform1i(Some(&uniforms.use_matcap), self.matcap.is_some() as i32);
+        self.gl.uniform1i(
+            Some(&uniforms.use_texture),
+            self.base_texture.is_some() as i32,
+        );
+        self.gl.uniform1i(
+            Some(&uniforms.use_environment),
+            self.environment.is_some() as i32,
+        );
+    }
+
+    /// Issue the draw calls for the current mesh in `mode`, repeated over `instances`
+    /// when given.
+    fn draw_geometry(
+        &self,
+        stats: &mut RenderStats,
+        mode: RenderMode,
+        instances: Option<(&B::Instancing, i32)>,
+    ) {
+        let vertices = self.vertex_count.max(0);
+        let n = instances.map_or(1, |(_, n)| n);
+        let draw_arrays = |mode: u32, count: i32| match instances {
+            Some((instancing, n)) => instancing.draw_arrays_instanced(mode, 0, count, n),
+            None => self.gl.draw_arrays(mode, 0, count),
+        };
+        let draw_elements = |mode: u32, count: i32, offset: i32| match instances {
+            Some((instancing, n)) => {
+                instancing.draw_elements_instanced(mode, count, self.index_type, offset, n)
+            }
+            None => self
+                .gl
+                .draw_elements_with_i32(mode, count, self.index_type, offset),
+        };
+
+        match self.topology {
+            // Point clouds and lines look the same in every render mode.
+            Topology::Points => {
+                draw_arrays(WebGlRenderingContext::POINTS, vertices);
+                stats.record_instanced(WebGlRenderingContext::POINTS, vertices, vertices, n);
+                return;
+            }
+            Topology::Lines => {
+                draw_arrays(WebGlRenderingContext::LINES, vertices);
+                stats.record_instanced(WebGlRenderingContext::LINES, vertices, vertices, n);
+                return;
+            }
+            Topology::LineStrip => {
+                draw_arrays(WebGlRenderingContext::LINE_STRIP, vertices);
+                stats.record_instanced(WebGlRenderingContext::LINE_STRIP, vertices, vertices, n);
+                return;
+            }
+            Topology::Triangles => {}
+        }
+
+        match mode {
             RenderMode::Solid => {
                 if let Some(ibo) = &self.ibo {
                     self.gl.bind_buffer(
                         WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
                         Some(ibo),
                     );
-                    self.gl.draw_elements_with_i32(
+                    draw_elements(self.index_mode, self.index_count, 0);
+                    // Count the triangles drawn, not the strip's indices.
+                    stats.record_instanced(
                         WebGlRenderingContext::TRIANGLES,
-                        self.index_count,
-                        WebGlRenderingContext::UNSIGNED_SHORT,
-                        0,
+                        self.triangle_count * 3,
+                        vertices,
+                        n,
                     );
                 } else {
-                    self.gl.draw_arrays(
-                        WebGlRenderingContext::TRIANGLES,
-                        0,
-                        self.vertex_count.max(0),
-                    );
+                    draw_arrays(WebGlRenderingContext::TRIANGLES, vertices);
+                    stats.record_instanced(WebGlRenderingContext::TRIANGLES, vertices, vertices, n);
                 }
             }
             RenderMode::Wireframe => {
@@ -263,11 +1991,12 @@ impl Renderer {
                         WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
                         Some(wireframe_ibo),
                     );
-                    self.gl.draw_elements_with_i32(
+                    draw_elements(WebGlRenderingContext::LINES, self.wireframe_index_count, 0);
+                    stats.record_instanced(
                         WebGlRenderingContext::LINES,
                         self.wireframe_index_count,
-                        WebGlRenderingContext::UNSIGNED_SHORT,
-                        0,
+                        vertices,
+                        n,
                     );
                 } else if let Some(ibo) = &self.ibo {
                     // Fallback: draw triangles as line loops (less efficient)
@@ -275,43 +2004,980 @@ impl Renderer {
                         WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
                         Some(ibo),
                     );
-                    // Draw each triangle as a line loop
+                    // Draw each triangle as a line loop (offsets are in bytes)
+                    let index_size = match self.index_type {
+                        WebGlRenderingContext::UNSIGNED_INT => 4,
+                        _ => 2,
+                    };
                     for i in 0..(self.index_count / 3) {
-                        self.gl.draw_elements_with_i32(
-                            WebGlRenderingContext::LINE_LOOP,
-                            3,
-                            WebGlRenderingContext::UNSIGNED_SHORT,
-                            (i * 3) as i32,
-                        );
+                        draw_elements(WebGlRenderingContext::LINE_LOOP, 3, i * 3 * index_size);
+                        stats.record_instanced(WebGlRenderingContext::LINE_LOOP, 3, 0, n);
                     }
+                    stats.vertices += vertices.saturating_mul(n) as u32;
                 } else {
                     // Non-indexed wireframe: draw as lines
-                    let line_count = (self.vertex_count / 3) * 3;
-                    self.gl.draw_arrays(
-                        WebGlRenderingContext::LINES,
-                        0,
-                        line_count.max(0),
-                    );
+                    let line_count = ((self.vertex_count / 3) * 3).max(0);
+                    draw_arrays(WebGlRenderingContext::LINES, line_count);
+                    stats.record_instanced(WebGlRenderingContext::LINES, line_count, vertices, n);
                 }
             }
         }
     }
 }
 
-fn upload_f32_slice(gl: &WebGlRenderingContext, target: u32, data: &[f32], usage: u32) {
-    unsafe {
-        let view = js_sys::Float32Array::view(data);
-        gl.buffer_data_with_array_buffer_view(target, &view, usage);
+impl<B: GraphicsBackend> Drop for Renderer<B> {
+    fn drop(&mut self) {
+        self.release();
     }
 }
 
-fn upload_u16_slice(gl: &WebGlRenderingContext, target: u32, data: &[u16], usage: u32) {
-    unsafe {
-        let view = js_sys::Uint16Array::view(data);
-        gl.buffer_data_with_array_buffer_view(target, &view, usage);
+/// The inverse of a view matrix's rotation (its transpose), as a column-major 3x3 that
+/// takes view-space directions back to world space.
+fn view_to_world(view: &[f32; 16]) -> [f32; 9] {
+    std::array::from_fn(|i| view[(i % 3) * 4 + i / 3])
+}
+
+/// View-space direction `dir` in world space, through `view_to_world`: the same turn the
+/// fragment shader gives its environment lookups.
+fn direction_to_world(view: &[f32; 16], dir: Vec3) -> Vec3 {
+    let m = view_to_world(view);
+    Vec3::new(
+        m[0] * dir.x + m[3] * dir.y + m[6] * dir.z,
+        m[1] * dir.x + m[4] * dir.y + m[7] * dir.z,
+        m[2] * dir.x + m[5] * dir.y + m[8] * dir.z,
+    )
+}
+
+/// A column-major view matrix without its translation, so only the camera turn remains.
+fn rotation_only(view: &[f32; 16]) -> [f32; 16] {
+    let mut m = *view;
+    m[12] = 0.0;
+    m[13] = 0.0;
+    m[14] = 0.0;
+    m
+}
+
+/// World-space matrix that flattens a mesh with model-space `bounds`, placed by `model`,
+/// onto the ground plane (normal `up`, unit length) through its lowest point along a light
+/// travelling in `light_view_dir` (view space, as the renderer stores lights). `None`
+/// unless the light travels downward.
+fn ground_shadow_matrix(
+    bounds: &Bounds,
+    model: &Mat4,
+    view: &[f32; 16],
+    light_view_dir: Vec3,
+    up: Vec3,
+) -> Option<Mat4> {
+    let dir = direction_to_world(view, light_view_dir).normalize();
+    if dir.dot(up) > -1e-3 {
+        return None;
+    }
+    let ground = (0..8)
+        .map(|i| model.mul_point(bounds.corner(i)).dot(up))
+        .fold(f32::INFINITY, f32::min);
+    Some(Mat4::shadow_projection([up.x, up.y, up.z, -ground], dir))
+}
+
+/// Normalize a caller-supplied light direction; zero/NaN vectors fall back to the default.
+fn light_direction_or_default(dir: Vec3) -> Vec3 {
+    let len = dir.length();
+    if len.is_finite() && len > 1e-8 {
+        dir.div(len)
+    } else {
+        DEFAULT_LIGHT_DIR.normalize()
+    }
+}
+
+/// Filtering and wrapping for a `width` x `height` texture. Power-of-two sizes, and any
+/// size on WebGL2, get trilinear mipmaps and repeat when asked to; WebGL1 can do neither
+/// for other sizes (such textures sample as black), so those are filtered linearly and
+/// clamped at the edges.
+fn texture_sampling(width: u32, height: u32, repeat: bool, webgl2: bool) -> Sampling {
+    let full = webgl2 || (width.is_power_of_two() && height.is_power_of_two());
+    Sampling {
+        min_filter: if full {
+            WebGlRenderingContext::LINEAR_MIPMAP_LINEAR
+        } else {
+            WebGlRenderingContext::LINEAR
+        },
+        wrap: if full && repeat {
+            WebGlRenderingContext::REPEAT
+        } else {
+            WebGlRenderingContext::CLAMP_TO_EDGE
+        },
+        mipmaps: full,
+    }
+}
+
+/// Clamp a caller-supplied gamma; zero, negative and NaN fall back to 1.0 (off).
+fn gamma_or_default(gamma: f32) -> f32 {
+    if gamma.is_finite() && gamma > 0.0 {
+        gamma.clamp(0.1, 5.0)
+    } else {
+        1.0
+    }
+}
+
+/// Number of instances in a flat array of column-major 4x4 model matrices.
+pub(crate) fn instance_count(matrix_floats: usize) -> Result<i32, String> {
+    if !matrix_floats.is_multiple_of(16) {
+        return Err(format!(
+            "model matrix array length {matrix_floats} is not a multiple of 16"
+        ));
+    }
+    i32::try_from(matrix_floats / 16).map_err(|_| "too many instances".to_string())
+}
+
+/// Build lights from flattened `[x,y,z, ...]` directions and `[r,g,b, ...]` colors.
+/// Extra lights beyond `MAX_LIGHTS` are dropped; an empty list is allowed.
+pub(crate) fn lights_from_flat(dirs: &[f32], colors: &[f32]) -> Result<Vec<Light>, String> {
+    if !dirs.len().is_multiple_of(3) {
+        return Err(format!(
+            "light directions length {} is not a multiple of 3",
+            dirs.len()
+        ));
+    }
+    if colors.len() != dirs.len() {
+        return Err(format!(
+            "expected {} light color components, got {}",
+            dirs.len(),
+            colors.len()
+        ));
+    }
+    Ok(dirs
+        .chunks_exact(3)
+        .zip(colors.chunks_exact(3))
+        .take(MAX_LIGHTS)
+        .map(|(d, c)| Light {
+            dir: light_direction_or_default(Vec3::new(d[0], d[1], d[2])),
+            color: [c[0], c[1], c[2]].map(|v| v.max(0.0)),
+        })
+        .collect())
+}
+
+fn upload_indices<B: GraphicsBackend>(gl: &B, target: u32, indices: &IndexBuffer, usage: u32) {
+    match indices {
+        IndexBuffer::U16(data) => gl.buffer_data_u16(target, data, usage),
+        IndexBuffer::U32(data) => gl.buffer_data_u32(target, data, usage),
     }
 }
 
 fn js_error(msg: &str) -> JsValue {
     JsValue::from_str(msg)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn gamma_is_sanitized_and_declared_in_shader() {
+        assert!(FRAGMENT_SHADER_SRC.contains("uniform float u_gamma;"));
+        assert_eq!(gamma_or_default(2.2), 2.2);
+        assert_eq!(gamma_or_default(1.0), 1.0);
+        assert_eq!(gamma_or_default(100.0), 5.0);
+        assert_eq!(gamma_or_default(0.0), 1.0);
+        assert_eq!(gamma_or_default(-2.2), 1.0);
+        assert_eq!(gamma_or_default(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn fog_ramps_linearly_between_near_and_far() {
+        // Clamped from 0 at the near distance to 1 at the far one, and skipped entirely
+        // while the range is empty (the "off" state).
+        assert!(FRAGMENT_SHADER_SRC.contains("varying float v_fog_depth;"));
+        assert!(VERTEX_SHADER_SRC.contains("v_fog_depth = -pos_vs.z;"));
+        assert!(FRAGMENT_SHADER_SRC.contains("if (u_fog_far > u_fog_near)"));
+        assert!(FRAGMENT_SHADER_SRC
+            .contains("clamp((v_fog_depth - u_fog_near) / (u_fog_far - u_fog_near), 0.0, 1.0)"));
+        assert!(FRAGMENT_SHADER_SRC.contains("color = mix(color, u_fog_color, fog);"));
+    }
+
+    #[test]
+    fn matcap_uv_maps_normals_onto_the_image() {
+        // The view-space normal's xy from [-1,1] onto the image, up-facing normals at the
+        // top row (v = 0, as images are uploaded top row first).
+        assert!(FRAGMENT_SHADER_SRC.contains("vec2(0.5 + 0.5 * n.x, 0.5 - 0.5 * n.y)"));
+
+        assert!(TextureImage::new(&[0; 2 * 3 * 4], 2, 3).is_ok());
+        assert!(TextureImage::new(&[0; 5], 2, 3).is_err());
+        assert!(TextureImage::new(&[], 0, 0).is_err());
+    }
+
+    #[test]
+    fn environment_reflects_the_view_about_the_normal() {
+        assert!(FRAGMENT_SHADER_SRC.contains("vec3 r = u_view_to_world * reflect(-v, n);"));
+        let close = |a: Vec3, b: Vec3| a.sub(b).length() <= 1e-5;
+        // Facing the camera reflects straight back; a 45 degree slope sends it up.
+        let (ahead, facing) = (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let back = ahead.reflect(facing);
+        assert!(close(back, Vec3::new(0.0, 0.0, 1.0)));
+        let slope = Vec3::new(0.0, 1.0, 1.0).normalize();
+        assert!(close(ahead.reflect(slope), Vec3::new(0.0, 1.0, 0.0)));
+
+        // The lookup is in world space: turn the camera and the same view-space reflection
+        // points elsewhere in the world, wherever the view sends it back from.
+        let view = Mat4::rotation_y(std::f32::consts::FRAC_PI_2).m;
+        let turned = direction_to_world(&view, back);
+        assert!(close(turned, Vec3::new(-1.0, 0.0, 0.0)));
+        let in_view = Mat4 { m: view }.mul_point(turned);
+        assert!(close(in_view, back));
+
+        assert!(CubemapImage::new(&[0; 4 * 4 * 4 * 6], 4).is_ok());
+        assert!(CubemapImage::new(&[0; 4 * 4 * 4], 4).is_err());
+    }
+
+    #[test]
+    fn npot_textures_clamp_on_webgl1() {
+        let pot = texture_sampling(256, 64, true, false);
+        assert_eq!(pot.min_filter, WebGlRenderingContext::LINEAR_MIPMAP_LINEAR);
+        assert_eq!(pot.wrap, WebGlRenderingContext::REPEAT);
+        assert!(pot.mipmaps);
+
+        let npot = texture_sampling(300, 64, true, false);
+        assert_eq!(npot.min_filter, WebGlRenderingContext::LINEAR);
+        assert_eq!(npot.wrap, WebGlRenderingContext::CLAMP_TO_EDGE);
+        assert!(!npot.mipmaps);
+
+        // WebGL2 has no such limits; a matcap clamps either way.
+        assert_eq!(texture_sampling(300, 64, true, true), pot);
+        assert_eq!(
+            texture_sampling(256, 64, false, false).wrap,
+            WebGlRenderingContext::CLAMP_TO_EDGE
+        );
+    }
+
+    #[test]
+    fn shading_models_parse_by_name() {
+        assert_eq!(ShadingModel::from_str("toon"), Some(ShadingModel::Toon));
+        assert_eq!(ShadingModel::from_str("phong"), Some(ShadingModel::Phong));
+        assert_eq!(
+            ShadingModel::from_str("lambert"),
+            Some(ShadingModel::Lambert)
+        );
+        assert_eq!(ShadingModel::from_str("Toon"), None);
+        assert_eq!(ShadingModel::from_str("gouraud"), None);
+        assert_eq!(ShadingModel::default(), ShadingModel::Lambert);
+    }
+
+    #[test]
+    fn instance_count_is_one_per_matrix() {
+        assert_eq!(instance_count(0), Ok(0));
+        assert_eq!(instance_count(16), Ok(1));
+        assert_eq!(instance_count(16 * 1000), Ok(1000));
+        assert!(instance_count(17).is_err());
+        assert!(instance_count(15).is_err());
+    }
+
+    #[test]
+    fn opaque_transparency_matches_opaque_pass() {
+        let opaque = PassState::new(false, 0.3);
+        assert_eq!(PassState::new(true, 1.0), opaque);
+        assert_eq!(opaque.opacity, 1.0);
+        assert!(opaque.depth_write && !opaque.blend);
+
+        let glass = PassState::new(true, 0.4);
+        assert!(glass.blend && !glass.depth_write);
+        assert_eq!(glass.opacity, 0.4);
+    }
+
+    #[test]
+    fn stats_count_indexed_cube() {
+        let cube = crate::geometry::cube();
+        let mut stats = RenderStats::default();
+        stats.record(
+            WebGlRenderingContext::TRIANGLES,
+            cube.indices.len() as i32,
+            (cube.positions.len() / 3) as i32,
+        );
+        assert_eq!(stats.draw_calls, 1);
+        assert_eq!(stats.triangles, 12);
+        // Four vertices per face so each face keeps its own normal.
+        assert_eq!(stats.vertices, 24);
+
+        stats.record(WebGlRenderingContext::LINES, 6, 6);
+        assert_eq!(
+            (stats.draw_calls, stats.triangles, stats.vertices),
+            (2, 12, 30)
+        );
+    }
+
+    #[test]
+    fn gizmo_view_drops_translation() {
+        let view = Mat4::look_at(
+            Vec3::new(3.0, 2.0, 5.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let gizmo = Mat4 {
+            m: rotation_only(&view.m),
+        };
+        let origin = gizmo.mul_point(Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!([origin.x, origin.y, origin.z], [0.0, 0.0, 0.0]);
+        // Directions transform the same as with the full view matrix.
+        let dir = Vec3::new(0.0, 0.0, 1.0);
+        let expected = view
+            .mul_point(dir)
+            .sub(view.mul_point(Vec3::new(0.0, 0.0, 0.0)));
+        let got = gizmo.mul_point(dir);
+        assert!(got.sub(expected).length() <= 1e-5);
+    }
+
+    #[test]
+    fn light_direction_is_normalized() {
+        let l = light_direction_or_default(Vec3::new(0.0, -3.0, 4.0));
+        assert!((l.length() - 1.0).abs() <= 1e-6);
+        assert!((l.y + 0.6).abs() <= 1e-6 && (l.z - 0.8).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn lights_from_flat_clamps_count() {
+        let dirs = [0.0, 0.0, -2.0].repeat(6);
+        let colors = [1.0, 0.5, 0.25].repeat(6);
+        let lights = lights_from_flat(&dirs, &colors).unwrap();
+        assert_eq!(lights.len(), MAX_LIGHTS);
+        assert!((lights[0].dir.z + 1.0).abs() <= 1e-6);
+        assert_eq!(lights[3].color, [1.0, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn lights_from_flat_accepts_zero_and_rejects_mismatch() {
+        assert!(lights_from_flat(&[], &[]).unwrap().is_empty());
+        assert!(lights_from_flat(&[0.0, 0.0, -1.0], &[1.0, 1.0]).is_err());
+        assert!(lights_from_flat(&[0.0, -1.0], &[1.0, 1.0]).is_err());
+    }
+
+    /// GL calls that the draw tests care about; everything else is accepted silently.
+    #[derive(Clone, Debug, PartialEq)]
+    enum Call {
+        BufferData {
+            target: u32,
+            len: usize,
+            usage: u32,
+        },
+        DrawArrays {
+            mode: u32,
+            count: i32,
+        },
+        DrawElements {
+            mode: u32,
+            count: i32,
+            ty: u32,
+            offset: i32,
+        },
+        DrawElementsInstanced {
+            mode: u32,
+            count: i32,
+            instances: i32,
+        },
+        /// A buffer, program or shader was deleted.
+        Delete(u32),
+        /// A `vec3` uniform was set, by the name it was looked up with.
+        Uniform3f {
+            name: String,
+            value: Vec<f32>,
+        },
+    }
+
+    /// A WebGL1-like backend that hands out numbered objects, compiles and links
+    /// everything, and logs the calls in `Call`.
+    #[derive(Clone, Default)]
+    struct RecordingBackend {
+        calls: Rc<RefCell<Vec<Call>>>,
+        next_id: Rc<Cell<u32>>,
+        /// Uniform names by the location handed out for them.
+        uniform_names: Rc<RefCell<Vec<(u32, String)>>>,
+        /// Whether to expose instanced draws (as `ANGLE_instanced_arrays` would).
+        instancing: bool,
+    }
+
+    impl RecordingBackend {
+        fn id(&self) -> u32 {
+            self.next_id.set(self.next_id.get() + 1);
+            self.next_id.get()
+        }
+
+        fn log(&self, call: Call) {
+            self.calls.borrow_mut().push(call);
+        }
+
+        fn draws(&self) -> Vec<Call> {
+            let calls = self.calls.borrow();
+            calls
+                .iter()
+                .filter(|c| {
+                    !matches!(
+                        c,
+                        Call::BufferData { .. } | Call::Delete(_) | Call::Uniform3f { .. }
+                    )
+                })
+                .cloned()
+                .collect()
+        }
+    }
+
+    impl InstancedDraw for RecordingBackend {
+        fn vertex_attrib_divisor(&self, _index: u32, _divisor: u32) {}
+
+        fn draw_arrays_instanced(&self, mode: u32, _first: i32, count: i32, instances: i32) {
+            self.log(Call::DrawArrays {
+                mode,
+                count: count * instances,
+            });
+        }
+
+        fn draw_elements_instanced(&self, mode: u32, count: i32, _: u32, _: i32, instances: i32) {
+            self.log(Call::DrawElementsInstanced {
+                mode,
+                count,
+                instances,
+            });
+        }
+    }
+
+    impl GraphicsBackend for RecordingBackend {
+        type Buffer = u32;
+        type Program = u32;
+        type Shader = u32;
+        type Texture = u32;
+        type UniformLocation = u32;
+        type VertexArray = u32;
+        type Instancing = Self;
+
+        fn is_webgl2(&self) -> bool {
+            false
+        }
+        fn create_vertex_array(&self) -> Option<u32> {
+            None
+        }
+        fn bind_vertex_array(&self, _vao: Option<&u32>) {}
+        fn delete_vertex_array(&self, _vao: Option<&u32>) {}
+        fn instancing(&self) -> Option<Self> {
+            self.instancing.then(|| self.clone())
+        }
+        fn enable_extension(&self, _name: &str) -> bool {
+            true
+        }
+        fn fragment_highp_supported(&self) -> bool {
+            true
+        }
+        fn shader_compiled(&self, _shader: &u32) -> bool {
+            true
+        }
+        fn program_linked(&self, _program: &u32) -> bool {
+            true
+        }
+        fn buffer_data_f32(&self, target: u32, data: &[f32], usage: u32) {
+            self.log(Call::BufferData {
+                target,
+                len: data.len(),
+                usage,
+            });
+        }
+        fn buffer_data_u16(&self, target: u32, data: &[u16], usage: u32) {
+            self.log(Call::BufferData {
+                target,
+                len: data.len(),
+                usage,
+            });
+        }
+        fn buffer_data_u32(&self, target: u32, data: &[u32], usage: u32) {
+            self.log(Call::BufferData {
+                target,
+                len: data.len(),
+                usage,
+            });
+        }
+        fn tex_image_2d_rgba(&self, _: u32, _: i32, _: i32, _: &[u8]) -> Result<(), JsValue> {
+            Ok(())
+        }
+        fn read_pixels(&self, _: i32, _: i32, _: i32, _: i32, _: &mut [u8]) -> Result<(), JsValue> {
+            Ok(())
+        }
+        fn now_ms(&self) -> f64 {
+            0.0
+        }
+        fn active_texture(&self, _texture: u32) {}
+        fn attach_shader(&self, _program: &u32, _shader: &u32) {}
+        fn bind_buffer(&self, _target: u32, _buffer: Option<&u32>) {}
+        fn bind_texture(&self, _target: u32, _texture: Option<&u32>) {}
+        fn blend_func_separate(&self, _: u32, _: u32, _: u32, _: u32) {}
+        fn clear(&self, _mask: u32) {}
+        fn clear_color(&self, _r: f32, _g: f32, _b: f32, _a: f32) {}
+        fn compile_shader(&self, _shader: &u32) {}
+        fn create_buffer(&self) -> Option<u32> {
+            Some(self.id())
+        }
+        fn create_program(&self) -> Option<u32> {
+            Some(self.id())
+        }
+        fn create_shader(&self, _ty: u32) -> Option<u32> {
+            Some(self.id())
+        }
+        fn create_texture(&self) -> Option<u32> {
+            Some(self.id())
+        }
+        fn delete_buffer(&self, buffer: Option<&u32>) {
+            if let Some(&id) = buffer {
+                self.log(Call::Delete(id));
+            }
+        }
+        fn delete_program(&self, program: Option<&u32>) {
+            if let Some(&id) = program {
+                self.log(Call::Delete(id));
+            }
+        }
+        fn delete_shader(&self, shader: Option<&u32>) {
+            if let Some(&id) = shader {
+                self.log(Call::Delete(id));
+            }
+        }
+        fn delete_texture(&self, texture: Option<&u32>) {
+            if let Some(&id) = texture {
+                self.log(Call::Delete(id));
+            }
+        }
+        fn depth_mask(&self, _flag: bool) {}
+        fn disable(&self, _cap: u32) {}
+        fn disable_vertex_attrib_array(&self, _index: u32) {}
+        fn draw_arrays(&self, mode: u32, _first: i32, count: i32) {
+            self.log(Call::DrawArrays { mode, count });
+        }
+        fn draw_elements_with_i32(&self, mode: u32, count: i32, ty: u32, offset: i32) {
+            self.log(Call::DrawElements {
+                mode,
+                count,
+                ty,
+                offset,
+            });
+        }
+        fn enable(&self, _cap: u32) {}
+        fn enable_vertex_attrib_array(&self, _index: u32) {}
+        fn front_face(&self, _mode: u32) {}
+        fn generate_mipmap(&self, _target: u32) {}
+        fn get_attrib_location(&self, _program: &u32, _name: &str) -> i32 {
+            self.id() as i32
+        }
+        fn get_error(&self) -> u32 {
+            WebGlRenderingContext::NO_ERROR
+        }
+        fn get_program_info_log(&self, _program: &u32) -> Option<String> {
+            None
+        }
+        fn get_shader_info_log(&self, _shader: &u32) -> Option<String> {
+            None
+        }
+        fn get_uniform_location(&self, _program: &u32, name: &str) -> Option<u32> {
+            let id = self.id();
+            self.uniform_names.borrow_mut().push((id, name.to_string()));
+            Some(id)
+        }
+        fn is_context_lost(&self) -> bool {
+            false
+        }
+        fn link_program(&self, _program: &u32) {}
+        fn polygon_offset(&self, _factor: f32, _units: f32) {}
+        fn shader_source(&self, _shader: &u32, _src: &str) {}
+        fn stencil_func(&self, _func: u32, _ref: i32, _mask: u32) {}
+        fn stencil_mask(&self, _mask: u32) {}
+        fn stencil_op(&self, _fail: u32, _zfail: u32, _zpass: u32) {}
+        fn tex_parameteri(&self, _target: u32, _pname: u32, _param: i32) {}
+        fn uniform1f(&self, _location: Option<&u32>, _x: f32) {}
+        fn uniform1i(&self, _location: Option<&u32>, _x: i32) {}
+        fn uniform2fv_with_f32_array(&self, _location: Option<&u32>, _data: &[f32]) {}
+        fn uniform3fv_with_f32_array(&self, location: Option<&u32>, data: &[f32]) {
+            let names = self.uniform_names.borrow();
+            let name = names.iter().find(|(id, _)| Some(id) == location);
+            if let Some((_, name)) = name {
+                self.log(Call::Uniform3f {
+                    name: name.clone(),
+                    value: data.to_vec(),
+                });
+            }
+        }
+        fn uniform_matrix3fv_with_f32_array(&self, _: Option<&u32>, _: bool, _: &[f32]) {}
+        fn uniform_matrix4fv_with_f32_array(&self, _: Option<&u32>, _: bool, _: &[f32]) {}
+        fn use_program(&self, _program: Option<&u32>) {}
+        fn vertex_attrib_pointer_with_i32(&self, _: u32, _: i32, _: u32, _: bool, _: i32, _: i32) {}
+        fn viewport(&self, _x: i32, _y: i32, _width: i32, _height: i32) {}
+    }
+
+    const IDENTITY_3: [f32; 9] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+
+    fn draw_single(renderer: &Renderer<RecordingBackend>) {
+        let identity = Mat4::identity().m;
+        let placement = Placement::Single {
+            model: &identity,
+            normal_matrix: &IDENTITY_3,
+        };
+        renderer.draw(64, 64, &identity, &identity, placement);
+    }
+
+    #[test]
+    fn solid_cube_is_one_indexed_triangle_draw() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        let uploaded = gl.calls.borrow().clone();
+        assert!(uploaded.contains(&Call::BufferData {
+            target: WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
+            len: 36,
+            usage: WebGlRenderingContext::STATIC_DRAW,
+        }));
+
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawElements {
+                mode: WebGlRenderingContext::TRIANGLES,
+                count: 36,
+                ty: WebGlRenderingContext::UNSIGNED_SHORT,
+                offset: 0,
+            }]
+        );
+        assert_eq!(renderer.stats().triangles, 12);
+    }
+
+    #[test]
+    fn grid_meshes_draw_as_one_strip() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        let sphere = geometry::sphere(0.5, 8, 4);
+        renderer.set_mesh(&sphere).unwrap();
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawElements {
+                mode: WebGlRenderingContext::TRIANGLE_STRIP,
+                count: sphere.strip.len() as i32,
+                ty: WebGlRenderingContext::UNSIGNED_SHORT,
+                offset: 0,
+            }]
+        );
+        // Every quad is two triangles, except that pole quads keep one.
+        assert_eq!(renderer.stats().triangles, 8 * 4 * 2 - 2 * 8);
+
+        // Wireframe still reads the edges of the triangle list.
+        renderer.set_render_mode(RenderMode::Wireframe);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert!(matches!(
+            gl.draws()[..],
+            [Call::DrawElements {
+                mode: WebGlRenderingContext::LINES,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn wireframe_and_point_meshes_pick_their_primitives() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        let cube = geometry::cube();
+        renderer.set_mesh(&cube).unwrap();
+        renderer.set_render_mode(RenderMode::Wireframe);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        let IndexBuffer::U16(indices) = &cube.indices else {
+            panic!("cube has 16-bit indices");
+        };
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawElements {
+                mode: WebGlRenderingContext::LINES,
+                count: build_wireframe_indices(indices).len() as i32,
+                ty: WebGlRenderingContext::UNSIGNED_SHORT,
+                offset: 0,
+            }]
+        );
+
+        let points = Mesh {
+            topology: Topology::Points,
+            indices: IndexBuffer::U16(Vec::new()),
+            ..cube
+        };
+        renderer.set_mesh(&points).unwrap();
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawArrays {
+                mode: WebGlRenderingContext::POINTS,
+                count: 24,
+            }]
+        );
+    }
+
+    #[test]
+    fn instances_draw_in_one_call_or_fall_back_to_a_loop() {
+        let identity = Mat4::identity().m;
+        let models = identity.repeat(3);
+        for instancing in [true, false] {
+            let gl = RecordingBackend {
+                instancing,
+                ..RecordingBackend::default()
+            };
+            let mut renderer = Renderer::new(gl.clone()).unwrap();
+            renderer.set_mesh(&geometry::cube()).unwrap();
+            gl.calls.borrow_mut().clear();
+            let placement = Placement::Instances {
+                models: &models,
+                count: 3,
+            };
+            renderer.draw(64, 64, &identity, &identity, placement);
+
+            let expected = if instancing {
+                vec![Call::DrawElementsInstanced {
+                    mode: WebGlRenderingContext::TRIANGLES,
+                    count: 36,
+                    instances: 3,
+                }]
+            } else {
+                let single = Call::DrawElements {
+                    mode: WebGlRenderingContext::TRIANGLES,
+                    count: 36,
+                    ty: WebGlRenderingContext::UNSIGNED_SHORT,
+                    offset: 0,
+                };
+                vec![single; 3]
+            };
+            assert_eq!(gl.draws(), expected, "instancing: {instancing}");
+            assert_eq!(renderer.stats().triangles, 36);
+        }
+    }
+
+    #[test]
+    fn wireframe_overlay_draws_edges_after_the_fill() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        let cube = geometry::cube();
+        renderer.set_mesh(&cube).unwrap();
+        renderer.set_wireframe_overlay(true);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        let IndexBuffer::U16(indices) = &cube.indices else {
+            panic!("cube has 16-bit indices");
+        };
+        let elements = |mode, count| Call::DrawElements {
+            mode,
+            count,
+            ty: WebGlRenderingContext::UNSIGNED_SHORT,
+            offset: 0,
+        };
+        assert_eq!(
+            gl.draws(),
+            [
+                elements(WebGlRenderingContext::TRIANGLES, 36),
+                elements(
+                    WebGlRenderingContext::LINES,
+                    build_wireframe_indices(indices).len() as i32
+                ),
+            ]
+        );
+        // Wireframe mode alone is unaffected by the overlay flag.
+        renderer.set_render_mode(RenderMode::Wireframe);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(gl.draws().len(), 1);
+    }
+
+    #[test]
+    fn polylines_draw_as_strips_or_quads_by_width() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        let points = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        renderer.set_mesh(&geometry::polyline(&points)).unwrap();
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawArrays {
+                mode: WebGlRenderingContext::LINE_STRIP,
+                count: 4,
+            }]
+        );
+
+        renderer.set_line_width(4.0);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        // Three segments, two triangles each.
+        assert_eq!(
+            gl.draws(),
+            [Call::DrawArrays {
+                mode: WebGlRenderingContext::TRIANGLES,
+                count: 18,
+            }]
+        );
+        assert_eq!(renderer.stats().triangles, 6);
+    }
+
+    #[test]
+    fn release_deletes_each_object_once() {
+        let gl = RecordingBackend::default();
+        let deleted = || {
+            let calls = gl.calls.borrow();
+            let mut ids: Vec<u32> = calls
+                .iter()
+                .filter_map(|c| match c {
+                    Call::Delete(id) => Some(*id),
+                    _ => None,
+                })
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        assert!(deleted().is_empty());
+        // Replacing the mesh frees the old index buffers.
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        assert_eq!(deleted().len(), 2);
+
+        renderer.release();
+        let once = deleted();
+        let mut unique = once.clone();
+        unique.dedup();
+        assert_eq!(once, unique);
+        // The two replaced index buffers, 7 mesh buffers, the main program and its shaders,
+        // two buffers, a program and two shaders for each of the three line passes, the
+        // thick line pass with three buffers, and the flat program and its shaders.
+        assert_eq!(once.len(), 2 + 7 + 3 + 3 * 5 + 6 + 3);
+
+        renderer.release();
+        drop(renderer);
+        assert_eq!(deleted(), once);
+    }
+
+    #[test]
+    fn selection_adds_an_outline_pass() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        renderer.set_selected(true);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        let cube_draw = Call::DrawElements {
+            mode: WebGlRenderingContext::TRIANGLES,
+            count: 36,
+            ty: WebGlRenderingContext::UNSIGNED_SHORT,
+            offset: 0,
+        };
+        // The mesh, then the same triangles again for the rim.
+        assert_eq!(gl.draws(), [cube_draw.clone(), cube_draw.clone()]);
+        assert_eq!(renderer.stats().draw_calls, 2);
+
+        // Wireframe has no silhouette to outline.
+        renderer.set_render_mode(RenderMode::Wireframe);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(gl.draws().len(), 1);
+
+        renderer.set_render_mode(RenderMode::Solid);
+        renderer.set_selected(false);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(gl.draws(), [cube_draw]);
+    }
+
+    #[test]
+    fn hemisphere_light_colors_reach_the_shader() {
+        for name in ["u_sky_color", "u_ground_color", "u_up_vs"] {
+            assert!(FRAGMENT_SHADER_SRC.contains(&format!("uniform vec3 {name};")));
+        }
+        // A normal along up weighs the sky fully: mix(ground, sky, 0.5 * (1 + 1)).
+        assert!(FRAGMENT_SHADER_SRC
+            .contains("mix(u_ground_color, u_sky_color, 0.5 * (dot(n, u_up_vs) + 1.0))"));
+
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        renderer.set_hemisphere_light([0.6, 0.7, 1.0], [0.3, 0.2, 0.1]);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        let value = |uniform: &str| {
+            gl.calls.borrow().iter().find_map(|c| match c {
+                Call::Uniform3f { name, value } if name == uniform => Some(value.clone()),
+                _ => None,
+            })
+        };
+        assert_eq!(value("u_sky_color").unwrap(), [0.6, 0.7, 1.0]);
+        assert_eq!(value("u_ground_color").unwrap(), [0.3, 0.2, 0.1]);
+        // `draw_single` looks down -Z with Y up, so world up stays up in view space.
+        assert_eq!(value("u_up_vs").unwrap(), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn ground_shadow_draws_the_mesh_again_under_it() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        renderer.set_ground_shadow(Some(0.4));
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        // The default light shines downward, so the flattened cube is drawn first.
+        assert_eq!(gl.draws().len(), 2);
+        assert_eq!(renderer.stats().draw_calls, 2);
+
+        // A NaN alpha leaves the shadow as it was.
+        renderer.set_ground_shadow(Some(f32::NAN));
+        assert_eq!(renderer.ground_shadow, Some(0.4));
+
+        renderer.set_ground_shadow(None);
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(gl.draws().len(), 1);
+    }
+
+    #[test]
+    fn ground_shadow_lands_on_the_world_up_ground() {
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 2.0));
+        // Identity view, so the light's view and world directions agree.
+        let view = Mat4::identity().m;
+        let light = Vec3::new(0.5, 0.0, -1.0);
+        let z_up = Vec3::new(0.0, 0.0, 1.0);
+        let m = ground_shadow_matrix(&bounds, &Mat4::identity(), &view, light, z_up).unwrap();
+        let top = m.mul_point(Vec3::new(0.0, 0.0, 2.0));
+        assert!(top.z.abs() <= 1e-5);
+        assert!((top.x - 1.0).abs() <= 1e-5);
+
+        // The same light runs parallel to a Y-up ground and casts nothing.
+        let y_up = Vec3::new(0.0, 1.0, 0.0);
+        assert!(ground_shadow_matrix(&bounds, &Mat4::identity(), &view, light, y_up).is_none());
+    }
+
+    #[test]
+    fn set_precision_replaces_the_programs() {
+        let gl = RecordingBackend::default();
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        renderer
+            .set_precision(Some(FloatPrecision::Medium))
+            .unwrap();
+        let deleted = gl
+            .calls
+            .borrow()
+            .iter()
+            .filter(|c| matches!(c, Call::Delete(_)))
+            .count();
+        // Everything `release` frees for a renderer without a mesh.
+        assert_eq!(deleted, 5 + 3 + 3 * 5 + 6 + 3);
+        assert_eq!(renderer.precision_override, Some(FloatPrecision::Medium));
+
+        renderer.set_mesh(&geometry::cube()).unwrap();
+        gl.calls.borrow_mut().clear();
+        draw_single(&renderer);
+        assert_eq!(gl.draws().len(), 1);
+    }
+
+    #[test]
+    fn zero_light_direction_falls_back_to_default() {
+        let l = light_direction_or_default(Vec3::new(0.0, 0.0, 0.0));
+        let d = DEFAULT_LIGHT_DIR.normalize();
+        assert_eq!((l.x, l.y, l.z), (d.x, d.y, d.z));
+    }
+}
@@ -1,56 +1,324 @@
+use crate::gl::GraphicsBackend;
 use wasm_bindgen::prelude::*;
-use web_sys::{WebGlProgram, WebGlRenderingContext, WebGlShader};
 
 pub(crate) const VERTEX_SHADER_SRC: &str = r#"
 attribute vec3 position;
 attribute vec3 normal;
+attribute vec2 a_uv;
+attribute vec4 a_tangent; // xyz + handedness, for normal mapping
+attribute vec3 a_color;
 
 uniform mat4 u_model;
 uniform mat4 u_view;
 uniform mat4 u_proj;
+uniform mat3 u_normal_matrix; // Inverse-transpose of the model-view 3x3.
 
 varying vec3 v_normal_vs;
+varying vec3 v_pos_vs;
+varying vec2 v_uv;
+varying vec4 v_tangent_vs;
+varying vec3 v_color;
+varying float v_fog_depth; // Distance in front of the eye, for fog.
 
 void main() {
+    v_uv = a_uv;
+    v_color = a_color;
+    v_tangent_vs = vec4(u_normal_matrix * a_tangent.xyz, a_tangent.w);
     vec4 pos_vs = u_view * u_model * vec4(position, 1.0);
-    // Transform normal with the upper-left 3x3 of the model-view matrix.
-    v_normal_vs = mat3(u_view * u_model) * normal;
+    v_pos_vs = pos_vs.xyz;
+    v_fog_depth = -pos_vs.z;
+    // Inverse-transpose keeps normals perpendicular under non-uniform scale.
+    v_normal_vs = u_normal_matrix * normal;
     gl_Position = u_proj * pos_vs;
+    // Only read when drawing POINTS (point clouds).
+    gl_PointSize = 2.0;
 }
 "#;
 
+/// Vertex shader for instanced draws, paired with `FRAGMENT_SHADER_SRC`. The model matrix
+/// is a per-instance attribute (four consecutive locations, one column each).
+pub(crate) const INSTANCED_VERTEX_SHADER_SRC: &str = r#"
+attribute vec3 position;
+attribute vec3 normal;
+attribute vec2 a_uv;
+attribute vec3 a_color;
+attribute mat4 a_model;
+
+uniform mat4 u_view;
+uniform mat4 u_proj;
+
+varying vec3 v_normal_vs;
+varying vec3 v_pos_vs;
+varying vec2 v_uv;
+varying vec3 v_color;
+varying float v_fog_depth;
+
+void main() {
+    v_uv = a_uv;
+    v_color = a_color;
+    mat4 model_view = u_view * a_model;
+    vec4 pos_vs = model_view * vec4(position, 1.0);
+    v_pos_vs = pos_vs.xyz;
+    v_fog_depth = -pos_vs.z;
+    // GLSL ES 1.00 has no inverse(); the upper 3x3 is exact for rotations and uniform
+    // scale, which is what repeated primitives use.
+    v_normal_vs = mat3(model_view[0].xyz, model_view[1].xyz, model_view[2].xyz) * normal;
+    gl_Position = u_proj * pos_vs;
+    gl_PointSize = 2.0;
+}
+"#;
+
+/// Mesh fragment shader without its precision statement; assemble it with
+/// `fragment_source`.
 pub(crate) const FRAGMENT_SHADER_SRC: &str = r#"
-precision mediump float;
+// WebGL1 loops need a constant bound; keep in sync with renderer::MAX_LIGHTS.
+#define MAX_LIGHTS 4
+// Diffuse levels in toon shading.
+#define TOON_BANDS 3.0
 
 varying vec3 v_normal_vs;
+varying vec3 v_pos_vs;
+varying vec2 v_uv;
+varying vec3 v_color;
+varying float v_fog_depth;
+
+uniform vec3 u_light_dirs[MAX_LIGHTS]; // Direction each light travels, in view space.
+uniform vec3 u_light_colors[MAX_LIGHTS];
+uniform int u_light_count;
+uniform vec3 u_base_color;
+uniform vec3 u_ambient;
+// Hemisphere ambient: u_ambient is tinted from u_ground_color (facing down) to u_sky_color
+// (facing up), with up being the world's up axis in view space. Equal colors give flat
+// ambient.
+uniform vec3 u_sky_color;
+uniform vec3 u_ground_color;
+uniform vec3 u_up_vs;
+uniform vec3 u_specular_color;
+uniform float u_shininess;
+uniform float u_opacity;
+uniform bool u_use_vertex_color; // Tint the base color by the interpolated `a_color`.
+uniform sampler2D u_base_texture;
+uniform bool u_use_texture; // Tint the base color by the texel at `v_uv`.
+// Display gamma. Lighting is linear but the framebuffer is shown as sRGB, so 2.2
+// brightens midtones to look right; 1.0 writes linear values as before.
+uniform float u_gamma;
+uniform int u_shading_model; // renderer::ShadingModel: 0 Lambert, 1 Phong, 2 Toon.
+// Linear fog from u_fog_near to u_fog_far (view-space depth); off when far <= near.
+uniform vec3 u_fog_color;
+uniform float u_fog_near;
+uniform float u_fog_far;
+// Matcap: the color comes from this image, looked up by the view-space normal's xy
+// (mapped onto [0,1], up-facing normals at the top row), instead of from the lights.
+uniform sampler2D u_matcap;
+uniform bool u_use_matcap;
+// Environment reflection: the view vector mirrored about the normal, turned into world
+// space, looks up a cube map that is blended over the shaded color.
+uniform samplerCube u_environment;
+uniform bool u_use_environment;
+uniform float u_reflectivity;
+uniform mat3 u_view_to_world; // Rotation part of the inverse view matrix.
+
+void main() {
+    // Everything in view space (the eye sits at the origin).
+    vec3 n = normalize(v_normal_vs);
+    vec3 v = normalize(-v_pos_vs);
+
+    vec3 diffuse = vec3(0.0);
+    vec3 specular = vec3(0.0);
+    for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= u_light_count) {
+            break;
+        }
+        vec3 l = -normalize(u_light_dirs[i]);
+        float ndl = max(dot(n, l), 0.0);
+        // Phong compares the mirrored light with the view; the others use the
+        // Blinn half vector.
+        float lobe = u_shading_model == 1
+            ? dot(reflect(-l, n), v)
+            : dot(n, normalize(l + v));
+        float spec = ndl > 0.0 ? pow(max(lobe, 0.0), u_shininess) : 0.0;
+        if (u_shading_model == 2) {
+            ndl = ceil(ndl * TOON_BANDS) / TOON_BANDS;
+            spec = step(0.5, spec);
+        }
+        diffuse += u_light_colors[i] * (0.85 * ndl);
+        specular += u_light_colors[i] * spec;
+    }
+
+    // With every light switched off, keep a little ambient so the object stays visible.
+    vec3 ambient = u_light_count > 0 ? u_ambient : max(u_ambient, vec3(0.2));
+    ambient *= mix(u_ground_color, u_sky_color, 0.5 * (dot(n, u_up_vs) + 1.0));
+
+    vec3 albedo = u_use_vertex_color ? u_base_color * v_color : u_base_color;
+    if (u_use_texture) {
+        albedo *= texture2D(u_base_texture, v_uv).rgb;
+    }
+    vec3 color;
+    if (u_use_matcap) {
+        // Images are stored top row first, so up-facing normals read from the top.
+        // Matcaps are display-ready like any picture, so they skip the gamma.
+        color = texture2D(u_matcap, vec2(0.5 + 0.5 * n.x, 0.5 - 0.5 * n.y)).rgb;
+    } else {
+        color = albedo * (ambient + diffuse) + u_specular_color * specular;
+        // pow() is undefined for negative bases; lighting never goes below zero anyway.
+        color = pow(max(color, vec3(0.0)), vec3(1.0 / u_gamma));
+    }
+    if (u_use_environment) {
+        vec3 r = u_view_to_world * reflect(-v, n);
+        color = mix(color, textureCube(u_environment, r).rgb, u_reflectivity);
+    }
+    // After gamma, so the fog color is written as given (like the clear color).
+    if (u_fog_far > u_fog_near) {
+        float fog = clamp((v_fog_depth - u_fog_near) / (u_fog_far - u_fog_near), 0.0, 1.0);
+        color = mix(color, u_fog_color, fog);
+    }
+    gl_FragColor = vec4(color, u_opacity);
+}
+"#;
+
+/// Unlit lines for overlays: one uniform color (grid) or per-vertex colors (axes).
+pub(crate) const LINE_VERTEX_SHADER_SRC: &str = r#"
+attribute vec3 position;
+attribute vec3 a_color;
+
+uniform mat4 u_view;
+uniform mat4 u_proj;
+
+varying vec3 v_color;
+
+void main() {
+    v_color = a_color;
+    gl_Position = u_proj * u_view * vec4(position, 1.0);
+}
+"#;
+
+/// Lines as screen-facing quads (`geometry::line_quads`): each corner is pushed
+/// `u_width / 2` pixels to one side of its segment. Pairs with the line fragment shader.
+pub(crate) const THICK_LINE_VERTEX_SHADER_SRC: &str = r#"
+attribute vec3 position;
+attribute vec3 a_other;
+attribute float a_side;
+
+uniform mat4 u_view;
+uniform mat4 u_proj;
+uniform vec2 u_viewport; // Pixels.
+uniform float u_width;   // Pixels.
 
-uniform vec3 u_light_dir_vs; // Direction the light travels, in view space.
+varying vec3 v_color;
 
 void main() {
-    vec3 n = -normalize(v_normal_vs);
-    float ndl = max(dot(n, -normalize(u_light_dir_vs)), 0.0);
-    vec3 base = vec3(0.8, 0.85, 0.95);
-    vec3 color = base * (0.15 + 0.85 * ndl);
-    gl_FragColor = vec4(color, 1.0);
+    v_color = vec3(0.0);
+    vec4 clip = u_proj * u_view * vec4(position, 1.0);
+    vec4 other = u_proj * u_view * vec4(a_other, 1.0);
+    vec2 delta = (other.xy / other.w - clip.xy / clip.w) * u_viewport;
+    vec2 dir = length(delta) > 1e-6 ? normalize(delta) : vec2(1.0, 0.0);
+    // Half the width in pixels is width / viewport in NDC; scale by w to stay in clip space.
+    clip.xy += vec2(-dir.y, dir.x) * a_side * u_width / u_viewport * clip.w;
+    gl_Position = clip;
 }
 "#;
 
-pub(crate) fn compile_shader(
-    gl: &WebGlRenderingContext,
+pub(crate) const LINE_FRAGMENT_SHADER_SRC: &str = r#"
+precision mediump float;
+
+varying vec3 v_color;
+
+uniform vec3 u_color;
+uniform bool u_use_vertex_color;
+
+void main() {
+    gl_FragColor = vec4(u_use_vertex_color ? v_color : u_color, 1.0);
+}
+"#;
+
+/// Unlit mesh in one color, for the selection outline and the ground shadow. Each vertex
+/// can be pushed `u_grow` pixels outward along its screen-space normal; the outline draws
+/// that grown mesh where the stencil doesn't mark the mesh, so only a rim shows.
+pub(crate) const FLAT_VERTEX_SHADER_SRC: &str = r#"
+attribute vec3 position;
+attribute vec3 normal;
+
+uniform mat4 u_model;
+uniform mat4 u_view;
+uniform mat4 u_proj;
+uniform mat3 u_normal_matrix; // Inverse-transpose of the model-view 3x3.
+uniform vec2 u_viewport; // Pixels.
+uniform float u_grow;    // Pixels.
+
+void main() {
+    vec4 clip = u_proj * u_view * u_model * vec4(position, 1.0);
+    vec2 dir = (u_proj * vec4(u_normal_matrix * normal, 0.0)).xy * u_viewport;
+    if (u_grow > 0.0 && length(dir) > 1e-6) {
+        // One pixel is 2 / viewport in NDC; scale by w to stay in clip space.
+        clip.xy += normalize(dir) * 2.0 * u_grow / u_viewport * clip.w;
+    }
+    gl_Position = clip;
+}
+"#;
+
+pub(crate) const FLAT_FRAGMENT_SHADER_SRC: &str = r#"
+precision mediump float;
+
+uniform vec3 u_color;
+uniform float u_alpha;
+
+void main() {
+    gl_FragColor = vec4(u_color, u_alpha);
+}
+"#;
+
+/// Default float precision of a fragment shader. Vertex shaders are always `highp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FloatPrecision {
+    High,
+    Medium,
+}
+
+impl FloatPrecision {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "highp" => Some(Self::High),
+            "mediump" => Some(Self::Medium),
+            _ => None,
+        }
+    }
+
+    /// `highp` when the fragment stage implements it. Desktop GPUs nearly always do and
+    /// some mobile GPUs don't (the spec makes it optional in WebGL1 fragment shaders);
+    /// `mediump` may be as low as 16-bit there, which shows as banding in gradients.
+    pub(crate) fn best_supported<B: GraphicsBackend>(gl: &B) -> Self {
+        if gl.fragment_highp_supported() {
+            Self::High
+        } else {
+            Self::Medium
+        }
+    }
+
+    fn statement(self) -> &'static str {
+        match self {
+            Self::High => "precision highp float;\n",
+            Self::Medium => "precision mediump float;\n",
+        }
+    }
+}
+
+/// `src` with a default float `precision` statement in front.
+pub(crate) fn fragment_source(src: &str, precision: FloatPrecision) -> String {
+    format!("{}{src}", precision.statement())
+}
+
+pub(crate) fn compile_shader<B: GraphicsBackend>(
+    gl: &B,
     ty: u32,
     src: &str,
-) -> Result<WebGlShader, JsValue> {
+) -> Result<B::Shader, JsValue> {
     let shader = gl
         .create_shader(ty)
         .ok_or_else(|| js_error("failed to create shader"))?;
     gl.shader_source(&shader, src);
     gl.compile_shader(&shader);
 
-    if gl
-        .get_shader_parameter(&shader, WebGlRenderingContext::COMPILE_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
+    if gl.shader_compiled(&shader) {
         Ok(shader)
     } else {
         Err(js_error(&gl.get_shader_info_log(&shader).unwrap_or_else(
@@ -59,11 +327,11 @@ pub(crate) fn compile_shader(
     }
 }
 
-pub(crate) fn link_program(
-    gl: &WebGlRenderingContext,
-    vs: &WebGlShader,
-    fs: &WebGlShader,
-) -> Result<WebGlProgram, JsValue> {
+pub(crate) fn link_program<B: GraphicsBackend>(
+    gl: &B,
+    vs: &B::Shader,
+    fs: &B::Shader,
+) -> Result<B::Program, JsValue> {
     let program = gl
         .create_program()
         .ok_or_else(|| js_error("failed to create program"))?;
@@ -71,11 +339,7 @@ pub(crate) fn link_program(
     gl.attach_shader(&program, fs);
     gl.link_program(&program);
 
-    if gl
-        .get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
+    if gl.program_linked(&program) {
         Ok(program)
     } else {
         Err(js_error(&gl.get_program_info_log(&program).unwrap_or_else(
@@ -87,3 +351,18 @@ pub(crate) fn link_program(
 fn js_error(msg: &str) -> JsValue {
     JsValue::from_str(msg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragment_source_starts_with_the_precision() {
+        let high = fragment_source(FRAGMENT_SHADER_SRC, FloatPrecision::High);
+        assert!(high.starts_with("precision highp float;\n"));
+        assert!(high.ends_with(FRAGMENT_SHADER_SRC));
+        let medium = FloatPrecision::from_str("mediump").unwrap();
+        assert!(fragment_source("", medium).starts_with("precision mediump float;"));
+        assert_eq!(FloatPrecision::from_str("lowp"), None);
+    }
+}
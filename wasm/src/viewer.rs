@@ -1,16 +1,27 @@
+use std::cell::Cell;
+
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 
-use crate::camera::{Bounds, Camera};
-use crate::geometry::{make_primitive, Primitive};
-use crate::math::{Mat4, Vec3};
-use crate::renderer::{RenderMode, Renderer};
-
-#[derive(Clone, Copy, Debug)]
-enum ViewMode {
-    Perspective,
-    Orthographic,
-}
+use crate::camera::{
+    wheel_zoom_factor, Bounds, Camera, CameraState, OrbitMode, ProjectionMode, RotationMode,
+    ViewPreset, DEFAULT_FIT_PADDING, WHEEL_ZOOM_SENSITIVITY,
+};
+use crate::geometry::{
+    bounding_sphere, grid_lines, make_primitive, make_primitive_detailed,
+    make_primitive_with_detail, mesh_from_raw, normal_lines, optimize_vertex_cache, parse_glb,
+    parse_obj, parse_ply, parse_stl_binary, polyline, simplify, subdivide_loop, weld_vertices,
+    IndexBuffer, Mesh, MeshStats, NormalMode, Primitive, UvProjection,
+};
+use crate::gl::Gl;
+use crate::math::{Mat4, Quat, Vec3};
+use crate::pick::Ray;
+use crate::png;
+use crate::renderer::{
+    instance_count, lights_from_flat, CubemapImage, Placement, RenderMode, Renderer, ShadingModel,
+    TextureImage,
+};
+use crate::shader::FloatPrecision;
 
 #[wasm_bindgen]
 pub struct Viewer {
@@ -18,16 +29,67 @@ pub struct Viewer {
     camera: Camera,
     width: i32,
     height: i32,
+    /// Use `width / height` as the aspect ratio; otherwise `aspect_override`.
+    aspect_auto: bool,
+    aspect_override: f32,
+    /// Drawing-buffer pixels per `width`/`height` unit; see `set_dpr`.
+    dpr: f32,
     bounds: Bounds,
-    view_mode: ViewMode,
+    /// Sizes of the mesh as last uploaded.
+    mesh_stats: MeshStats,
     orthographic_size: f32,
+    /// Current geometry as loaded (smooth normals); shading is applied on upload.
+    mesh: Mesh,
+    normal_mode: NormalMode,
+    /// Auto-rotate speed around +Y, radians per second (0 = off).
+    auto_rotate_speed: f32,
+    /// Model-space yaw applied in `draw`.
+    model_yaw: f32,
+    /// Object placement set from JS; see `model_transform`.
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    /// Fit to the mesh's bounding sphere instead of the AABB's circumscribed sphere.
+    tight_fit: bool,
+    /// Weld distance applied to `set_mesh_data` input (0 = keep vertices as given).
+    weld_epsilon: f32,
+    /// Reorder `set_mesh_data` triangles for the GPU's vertex cache.
+    optimize_vertex_cache: bool,
+    /// Make imported triangles agree on their winding; see `Mesh::fix_winding`.
+    fix_winding: bool,
+    /// Texture coordinates generated for meshes that come without any.
+    uv_projection: Option<UvProjection>,
+    /// The current mesh as imported, before `uv_projection` gave it UVs, so the projection
+    /// can be switched; `None` while the mesh's UVs are its own.
+    uv_source: Option<Mesh>,
+    /// Draw a ground grid sized to the mesh.
+    show_grid: bool,
+    /// What `arcball` drags do.
+    rotation_mode: RotationMode,
+    /// Length of the normal lines while they are shown.
+    normal_length: Option<f32>,
+    /// Set by anything that changes the picture; cleared by drawing.
+    needs_redraw: RedrawFlag,
 }
 
 #[wasm_bindgen]
 impl Viewer {
+    /// Create a viewer with an antialiased, alpha-enabled context.
     #[wasm_bindgen(constructor)]
     pub fn new(canvas: HtmlCanvasElement) -> Result<Viewer, JsValue> {
-        let gl = crate::get_webgl_context(&canvas)?;
+        Self::with_options(canvas, true, true)
+    }
+
+    /// Create a viewer with explicit `antialias` and `alpha` context attributes.
+    /// Multisampling is a request, not a guarantee: WebGL1 MSAA depends on browser support.
+    /// The attributes are fixed once a canvas has a context, so use a fresh canvas.
+    pub fn with_options(
+        canvas: HtmlCanvasElement,
+        antialias: bool,
+        alpha: bool,
+    ) -> Result<Viewer, JsValue> {
+        crate::start_diagnostics();
+        let gl = Gl::from_canvas(&canvas, antialias, alpha)?;
         let width = canvas.width() as i32;
         let height = canvas.height() as i32;
 
@@ -35,7 +97,7 @@ impl Viewer {
         let camera = Camera::new();
 
         let mesh = make_primitive(Primitive::Triangle);
-        renderer.set_mesh(&mesh);
+        renderer.set_mesh(&mesh)?;
         let bounds = mesh.bounds;
 
         let mut viewer = Viewer {
@@ -43,9 +105,29 @@ impl Viewer {
             camera,
             width,
             height,
+            aspect_auto: true,
+            aspect_override: 1.0,
+            dpr: 1.0,
             bounds,
-            view_mode: ViewMode::Perspective,
+            mesh_stats: mesh.stats(),
             orthographic_size: 2.0,
+            mesh,
+            normal_mode: NormalMode::Smooth,
+            auto_rotate_speed: 0.0,
+            model_yaw: 0.0,
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Quat::identity(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            tight_fit: false,
+            weld_epsilon: 0.0,
+            optimize_vertex_cache: false,
+            fix_winding: false,
+            uv_projection: None,
+            uv_source: None,
+            show_grid: false,
+            rotation_mode: RotationMode::Orbit,
+            normal_length: None,
+            needs_redraw: RedrawFlag::default(),
         };
         viewer.fit_to_view();
         viewer.draw();
@@ -53,28 +135,520 @@ impl Viewer {
     }
 
     pub fn resize(&mut self, width: i32, height: i32) {
+        self.needs_redraw.mark();
         self.width = width.max(1);
         self.height = height.max(1);
     }
 
-    pub fn set_bounds(&mut self, min_x: f32, min_y: f32, min_z: f32, max_x: f32, max_y: f32, max_z: f32) {
-        self.bounds = Bounds::new(Vec3::new(min_x, min_y, min_z), Vec3::new(max_x, max_y, max_z));
+    /// Render at `dpr` drawing-buffer pixels per `resize` unit, for sharp output on
+    /// high-DPI screens: pass `window.devicePixelRatio`, give `resize` the canvas's CSS
+    /// size, and size the backing store (`canvas.width`/`height`) to the CSS size times
+    /// `dpr`. Pointer and `project`/`unproject` coordinates stay in CSS pixels. Values
+    /// that are not positive are ignored.
+    pub fn set_dpr(&mut self, dpr: f32) {
+        self.needs_redraw.mark();
+        if dpr > 0.0 && dpr.is_finite() {
+            self.dpr = dpr;
+        }
+    }
+
+    pub fn set_bounds(
+        &mut self,
+        min_x: f32,
+        min_y: f32,
+        min_z: f32,
+        max_x: f32,
+        max_y: f32,
+        max_z: f32,
+    ) {
+        self.needs_redraw.mark();
+        self.bounds = Bounds::new(
+            Vec3::new(min_x, min_y, min_z),
+            Vec3::new(max_x, max_y, max_z),
+        );
     }
 
     /// Switch the rendered primitive.
-    /// Allowed: "triangle", "cube", "cylinder", "sphere", "torus".
-    pub fn set_primitive(&mut self, name: &str) {
-        if let Some(p) = Primitive::from_str(name) {
-            let mesh = make_primitive(p);
-            self.renderer.set_mesh(&mesh);
-            self.bounds = mesh.bounds;
-            self.fit_to_view();
+    /// Allowed: "triangle", "cube", "cylinder", "tube" (cylinder without caps), "sphere",
+    /// "torus", "plane", "cone", "capsule", "icosphere", "klein", "mobius".
+    pub fn set_primitive(&mut self, name: &str) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        match Primitive::from_str(name) {
+            Some(p) => self.show_mesh(make_primitive(p)),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `set_primitive`, with segment counts to trade quality for speed: for
+    /// "cylinder" and "tube", around the axis (`segments_minor` is unused); for "sphere",
+    /// longitude and latitude; for "torus", around the hole and around the tube. Counts
+    /// are clamped to at least 3 (2 for sphere latitude) and at most 255. Other primitives
+    /// treat `segments_major` like `set_primitive_ex`'s `detail`.
+    pub fn set_primitive_detailed(
+        &mut self,
+        name: &str,
+        segments_major: u32,
+        segments_minor: u32,
+    ) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        match Primitive::from_str(name) {
+            Some(p) => self.show_mesh(make_primitive_detailed(p, segments_major, segments_minor)),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `set_primitive`, with a tessellation level: for "cube", each face becomes a
+    /// `detail` x `detail` grid (clamped to 1..=100). Other primitives ignore `detail`.
+    pub fn set_primitive_ex(&mut self, name: &str, detail: u32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        match Primitive::from_str(name) {
+            Some(p) => self.show_mesh(make_primitive_with_detail(p, detail)),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the displayed mesh with caller-provided geometry.
+    /// `positions` is flat xyz; pass an empty `indices` for non-indexed triangles.
+    /// Meshes above 65,536 vertices need the `OES_element_index_uint` extension.
+    pub fn set_mesh_data(&mut self, positions: &[f32], indices: &[u32]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mut mesh = mesh_from_raw(positions, indices).map_err(|e| JsValue::from_str(&e))?;
+        if self.weld_epsilon > 0.0 || self.optimize_vertex_cache {
+            let (positions, mut indices) = if self.weld_epsilon > 0.0 {
+                weld_vertices(positions, indices, self.weld_epsilon)
+            } else {
+                (positions.to_vec(), indices.to_vec())
+            };
+            if self.optimize_vertex_cache {
+                optimize_vertex_cache(&mut indices, positions.len() / 3);
+            }
+            mesh = mesh_from_raw(&positions, &indices).map_err(|e| JsValue::from_str(&e))?;
+        }
+        self.replace_mesh(mesh)
+    }
+
+    /// Like `set_mesh_data`, with one RGB color (components in [0,1]) per vertex that
+    /// tints the base color. Welding is skipped since it would merge differently colored
+    /// vertices.
+    pub fn set_mesh_data_colored(
+        &mut self,
+        positions: &[f32],
+        indices: &[u32],
+        colors: &[f32],
+    ) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mut mesh = mesh_from_raw(positions, indices).map_err(|e| JsValue::from_str(&e))?;
+        mesh.set_colors(colors.to_vec())
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.replace_mesh(mesh)
+    }
+
+    /// Replace the displayed mesh with a polyline through `points` (flat xyz, at least two
+    /// points), drawn in the base color.
+    pub fn set_polyline(&mut self, points: &[f32]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        if !points.len().is_multiple_of(3) || points.len() < 6 {
+            return Err(JsValue::from_str(&format!(
+                "polyline needs at least two xyz points, got {} values",
+                points.len()
+            )));
+        }
+        self.replace_mesh(polyline(points))
+    }
+
+    /// Draw line meshes (polylines, line lists) `width` pixels wide, 1 for native lines.
+    /// Wider lines are built from quads since browsers cap `lineWidth` at 1.
+    pub fn set_line_width(&mut self, width: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_line_width(width);
+    }
+
+    /// Replace the displayed mesh with a Wavefront OBJ model (text contents of the file).
+    pub fn load_obj(&mut self, text: &str) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mesh = parse_obj(text).map_err(|e| JsValue::from_str(&e))?;
+        self.replace_mesh(mesh)
+    }
+
+    /// Merge `set_mesh_data`/`load_stl` vertices closer than `epsilon` (0 disables welding).
+    /// Triangle soups such as STL exports need this for smooth normals.
+    pub fn set_weld_epsilon(&mut self, epsilon: f32) {
+        self.weld_epsilon = if epsilon.is_finite() {
+            epsilon.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    /// Reorder `set_mesh_data` triangles so the GPU reuses more transformed vertices
+    /// (off by default). Worth it for large scans and exports; it costs a pass over the
+    /// mesh on load and changes nothing on screen.
+    pub fn set_optimize_vertex_cache(&mut self, on: bool) {
+        self.optimize_vertex_cache = on;
+    }
+
+    /// Make the triangles of meshes loaded from now on agree on their winding, flipping the
+    /// ones that disagree with most of their surface and turning inside-out meshes over
+    /// (off by default). Fixes the patchy lighting and culling of mixed-winding exports.
+    /// Triangle soups need `set_weld_epsilon` first so the triangles share edges.
+    pub fn set_fix_winding(&mut self, on: bool) {
+        self.fix_winding = on;
+    }
+
+    /// Generate texture coordinates for the current mesh and meshes loaded later, when they
+    /// come without any, so they can take a texture. Meshes with UVs keep theirs; generated
+    /// ones follow later calls, until `subdivide`, `simplify` or `flip_normals` edits the
+    /// mesh and they become its own.
+    /// Allowed: "box" (six sides, picked per triangle), "planar" (along the thinnest side of
+    /// the bounds), "none" (default; stop generating and drop generated UVs). Unknown names
+    /// are ignored.
+    pub fn generate_uvs(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.uv_projection = match mode {
+            "none" => None,
+            name => match UvProjection::from_str(name) {
+                Some(projection) => Some(projection),
+                None => return Ok(()),
+            },
+        };
+        let source = match self.uv_source.take() {
+            Some(source) => source,
+            None if !self.mesh.has_uvs() => self.mesh.clone(),
+            None => return Ok(()),
+        };
+        let mesh = self.with_generated_uvs(source.clone());
+        if matches!(mesh.indices, IndexBuffer::U32(_)) && !self.renderer.supports_u32_indices() {
+            self.uv_source = Some(source);
+            return Err(JsValue::from_str(
+                "mesh with UVs needs 32-bit indices but OES_element_index_uint is unavailable",
+            ));
+        }
+        self.mesh = mesh;
+        self.uv_source = Some(source);
+        self.upload_mesh()
+    }
+
+    /// Smooth the current mesh with `iterations` rounds of Loop subdivision (max 5).
+    /// Each round multiplies the triangle count by four.
+    pub fn subdivide(&mut self, iterations: u32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mesh = subdivide_loop(&self.mesh, iterations);
+        if matches!(mesh.indices, IndexBuffer::U32(_)) && !self.renderer.supports_u32_indices() {
+            return Err(JsValue::from_str(
+                "subdivided mesh needs 32-bit indices but OES_element_index_uint is unavailable",
+            ));
         }
+        self.mesh = mesh;
+        self.uv_source = None;
+        self.upload_mesh()
+    }
+
+    /// Reduce the current mesh to about `target_ratio` (0..1) of its triangles with quadric
+    /// error edge collapse. Imports split at every face should be welded first.
+    pub fn simplify(&mut self, target_ratio: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.mesh = simplify(&self.mesh, target_ratio);
+        self.uv_source = None;
+        self.upload_mesh()
+    }
+
+    /// Reverse the winding of the current mesh and rebuild its normals.
+    /// Fixes imports that render dark because their triangles face inward.
+    pub fn flip_normals(&mut self) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.mesh.flip_winding();
+        self.mesh.recompute_normals();
+        self.uv_source = None;
+        self.upload_mesh()
+    }
+
+    /// Replace the displayed mesh with the first primitive of a binary glTF (`.glb`) file.
+    pub fn load_glb(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mesh = parse_glb(bytes).map_err(|e| JsValue::from_str(&e))?;
+        self.replace_mesh(mesh)
+    }
+
+    /// Replace the displayed mesh with an ASCII PLY file.
+    /// Files without faces are shown as a point cloud.
+    pub fn load_ply(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mesh = parse_ply(bytes).map_err(|e| JsValue::from_str(&e))?;
+        self.replace_mesh(mesh)
+    }
+
+    /// Replace the displayed mesh with a binary STL file.
+    /// STL stores every triangle separately; set a weld epsilon to merge shared corners.
+    pub fn load_stl(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let mut mesh = parse_stl_binary(bytes).map_err(|e| JsValue::from_str(&e))?;
+        if self.weld_epsilon > 0.0 {
+            let (positions, indices) = weld_vertices(&mesh.positions, &[], self.weld_epsilon);
+            mesh = mesh_from_raw(&positions, &indices).map_err(|e| JsValue::from_str(&e))?;
+        }
+        self.replace_mesh(mesh)
+    }
+
+    /// Set how normals are generated for the current mesh.
+    /// Allowed: "smooth", "flat".
+    pub fn set_shading(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        if let Some(mode) = NormalMode::from_str(mode) {
+            self.normal_mode = mode;
+            self.upload_mesh()?;
+        }
+        Ok(())
+    }
+
+    /// Shade the current mesh smoothly across edges whose faces meet at less than
+    /// `degrees` and keep sharper edges hard, so boxes stay crisp while curved areas stay
+    /// round. Replaces "smooth" or "flat" shading until `set_shading` is called again.
+    pub fn set_crease_angle(&mut self, degrees: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        if degrees.is_finite() {
+            self.normal_mode = NormalMode::Crease(degrees.clamp(0.0, 180.0).to_radians());
+            self.upload_mesh()?;
+        }
+        Ok(())
+    }
+
+    /// Set the background (clear) color, components in [0,1].
+    /// Keeps the current alpha so a translucent canvas stays translucent.
+    pub fn set_background(&mut self, r: f32, g: f32, b: f32) {
+        self.needs_redraw.mark();
+        let a = self.renderer.clear_color()[3];
+        self.renderer.set_clear_color(r, g, b, a);
+    }
+
+    /// Set the background opacity. Only visible when the context was created with `alpha: true`.
+    pub fn set_background_alpha(&mut self, a: f32) {
+        self.needs_redraw.mark();
+        let [r, g, b, _] = self.renderer.clear_color();
+        self.renderer.set_clear_color(r, g, b, a);
+    }
+
+    /// Show a reference grid through the origin on the ground plane, square to
+    /// `set_world_up` (XZ with Y up, XY with Z up), sized to the current mesh.
+    pub fn set_grid(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.show_grid = on;
+        self.upload_grid();
+    }
+
+    /// Draw a line from every vertex along its normal, `length` long in model units
+    /// (non-positive picks 5% of the mesh size). Shows the normals the current shading
+    /// mode actually uploads, so flat and smooth shading look different here too.
+    pub fn set_show_normals(&mut self, on: bool, length: f32) {
+        self.needs_redraw.mark();
+        self.normal_length = on.then(|| {
+            if length.is_finite() && length > 0.0 {
+                length
+            } else {
+                self.bounds.radius().max(1e-3) * 0.05
+            }
+        });
+        let mesh = self.mesh.with_normal_mode(self.normal_mode);
+        self.upload_normal_lines(&mesh);
+    }
+
+    /// Show an XYZ axes gizmo (red, green, blue) in the bottom-left corner that turns
+    /// with the camera.
+    pub fn set_axes(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.renderer.set_axes_visible(on);
+    }
+
+    /// Grid line color, components in [0,1].
+    pub fn set_grid_color(&mut self, r: f32, g: f32, b: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_grid_color(r, g, b);
+    }
+
+    /// Set the direction the light travels, in view space (normalized internally).
+    /// A zero vector restores the default light.
+    pub fn set_light_direction(&mut self, x: f32, y: f32, z: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_light_direction(Vec3::new(x, y, z));
+    }
+
+    /// Replace all lights. `dirs` is flattened `[x,y,z, ...]` (view-space travel
+    /// directions) and `colors` flattened `[r,g,b, ...]`, one triple per light.
+    /// At most 4 lights are used; passing none leaves a dim ambient fill.
+    pub fn set_lights(&mut self, dirs: &[f32], colors: &[f32]) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let lights = lights_from_flat(dirs, colors).map_err(|e| JsValue::from_str(&e))?;
+        self.renderer.set_lights(lights);
+        Ok(())
+    }
+
+    /// Current light direction as `[x, y, z]`.
+    pub fn light_direction(&self) -> Vec<f32> {
+        let l = self.renderer.light_direction();
+        vec![l.x, l.y, l.z]
+    }
+
+    /// Set the object color, components clamped to [0,1].
+    /// Values are displayed as given (no gamma conversion), i.e. like CSS/sRGB colors.
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_base_color(r, g, b);
+    }
+
+    /// Set the Blinn-Phong material: `ambient` and `specular` are `[r, g, b]` in [0,1],
+    /// `shininess` is the specular exponent (e.g. 8 = broad, 128 = tight highlight).
+    pub fn set_material(
+        &mut self,
+        ambient: &[f32],
+        specular: &[f32],
+        shininess: f32,
+    ) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let ambient = rgb_from_slice(ambient, "ambient")?;
+        let specular = rgb_from_slice(specular, "specular")?;
+        self.renderer.set_material(ambient, specular, shininess);
+        Ok(())
+    }
+
+    /// Tint the material's ambient from `ground_rgb` on surfaces facing down to `sky_rgb`
+    /// on surfaces facing up (along the world up axis), for a softly top-lit look. Both
+    /// are `[r, g, b]` in [0,1]; equal colors give flat ambient, and white (the default)
+    /// leaves the material's ambient as set.
+    pub fn set_hemisphere_light(
+        &mut self,
+        sky_rgb: &[f32],
+        ground_rgb: &[f32],
+    ) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let sky = rgb_from_slice(sky_rgb, "sky_rgb")?;
+        let ground = rgb_from_slice(ground_rgb, "ground_rgb")?;
+        self.renderer.set_hemisphere_light(sky, ground);
+        Ok(())
+    }
+
+    /// Cull triangles facing away from the camera (off by default). Imported meshes with
+    /// inverted winding vanish with culling on; `flip_normals` fixes them.
+    pub fn set_cull_backfaces(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.renderer.set_cull(on);
+    }
+
+    /// Blend the mesh over the background using `set_opacity`. Triangles are not
+    /// depth-sorted, so concave meshes may show ordering artifacts.
+    pub fn set_transparent(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.renderer.set_transparent(on);
+    }
+
+    /// Mesh opacity in [0,1] while transparent mode is on (1 draws as opaque).
+    pub fn set_opacity(&mut self, alpha: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_opacity(alpha);
+    }
+
+    /// Gamma-correct the shaded mesh: lighting is computed in linear space, so writing it
+    /// straight to the (sRGB) canvas looks dark. 2.2 suits typical displays; 1.0, the
+    /// default, keeps the old uncorrected look.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_gamma(gamma);
+    }
+
+    /// Fade the mesh toward color `r, g, b` (components in [0,1]) with distance from the
+    /// eye, from none at `near` to full at `far`. Matching it to the background makes
+    /// large meshes recede. Disabled, or with `far <= near`, nothing is fogged.
+    pub fn set_fog(&mut self, r: f32, g: f32, b: f32, near: f32, far: f32, enabled: bool) {
+        self.needs_redraw.mark();
+        self.renderer
+            .set_fog(enabled.then_some(([r, g, b], near, far)));
+    }
+
+    /// Set the lighting model.
+    /// Allowed: "lambert" (default), "phong", "toon". Unknown names are ignored.
+    pub fn set_shading_model(&mut self, model: &str) {
+        self.needs_redraw.mark();
+        if let Some(model) = ShadingModel::from_str(model) {
+            self.renderer.set_shading_model(model);
+        }
+    }
+
+    /// Shade the model from a matcap (material capture): an image of a lit sphere, read at
+    /// each surface's view-facing direction, in place of the lights. `rgba` holds
+    /// `width * height` RGBA8 pixels, top row first (e.g. `ImageData.data`).
+    pub fn set_matcap(&mut self, rgba: &[u8], width: u32, height: u32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let image = TextureImage::new(rgba, width, height).map_err(|e| JsValue::from_str(&e))?;
+        self.renderer.set_matcap(Some(image))
+    }
+
+    /// Drop the matcap and go back to the lights.
+    pub fn clear_matcap(&mut self) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.renderer.set_matcap(None)
+    }
+
+    /// Multiply the model's color by a texture read at its texture coordinates, tiled
+    /// outside [0,1]. `rgba` holds `width * height` RGBA8 pixels, top row first (e.g.
+    /// `ImageData.data`). On WebGL1 only power-of-two sizes tile and mipmap; other sizes
+    /// are clamped at the edges.
+    pub fn set_texture(&mut self, rgba: &[u8], width: u32, height: u32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let image = TextureImage::new(rgba, width, height).map_err(|e| JsValue::from_str(&e))?;
+        self.renderer.set_texture(Some(image))
+    }
+
+    /// Remove the texture set by `set_texture`.
+    pub fn clear_texture(&mut self) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.renderer.set_texture(None)
+    }
+
+    /// Reflect an environment cube map in the model, for shiny materials. `faces` holds
+    /// six `size` x `size` RGBA8 images one after another in the order +X, -X, +Y, -Y, +Z,
+    /// -Z, each top row first. Off until this is called. One flat array rather than a
+    /// slice per face, since wasm-bindgen cannot take `&[&[u8]]`; build it from the six
+    /// faces with `Uint8Array.set`.
+    pub fn set_environment(&mut self, faces: &[u8], size: u32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let image = CubemapImage::new(faces, size).map_err(|e| JsValue::from_str(&e))?;
+        self.renderer.set_environment(Some(image))
+    }
+
+    /// Stop reflecting the environment set by `set_environment`.
+    pub fn clear_environment(&mut self) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.renderer.set_environment(None)
+    }
+
+    /// How much of the environment shows over the model's shading, from 0 (none) to 1
+    /// (a mirror). Defaults to 0.5.
+    pub fn set_reflectivity(&mut self, amount: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_reflectivity(amount);
+    }
+
+    /// Force the mesh fragment shader's float precision, e.g. to preview how a mobile GPU
+    /// without `highp` fragment support will band gradients.
+    /// Allowed: "auto" (default), "highp", "mediump". Unknown names are ignored.
+    ///
+    /// "auto" asks the context and uses `highp` wherever the fragment stage implements it,
+    /// so output can differ between browsers and devices. Recompiles the shaders.
+    pub fn set_precision(&mut self, precision: &str) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let precision = match precision {
+            "auto" => None,
+            name => match FloatPrecision::from_str(name) {
+                Some(precision) => Some(precision),
+                None => return Ok(()),
+            },
+        };
+        self.renderer.set_precision(precision)?;
+        self.upload_mesh()
     }
 
     /// Set the render mode.
     /// Allowed: "solid", "wireframe".
     pub fn set_render_mode(&mut self, mode: &str) {
+        self.needs_redraw.mark();
         match mode {
             "solid" => self.renderer.set_render_mode(RenderMode::Solid),
             "wireframe" => self.renderer.set_render_mode(RenderMode::Wireframe),
@@ -82,85 +656,940 @@ impl Viewer {
         }
     }
 
+    /// Toggle wireframe rendering (shorthand for `set_render_mode`).
+    pub fn set_wireframe(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        let mode = if on {
+            RenderMode::Wireframe
+        } else {
+            RenderMode::Solid
+        };
+        self.renderer.set_render_mode(mode);
+    }
+
+    /// Draw the wireframe over the solid mesh (a CAD-style edge view). The fill is pushed
+    /// back with a polygon offset so the edges don't z-fight with it. No effect in
+    /// wireframe mode or on points and lines.
+    pub fn set_wireframe_overlay(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.renderer.set_wireframe_overlay(on);
+    }
+
+    /// Highlight the model as selected with a rim a few pixels wide around its silhouette,
+    /// drawn over anything in front of it. Needs a stencil buffer, which the viewer asks
+    /// for when it creates the context; browsers that refuse one fill the grown model
+    /// instead of outlining it. Solid triangle meshes only.
+    pub fn set_selected(&mut self, on: bool) {
+        self.needs_redraw.mark();
+        self.renderer.set_selected(on);
+    }
+
+    /// Color of the selection outline, components in [0,1].
+    pub fn set_outline_color(&mut self, r: f32, g: f32, b: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_outline_color(r, g, b);
+    }
+
+    /// Darken the ground under the model with its shadow from the first light, flattened
+    /// onto the ground plane (square to `set_world_up`) through the model's lowest point;
+    /// `alpha` in [0,1] sets how dark, and a non-finite one is ignored. Needs the stencil
+    /// buffer, like the selection outline.
+    pub fn set_ground_shadow(&mut self, on: bool, alpha: f32) {
+        self.needs_redraw.mark();
+        self.renderer.set_ground_shadow(on.then_some(alpha));
+    }
+
     /// Set the view mode.
     /// Allowed: "perspective", "orth".
     pub fn set_view_mode(&mut self, mode: &str) {
-        match mode {
-            "perspective" => {
-                self.view_mode = ViewMode::Perspective;
-            }
-            "orth" => {
-                self.view_mode = ViewMode::Orthographic;
-                // Update orthographic size to match current view, but don't reset camera
-                // Scale orthographic size to roughly match the current perspective view
-                let tan_half_fovy = (self.camera.fovy * 0.5).tan();
-                let visible_height = 2.0 * self.camera.distance * tan_half_fovy;
-                self.orthographic_size = visible_height * 0.5;
-            }
-            _ => {}
+        self.needs_redraw.mark();
+        self.set_projection(mode);
+    }
+
+    /// Set the camera projection.
+    /// Allowed: "perspective", "orthographic".
+    pub fn set_projection(&mut self, mode: &str) {
+        self.needs_redraw.mark();
+        let Some(mode) = ProjectionMode::from_str(mode) else {
+            return;
+        };
+        if mode == ProjectionMode::Orthographic {
+            // Update orthographic size to match current view, but don't reset camera
+            // Scale orthographic size to roughly match the current perspective view
+            let tan_half_fovy = (self.camera.fovy * 0.5).tan();
+            let visible_height = 2.0 * self.camera.distance * tan_half_fovy;
+            self.orthographic_size = visible_height * 0.5;
         }
+        self.camera.projection_mode = mode;
     }
 
     pub fn fit_to_view(&mut self) {
-        let aspect = self.width as f32 / self.height as f32;
-        self.camera.fit_to_bounds(self.bounds, aspect);
+        self.needs_redraw.mark();
+        self.fit_to_view_padded(DEFAULT_FIT_PADDING);
+    }
+
+    /// Frame the bounds with a custom margin (1.0 = tight, the default is 1.15), as
+    /// placed by the translation, rotation and scale.
+    pub fn fit_to_view_padded(&mut self, padding: f32) {
+        self.needs_redraw.mark();
+        let aspect = self.aspect();
+        let model = self.model_matrix();
+        let radius = if self.tight_fit && !self.mesh.positions.is_empty() {
+            let (center, radius) = bounding_sphere(&self.mesh.positions);
+            let (center, radius) = placed_sphere(&model, self.scale, center, radius);
+            self.camera.fit_to_sphere(center, radius, aspect, padding);
+            radius
+        } else {
+            let bounds = self.bounds.transformed(&model);
+            self.camera
+                .fit_to_bounds_with_padding(bounds, aspect, padding);
+            bounds.radius()
+        };
         // Update orthographic size based on bounds
-        let r = self.bounds.radius().max(1e-4);
-        self.orthographic_size = r * 1.15;
+        self.orthographic_size = radius.max(1e-4) * padding.max(1e-3);
+    }
+
+    /// Frame using the mesh's exact bounding sphere rather than the box around it.
+    /// Leaves less empty space around round shapes; takes effect on the next fit.
+    pub fn set_tight_fit(&mut self, on: bool) {
+        self.tight_fit = on;
+    }
+
+    /// Return to the initial framing: default orientation, refit to the current bounds.
+    pub fn reset_view(&mut self) {
+        self.needs_redraw.mark();
+        self.camera.reset();
+        self.fit_to_view();
+    }
+
+    /// Jump to a canned view of the model and refit it.
+    /// Allowed: "front", "back", "top", "bottom", "left", "right", "iso". Unknown names
+    /// are ignored. Directions follow `set_world_up`; "top" stops just short of the pole.
+    pub fn set_view_preset(&mut self, name: &str) {
+        self.needs_redraw.mark();
+        if let Some(preset) = ViewPreset::from_str(name) {
+            let dir = self.camera.preset_direction(preset);
+            self.camera.view_from_direction(dir);
+            self.fit_to_view();
+        }
+    }
+
+    /// Bounds of the current model as a plain JS object: `{ min, max, center, radius }`,
+    /// with `[x, y, z]` points and the radius of the sphere through the box corners.
+    pub fn get_bounds(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.bounds.summary()).map_err(JsValue::from)
+    }
+
+    /// Camera snapshot as a plain JS object: `{ target, distance, yaw, pitch, roll, fovy }`.
+    pub fn get_camera_state(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.camera.state()).map_err(JsValue::from)
+    }
+
+    /// Restore a snapshot from `get_camera_state` (e.g. loaded from localStorage). Errors on
+    /// non-finite values or a distance that is not positive; pitch and distance are kept
+    /// within the orbit and zoom limits.
+    pub fn set_camera_state(&mut self, js: JsValue) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let state: CameraState = serde_wasm_bindgen::from_value(js)
+            .map_err(|e| JsValue::from_str(&format!("invalid camera state: {e}")))?;
+        self.camera
+            .set_state(state)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Put the camera at the eye point looking at the target point, in world units.
+    /// Orbiting continues around the target from there. Errors on non-finite
+    /// coordinates or an eye on the target.
+    pub fn look_at(
+        &mut self,
+        eye_x: f32,
+        eye_y: f32,
+        eye_z: f32,
+        target_x: f32,
+        target_y: f32,
+        target_z: f32,
+    ) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        let eye = Vec3::new(eye_x, eye_y, eye_z);
+        self.camera
+            .set_look_at(eye, Vec3::new(target_x, target_y, target_z))
+            .map_err(|e| JsValue::from_str(&e))
     }
 
     /// Rotate/orbit in radians.
     pub fn rotate(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        self.needs_redraw.mark();
         self.camera.orbit(delta_yaw, delta_pitch);
     }
 
-    /// Pan in world units (relative to current view).
-    pub fn pan(&mut self, right: f32, up: f32) {
-        match self.view_mode {
-            ViewMode::Perspective => {
-                // In perspective mode, pan is relative to camera distance
-                // The pan speed in TypeScript (PAN_SPEED) is already calibrated for perspective
-                self.camera.pan(right, up);
-            }
-            ViewMode::Orthographic => {
-                // In orthographic mode, scale pan to match perspective feel
-                // Use camera distance as reference to maintain consistent panning speed
-                // The orthographic_size represents the visible height, so we scale pan accordingly
-                let scale = self.camera.distance * 0.1;
-                self.camera.pan(right * scale, up * scale);
+    /// How `arcball` turns the camera: "orbit" (default) or "arcball". Unknown names
+    /// are ignored.
+    pub fn set_rotation_mode(&mut self, mode: &str) {
+        if let Some(mode) = RotationMode::from_str(mode) {
+            self.rotation_mode = mode;
+        }
+    }
+
+    /// "free" (default) orbits in yaw and pitch; "turntable" only yaws, keeping product
+    /// shots on-axis. Unknown names are ignored.
+    pub fn set_orbit_mode(&mut self, mode: &str) {
+        if let Some(mode) = OrbitMode::from_str(mode) {
+            self.camera.orbit_mode = mode;
+        }
+    }
+
+    /// Which world axis the orbit keeps up: "y" (default) or "z" for Z-up models such
+    /// as CAD exports. Yaw and pitch are kept, now measured about the new axis. The
+    /// hemisphere light's sky and the reference grid follow it.
+    /// Unknown names are ignored.
+    pub fn set_world_up(&mut self, axis: &str) {
+        self.needs_redraw.mark();
+        let up = match axis.to_ascii_lowercase().as_str() {
+            "y" => Vec3::new(0.0, 1.0, 0.0),
+            "z" => Vec3::new(0.0, 0.0, 1.0),
+            _ => return,
+        };
+        self.camera.world_up = up;
+        self.renderer.set_world_up(up);
+        self.upload_grid();
+    }
+
+    /// Rotate for a pointer drag from `prev` to `curr`, in canvas pixels (`resize` units)
+    /// from the top-left. In arcball mode the model follows the cursor like a trackball; in
+    /// orbit mode the same drag becomes yaw and pitch (a quarter turn per half the
+    /// shorter canvas side). A turntable orbit mode always takes the orbit path.
+    pub fn arcball(&mut self, prev_x: f32, prev_y: f32, curr_x: f32, curr_y: f32) {
+        self.needs_redraw.mark();
+        let prev = arcball_coords(prev_x, prev_y, self.width, self.height);
+        let curr = arcball_coords(curr_x, curr_y, self.width, self.height);
+        // A turntable only yaws, so a free trackball drag would break its lock.
+        let free = self.camera.orbit_mode == OrbitMode::Free;
+        match self.rotation_mode {
+            RotationMode::Arcball if free => self.camera.arcball_rotate(prev, curr),
+            _ => {
+                let quarter_turn = std::f32::consts::FRAC_PI_2;
+                self.camera.orbit(
+                    (curr.0 - prev.0) * quarter_turn,
+                    (curr.1 - prev.1) * quarter_turn,
+                );
             }
         }
     }
 
+    /// Tilt the horizon by rotating the camera around its view direction.
+    pub fn roll(&mut self, delta_radians: f32) {
+        self.needs_redraw.mark();
+        self.camera.roll(delta_radians);
+    }
+
+    /// Set the vertical field of view in degrees (10 to 120). With `refit` the camera
+    /// backs off or closes in so the model keeps its framing; without it the distance
+    /// is kept, which combined with `zoom` gives a dolly-zoom effect.
+    pub fn set_fov(&mut self, degrees: f32, refit: bool) {
+        self.needs_redraw.mark();
+        self.camera.set_fov(degrees);
+        if refit {
+            self.fit_to_view();
+        }
+    }
+
+    /// Keep orbit pitch between `min` and `max` radians (default about +/-1.54, just short
+    /// of the poles). Limits of +/-PI/2 allow looking straight down or up. Errors unless
+    /// `min < max`.
+    pub fn set_pitch_limits(&mut self, min: f32, max: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.camera
+            .set_pitch_limits(min, max)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Keep the camera between `min` and `max` world units from its target when zooming.
+    /// Loading or refitting a model widens the range as needed to frame it. Errors unless
+    /// `0 < min < max`.
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.camera
+            .set_zoom_limits(min, max)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Fix the near/far clip distances, e.g. to cure z-fighting in scenes with both tiny
+    /// and huge parts. Later fits keep them until `set_auto_clip_planes`. Errors unless
+    /// `0 < znear < zfar`.
+    pub fn set_clip_planes(&mut self, znear: f32, zfar: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.camera
+            .set_clip(znear, zfar)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Go back to clip planes derived from the model size, and refit.
+    pub fn set_auto_clip_planes(&mut self) {
+        self.needs_redraw.mark();
+        self.camera.clip_auto = true;
+        self.fit_to_view();
+    }
+
+    /// Project with this width-to-height ratio instead of the canvas's, e.g. when the
+    /// canvas is stretched by CSS or the frame is shown on another target. Refits the
+    /// model. Errors unless `aspect > 0`.
+    pub fn set_aspect_override(&mut self, aspect: f32) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        if !(aspect > 0.0 && aspect.is_finite()) {
+            return Err(JsValue::from_str(&format!(
+                "aspect must be > 0, got {aspect}"
+            )));
+        }
+        self.aspect_override = aspect;
+        self.aspect_auto = false;
+        self.fit_to_view();
+        Ok(())
+    }
+
+    /// Go back to the canvas's own aspect ratio, and refit.
+    pub fn set_auto_aspect(&mut self) {
+        self.needs_redraw.mark();
+        self.aspect_auto = true;
+        self.fit_to_view();
+    }
+
+    /// Pan by a drag of `right`/`up` canvas pixels. The motion is scaled so a point on
+    /// the target plane follows the cursor at any zoom level.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        self.needs_redraw.mark();
+        let visible_half_height = match self.camera.projection_mode {
+            ProjectionMode::Perspective => self.camera.distance * (self.camera.fovy * 0.5).tan(),
+            ProjectionMode::Orthographic => self.orthographic_size,
+        };
+        let scale = world_per_pixel(visible_half_height, self.height);
+        self.camera.pan(right * scale, up * scale);
+    }
+
     /// Zoom factor ( >1 out, <1 in ).
     pub fn zoom(&mut self, factor: f32) {
-        match self.view_mode {
-            ViewMode::Perspective => {
+        self.needs_redraw.mark();
+        match self.camera.projection_mode {
+            ProjectionMode::Perspective => {
                 self.camera.zoom(factor);
             }
-            ViewMode::Orthographic => {
+            ProjectionMode::Orthographic => {
                 // In orthographic mode, zoom by adjusting the orthographic size
                 self.orthographic_size = (self.orthographic_size / factor).max(0.01);
             }
         }
     }
 
-    pub fn draw(&self) {
-        let aspect = self.width as f32 / self.height as f32;
-        let proj = match self.view_mode {
-            ViewMode::Perspective => {
-                Mat4::perspective(self.camera.fovy, aspect, self.camera.znear, self.camera.zfar)
+    /// Zoom by a wheel event's `deltaY` (pixel mode): scrolling down (positive) zooms
+    /// out, and each notch changes the view by the same ratio at any distance.
+    pub fn zoom_wheel(&mut self, delta: f32) {
+        self.needs_redraw.mark();
+        match self.camera.projection_mode {
+            ProjectionMode::Perspective => {
+                self.camera.zoom_by_delta(-delta, WHEEL_ZOOM_SENSITIVITY);
+            }
+            ProjectionMode::Orthographic => {
+                let factor = wheel_zoom_factor(-delta, WHEEL_ZOOM_SENSITIVITY);
+                self.orthographic_size = (self.orthographic_size * factor).max(0.01);
+            }
+        }
+    }
+
+    /// Zoom toward the cursor at normalized device coordinates (-1..1, +Y up).
+    pub fn zoom_at(&mut self, factor: f32, ndc_x: f32, ndc_y: f32) {
+        self.needs_redraw.mark();
+        let aspect = self.aspect();
+        match self.camera.projection_mode {
+            ProjectionMode::Perspective => {
+                self.camera.zoom_at(factor, ndc_x, ndc_y, aspect);
             }
-            ViewMode::Orthographic => {
-                let h = self.orthographic_size;
-                let w = h * aspect;
-                Mat4::orthographic(-w, w, -h, h, self.camera.znear, self.camera.zfar)
+            ProjectionMode::Orthographic => {
+                // Shift by the change in visible half-extent so the cursor point stays put.
+                let old_size = self.orthographic_size;
+                self.zoom(factor);
+                let shift = old_size - self.orthographic_size;
+                self.camera.pan(ndc_x * aspect * shift, ndc_y * shift);
             }
+        }
+    }
+
+    /// Move the model by `(x, y, z)` world units. Like the other placement setters this
+    /// doesn't refit the camera, so the model visibly moves; call `fit_to_view` to
+    /// frame it again.
+    pub fn set_translation(&mut self, x: f32, y: f32, z: f32) {
+        self.needs_redraw.mark();
+        self.translation = Vec3::new(x, y, z);
+    }
+
+    /// Orient the model by rotations in radians about the X, then Y, then Z world axes.
+    pub fn set_rotation_euler(&mut self, x: f32, y: f32, z: f32) {
+        self.needs_redraw.mark();
+        let about = |axis: Vec3, angle: f32| Quat::from_axis_angle(axis, angle);
+        let qx = about(Vec3::new(1.0, 0.0, 0.0), x);
+        let qy = about(Vec3::new(0.0, 1.0, 0.0), y);
+        let qz = about(Vec3::new(0.0, 0.0, 1.0), z);
+        self.rotation = qz.mul(qy).mul(qx).normalize();
+    }
+
+    /// Scale the model per axis about its origin; lighting stays correct for non-uniform
+    /// scales. Negative factors mirror; zero or non-finite ones are ignored, since they
+    /// would flatten the model and break its normals.
+    pub fn set_scale(&mut self, x: f32, y: f32, z: f32) {
+        self.needs_redraw.mark();
+        if [x, y, z].iter().all(|v| v.is_finite() && *v != 0.0) {
+            self.scale = Vec3::new(x, y, z);
+        }
+    }
+
+    /// Spin the model around +Y at `speed_rad_per_sec` (0 stops it).
+    pub fn set_auto_rotate(&mut self, speed_rad_per_sec: f32) {
+        self.auto_rotate_speed = speed_rad_per_sec;
+    }
+
+    /// Advance animations by `dt_seconds` and redraw. Call from `requestAnimationFrame`.
+    pub fn tick(&mut self, dt_seconds: f32) {
+        self.model_yaw = advance_yaw(self.model_yaw, self.auto_rotate_speed, dt_seconds);
+        self.camera.update(dt_seconds);
+        self.draw();
+    }
+
+    /// Smooth orbit/zoom: fraction of the remaining motion kept per frame, 0..0.99.
+    /// 0 (the default) applies input immediately; otherwise drive the camera with `tick`.
+    pub fn set_damping(&mut self, damping: f32) {
+        self.needs_redraw.mark();
+        self.camera.damping = if damping.is_finite() {
+            damping.clamp(0.0, 0.99)
+        } else {
+            0.0
+        };
+        if self.camera.damping == 0.0 {
+            self.camera.update(0.0);
+        }
+    }
+
+    /// World-space distance along the ray under `(ndc_x, ndc_y)` (-1..1, +Y up) to the
+    /// nearest mesh triangle, measured from the near plane; `None` on a miss.
+    pub fn pick(&self, ndc_x: f32, ndc_y: f32) -> Option<f32> {
+        let view_proj = self.projection().mul(self.camera.view_matrix());
+        pick_placed(view_proj, self.model_matrix(), &self.mesh, ndc_x, ndc_y)
+    }
+
+    /// Canvas pixel position (origin top-left, y down) of the world-space point
+    /// `(x, y, z)`, e.g. to place an HTML label over it. `None` when the point is behind
+    /// the camera. Points outside the view give coordinates outside the canvas.
+    pub fn project(&self, x: f32, y: f32, z: f32) -> Option<Vec<f32>> {
+        let view_proj = self.projection().mul(self.camera.view_matrix());
+        world_to_pixel(view_proj, Vec3::new(x, y, z), self.width, self.height).map(Vec::from)
+    }
+
+    /// World-space point under canvas pixel `(screen_x, screen_y)` at window depth
+    /// `depth` (0 = near plane, 1 = far plane), as `[x, y, z]`. The inverse of `project`.
+    pub fn unproject(&self, screen_x: f32, screen_y: f32, depth: f32) -> Option<Vec<f32>> {
+        let inverse = self.projection().mul(self.camera.view_matrix()).inverse()?;
+        let p = pixel_to_world(inverse, screen_x, screen_y, depth, self.width, self.height);
+        Some(vec![p.x, p.y, p.z])
+    }
+
+    /// Rebuild GPU state after the browser restores a lost context. Call this from the
+    /// canvas `webglcontextrestored` handler (the `webglcontextlost` handler should
+    /// `preventDefault()` so restoration can happen), then `draw`.
+    pub fn restore_context(&mut self) -> Result<(), JsValue> {
+        self.needs_redraw.mark();
+        self.renderer.rebuild()?;
+        self.upload_mesh()
+    }
+
+    /// Free the viewer's GPU buffers, programs and shaders now instead of whenever JS
+    /// garbage-collects them. The viewer must not be used afterwards: the JS object is
+    /// consumed, so later calls (a second `dispose` included) throw instead of touching
+    /// freed state. The canvas and its context stay with the page.
+    pub fn dispose(mut self) {
+        self.renderer.release();
+    }
+
+    /// Render the current view and return it as PNG bytes (wrap in a `Blob` on the JS
+    /// side). Draws first so the read doesn't depend on `preserveDrawingBuffer`.
+    pub fn capture_png(&self) -> Result<Vec<u8>, JsValue> {
+        self.draw();
+        let (width, height) = self.viewport_size();
+        let mut pixels = self.renderer.read_pixels(width, height)?;
+        png::flip_rows(&mut pixels, width as usize * 4);
+        Ok(png::encode_rgba(width as u32, height as u32, &pixels))
+    }
+
+    /// Size of the current model as uploaded: `{ vertices, triangles, bytes }`, where
+    /// `bytes` covers its vertex and index buffers. Flat shading splits vertices per
+    /// triangle, so it can report far more vertices than the file has.
+    pub fn mesh_stats(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.mesh_stats).map_err(JsValue::from)
+    }
+
+    /// Counters from the last `draw`: `{ draw_calls, triangles, vertices, last_frame_ms }`.
+    pub fn stats(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.renderer.stats()).map_err(JsValue::from)
+    }
+
+    /// Redraw only if something changed since the last draw; returns whether it drew.
+    /// Cheap enough to call from every `requestAnimationFrame`.
+    pub fn draw_if_needed(&mut self) -> bool {
+        let dirty = self.needs_redraw.get();
+        if dirty {
+            self.draw();
+        }
+        dirty
+    }
+
+    /// Draw unconditionally.
+    pub fn draw(&self) {
+        self.needs_redraw.clear();
+        let proj = self.projection();
+        let view = self.camera.view_matrix();
+        let model = self.model_matrix();
+        let normal_matrix = view.mul(model).normal_matrix();
+        let placement = Placement::Single {
+            model: &model.m,
+            normal_matrix: &normal_matrix,
         };
-        let view = Mat4::look_at(self.camera.eye(), self.camera.target, self.camera.view_up());
-        let model = Mat4::identity();
+        let (width, height) = self.viewport_size();
         self.renderer
-            .draw(self.width, self.height, &proj.m, &view.m, &model.m);
+            .draw(width, height, &proj.m, &view.m, placement);
+    }
+
+    /// Draw one copy of the mesh per model matrix (16 column-major floats each, world
+    /// space) in a single instanced call, e.g. for voxel grids. WebGL1 without
+    /// `ANGLE_instanced_arrays` falls back to one draw per copy.
+    pub fn draw_instanced(&self, model_matrices: &[f32]) -> Result<(), JsValue> {
+        let count = instance_count(model_matrices.len()).map_err(|e| JsValue::from_str(&e))?;
+        self.needs_redraw.clear();
+        let placement = Placement::Instances {
+            models: model_matrices,
+            count,
+        };
+        let (width, height) = self.viewport_size();
+        self.renderer.draw(
+            width,
+            height,
+            &self.projection().m,
+            &self.camera.view_matrix().m,
+            placement,
+        );
+        Ok(())
     }
 }
 
+impl Viewer {
+    /// Show an imported `mesh` and frame it, after the import options (`set_fix_winding`,
+    /// `generate_uvs`); see `show_mesh`.
+    fn replace_mesh(&mut self, mut mesh: Mesh) -> Result<(), JsValue> {
+        if self.fix_winding {
+            mesh.fix_winding();
+        }
+        let source = (self.uv_projection.is_some() && !mesh.has_uvs()).then(|| mesh.clone());
+        let mesh = self.with_generated_uvs(mesh);
+        self.show_mesh(mesh)?;
+        self.uv_source = source;
+        Ok(())
+    }
+
+    /// Make `mesh` the current mesh, with UVs of its own, and frame it. Fails if it needs
+    /// 32-bit indices the context lacks or the upload fails; the previous mesh then stays,
+    /// on the GPU too.
+    fn show_mesh(&mut self, mesh: Mesh) -> Result<(), JsValue> {
+        if matches!(mesh.indices, IndexBuffer::U32(_)) && !self.renderer.supports_u32_indices() {
+            return Err(JsValue::from_str(
+                "mesh needs 32-bit indices but OES_element_index_uint is unavailable",
+            ));
+        }
+        let previous = std::mem::replace(&mut self.mesh, mesh);
+        if let Err(err) = self.upload_mesh() {
+            self.mesh = previous;
+            // Best effort: the error that matters is the one for the new mesh.
+            let _ = self.upload_mesh();
+            return Err(err);
+        }
+        self.uv_source = None;
+        self.fit_to_view();
+        Ok(())
+    }
+
+    /// `mesh` with UVs from the `generate_uvs` projection, if one is set and it has none.
+    fn with_generated_uvs(&self, mesh: Mesh) -> Mesh {
+        match self.uv_projection {
+            Some(projection) if !mesh.has_uvs() => mesh.with_generated_uvs(projection),
+            _ => mesh,
+        }
+    }
+
+    /// Push the current mesh to the GPU using the active normal mode.
+    fn upload_mesh(&mut self) -> Result<(), JsValue> {
+        let mesh = self.mesh.with_normal_mode(self.normal_mode);
+        self.renderer.set_mesh(&mesh)?;
+        self.bounds = mesh.bounds;
+        self.mesh_stats = mesh.stats();
+        self.upload_grid();
+        self.upload_normal_lines(&mesh);
+        Ok(())
+    }
+
+    /// Rebuild the normal lines for the uploaded `mesh`, or hide them.
+    fn upload_normal_lines(&mut self, mesh: &Mesh) {
+        match self.normal_length {
+            Some(length) => {
+                let lines = normal_lines(&mesh.positions, &mesh.normals, length);
+                self.renderer.set_normal_lines(Some(&lines));
+            }
+            None => self.renderer.set_normal_lines(None),
+        }
+    }
+
+    /// Rebuild the grid for the current bounds, or hide it.
+    fn upload_grid(&mut self) {
+        if !self.show_grid {
+            self.renderer.set_grid(None);
+            return;
+        }
+        // The grid lies on the ground plane of the current world up.
+        let (side, _, front) = self.camera.orbit_frame();
+        let (half_extent, divisions) = grid_size(&self.bounds, side, front);
+        self.renderer
+            .set_grid(Some(&grid_lines(half_extent, divisions, side, front)));
+    }
+
+    fn aspect(&self) -> f32 {
+        let aspect_override = (!self.aspect_auto).then_some(self.aspect_override);
+        viewport_aspect(self.width, self.height, aspect_override)
+    }
+
+    /// Drawing-buffer size the frame covers.
+    fn viewport_size(&self) -> (i32, i32) {
+        scaled_viewport(self.width, self.height, self.dpr)
+    }
+
+    fn projection(&self) -> Mat4 {
+        self.camera
+            .projection_matrix(self.aspect(), self.orthographic_size)
+    }
+
+    fn model_matrix(&self) -> Mat4 {
+        model_transform(self.translation, self.rotation, self.scale, self.model_yaw)
+    }
+}
+
+/// Dirty flag with interior mutability so `&self` drawing can clear it.
+#[derive(Default)]
+struct RedrawFlag(Cell<bool>);
+
+impl RedrawFlag {
+    fn mark(&self) {
+        self.0.set(true);
+    }
+
+    fn clear(&self) {
+        self.0.set(false);
+    }
+
+    fn get(&self) -> bool {
+        self.0.get()
+    }
+}
+
+/// Model matrix: scale, then rotate, then the auto-rotate spin about world +Y through
+/// the object's origin, then translate.
+fn model_transform(translation: Vec3, rotation: Quat, scale: Vec3, spin_yaw: f32) -> Mat4 {
+    Mat4::translation(translation)
+        .mul(Mat4::rotation_y(spin_yaw))
+        .mul(rotation.to_mat4())
+        .mul(Mat4::scaling(scale))
+}
+
+/// Sphere at model-space `center` with `radius`, placed by `model` built with `scale`.
+/// Rotation and translation keep lengths, so only the largest scale factor grows it.
+fn placed_sphere(model: &Mat4, scale: Vec3, center: Vec3, radius: f32) -> (Vec3, f32) {
+    let stretch = scale.x.abs().max(scale.y.abs()).max(scale.z.abs());
+    (model.mul_point(center), radius * stretch)
+}
+
+/// World-space distance from the near plane to the nearest triangle of `mesh`, placed by
+/// `model`, under `(ndc_x, ndc_y)`. The ray is cast in model space and its hit carried
+/// back out, so scaled models report world units.
+fn pick_placed(view_proj: Mat4, model: Mat4, mesh: &Mesh, ndc_x: f32, ndc_y: f32) -> Option<f32> {
+    let ray = Ray::from_ndc(view_proj.mul(model).inverse()?, ndc_x, ndc_y);
+    let t = ray.intersect_mesh(mesh)?;
+    let hit = model.mul_point(ray.origin.add(ray.dir.mul(t)));
+    Some(hit.sub(model.mul_point(ray.origin)).length())
+}
+
+/// Step a yaw angle by `speed * dt`, wrapped to [0, 2pi) so it never loses precision.
+fn advance_yaw(yaw: f32, speed: f32, dt: f32) -> f32 {
+    if speed == 0.0 || !dt.is_finite() {
+        return yaw;
+    }
+    (yaw + speed * dt).rem_euclid(std::f32::consts::TAU)
+}
+
+/// Aspect ratio to project with: the override if any, else the canvas's `width / height`.
+fn viewport_aspect(width: i32, height: i32, aspect_override: Option<f32>) -> f32 {
+    aspect_override.unwrap_or(width as f32 / height.max(1) as f32)
+}
+
+/// Viewport for a `width` x `height` canvas at `dpr` buffer pixels per unit, rounded to
+/// whole pixels and at least one.
+fn scaled_viewport(width: i32, height: i32, dpr: f32) -> (i32, i32) {
+    let scale = |n: i32| ((n as f32 * dpr).round() as i32).max(1);
+    (scale(width), scale(height))
+}
+
+/// World units covered by one pixel on the target plane, given half the visible height.
+fn world_per_pixel(visible_half_height: f32, height_px: i32) -> f32 {
+    2.0 * visible_half_height / height_px.max(1) as f32
+}
+
+/// Pixel position (y down) to arcball coordinates: y up, with the unit circle inscribed
+/// in the viewport so the virtual sphere stays round on wide canvases.
+fn arcball_coords(x: f32, y: f32, width: i32, height: i32) -> (f32, f32) {
+    let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+    let radius = w.min(h) * 0.5;
+    ((x - w * 0.5) / radius, (h * 0.5 - y) / radius)
+}
+
+/// Canvas pixel (y down) of a world point under `view_proj`; `None` behind the eye.
+fn world_to_pixel(view_proj: Mat4, world: Vec3, width: i32, height: i32) -> Option<[f32; 2]> {
+    let [x, y, _, w] = view_proj.mul_vec4([world.x, world.y, world.z, 1.0]);
+    if w <= 1e-6 {
+        return None;
+    }
+    let (w_px, h_px) = (width.max(1) as f32, height.max(1) as f32);
+    Some([(x / w * 0.5 + 0.5) * w_px, (0.5 - y / w * 0.5) * h_px])
+}
+
+/// World point at canvas pixel `(x, y)` and window depth `depth` in [0, 1].
+fn pixel_to_world(
+    inverse_view_proj: Mat4,
+    x: f32,
+    y: f32,
+    depth: f32,
+    width: i32,
+    height: i32,
+) -> Vec3 {
+    let (w_px, h_px) = (width.max(1) as f32, height.max(1) as f32);
+    let ndc = Vec3::new(
+        x / w_px * 2.0 - 1.0,
+        1.0 - y / h_px * 2.0,
+        depth * 2.0 - 1.0,
+    );
+    inverse_view_proj.mul_point(ndc)
+}
+
+/// Grid half-extent and division count covering the bounds' footprint on the ground plane
+/// spanned by the axis-aligned `side` and `front`, with margin. Cells are a power of ten
+/// in size, at most ten per side of the origin.
+fn grid_size(bounds: &Bounds, side: Vec3, front: Vec3) -> (f32, u32) {
+    let (min, max) = (bounds.min, bounds.max);
+    let reach = [min.dot(side), max.dot(side), min.dot(front), max.dot(front)]
+        .iter()
+        .fold(0.0f32, |r, v| r.max(v.abs()))
+        .max(1e-3)
+        * 1.5;
+    let cell = 10f32.powf((reach / 10.0).log10().ceil());
+    let per_side = (reach / cell).ceil().max(1.0);
+    (cell * per_side, 2 * per_side as u32)
+}
+
+fn rgb_from_slice(v: &[f32], name: &str) -> Result<[f32; 3], JsValue> {
+    match v {
+        [r, g, b] => Ok([*r, *g, *b]),
+        _ => Err(JsValue::from_str(&format!(
+            "{name} must have 3 components, got {}",
+            v.len()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arcball_coords_inscribe_the_unit_circle() {
+        assert_eq!(arcball_coords(400.0, 300.0, 800, 600), (0.0, 0.0));
+        // The shorter side spans [-1, 1]; the longer one reaches past it.
+        assert_eq!(arcball_coords(400.0, 0.0, 800, 600), (0.0, 1.0));
+        assert_eq!(arcball_coords(0.0, 300.0, 800, 600).0, -4.0 / 3.0);
+    }
+
+    #[test]
+    fn viewport_scales_with_the_device_pixel_ratio() {
+        assert_eq!(scaled_viewport(800, 600, 1.0), (800, 600));
+        assert_eq!(scaled_viewport(800, 600, 2.0), (1600, 1200));
+        assert_eq!(scaled_viewport(333, 201, 1.5), (500, 302));
+        assert_eq!(scaled_viewport(1, 1, 0.25), (1, 1));
+        // The aspect ratio only depends on the CSS size.
+        assert_eq!(viewport_aspect(800, 600, None), 1600.0 / 1200.0);
+    }
+
+    #[test]
+    fn cube_bounds_summary_spans_the_unit_cube() {
+        let summary = make_primitive(Primitive::Cube).bounds.summary();
+        assert_eq!(summary.min, [-0.5; 3]);
+        assert_eq!(summary.max, [0.5; 3]);
+        assert_eq!(summary.center, [0.0; 3]);
+        assert!((summary.radius - 0.75_f32.sqrt()).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn translated_cube_center_projects_shifted() {
+        let cube = make_primitive(Primitive::Cube);
+        let mut camera = Camera::new();
+        camera.fit_to_bounds(cube.bounds, 1.0);
+        let view_proj = camera.projection_matrix(1.0, 2.0).mul(camera.view_matrix());
+        let center_px = |model: Mat4| {
+            let world = model.mul_point(cube.bounds.center());
+            world_to_pixel(view_proj, world, 600, 600).unwrap()
+        };
+
+        let (one, still) = (Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.0, 0.0, 0.0));
+        let home = center_px(model_transform(still, Quat::identity(), one, 0.0));
+        assert!((home[0] - 300.0).abs() <= 0.01 && (home[1] - 300.0).abs() <= 0.01);
+
+        // +X moves right and +Y moves up the canvas (y grows downward).
+        let right = model_transform(Vec3::new(0.5, 0.0, 0.0), Quat::identity(), one, 0.0);
+        let [x, y] = center_px(right);
+        assert!(x > 300.0 + 1.0 && (y - 300.0).abs() <= 0.01);
+        let up = model_transform(Vec3::new(0.0, 0.5, 0.0), Quat::identity(), one, 0.7);
+        let [x, y] = center_px(up);
+        assert!((x - 300.0).abs() <= 0.01 && y < 300.0 - 1.0);
+
+        // Rotation and scale happen about the object's origin, before the spin.
+        let twist = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), 1.0);
+        let stretch = Vec3::new(2.0, 3.0, 1.0);
+        let [x, y] = center_px(model_transform(
+            Vec3::new(0.5, 0.0, 0.0),
+            twist,
+            stretch,
+            0.4,
+        ));
+        let [rx, ry] = center_px(right);
+        assert!((x - rx).abs() <= 0.01 && (y - ry).abs() <= 0.01);
+    }
+
+    #[test]
+    fn placed_fit_and_pick_use_world_units() {
+        let cube = make_primitive(Primitive::Cube);
+        let scale = Vec3::new(3.0, 1.0, 1.0);
+        let model = model_transform(Vec3::new(5.0, 0.0, 0.0), Quat::identity(), scale, 0.0);
+
+        let bounds = cube.bounds.transformed(&model);
+        assert_eq!((bounds.min.x, bounds.max.x), (3.5, 6.5));
+        let (center, radius) = placed_sphere(&model, scale, cube.bounds.center(), 1.0);
+        assert_eq!((center.x, radius), (5.0, 3.0));
+
+        // Looking down -Z at the stretched cube's face half a unit in front of its center.
+        let mut camera = Camera::new();
+        camera
+            .set_look_at(Vec3::new(5.0, 0.0, 10.0), Vec3::new(5.0, 0.0, 0.0))
+            .unwrap();
+        camera.znear = 1.0;
+        let view_proj = camera.projection_matrix(1.0, 2.0).mul(camera.view_matrix());
+        let t = pick_placed(view_proj, model, &cube, 0.0, 0.0).unwrap();
+        assert!((t - 8.5).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn aspect_override_drives_the_projection() {
+        assert_eq!(viewport_aspect(800, 600, None), 800.0 / 600.0);
+        assert_eq!(viewport_aspect(800, 600, Some(2.0)), 2.0);
+
+        // The horizontal focal term is the vertical one over the aspect.
+        let camera = Camera::new();
+        let m = camera
+            .projection_matrix(viewport_aspect(800, 600, Some(2.0)), 2.0)
+            .m;
+        assert!((m[0] * 2.0 - m[5]).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn fitted_center_projects_to_canvas_center_and_back() {
+        let bounds = Bounds::new(Vec3::new(1.0, -2.0, 0.5), Vec3::new(4.0, 1.0, 2.5));
+        let mut camera = Camera::new();
+        camera.orbit(0.7, 0.3);
+        camera.fit_to_bounds(bounds, 800.0 / 600.0);
+        let view_proj = camera
+            .projection_matrix(800.0 / 600.0, 2.0)
+            .mul(camera.view_matrix());
+
+        let [x, y] = world_to_pixel(view_proj, bounds.center(), 800, 600).unwrap();
+        assert!(
+            (x - 400.0).abs() <= 0.01 && (y - 300.0).abs() <= 0.01,
+            "({x}, {y})"
+        );
+
+        let behind = camera.eye().add(camera.eye().sub(camera.target));
+        assert_eq!(world_to_pixel(view_proj, behind, 800, 600), None);
+
+        let inverse = view_proj.inverse().unwrap();
+        let p = pixel_to_world(inverse, 120.0, 75.0, 0.5, 800, 600);
+        let [x, y] = world_to_pixel(view_proj, p, 800, 600).unwrap();
+        assert!(
+            (x - 120.0).abs() <= 0.05 && (y - 75.0).abs() <= 0.05,
+            "({x}, {y})"
+        );
+        // Depth 0 lands on the near plane.
+        let near = pixel_to_world(inverse, 400.0, 300.0, 0.0, 800, 600);
+        assert!((near.sub(camera.eye()).length() - camera.znear).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn redraw_flag_transitions() {
+        let flag = RedrawFlag::default();
+        assert!(!flag.get());
+        flag.mark();
+        flag.mark();
+        assert!(flag.get());
+        flag.clear();
+        assert!(!flag.get());
+    }
+
+    #[test]
+    fn pan_scale_follows_camera_distance() {
+        let tan_half = (45_f32.to_radians() * 0.5).tan();
+        let near = world_per_pixel(2.0 * tan_half, 600);
+        let far = world_per_pixel(4.0 * tan_half, 600);
+        assert!((far - 2.0 * near).abs() <= 1e-7);
+        // A full-height drag spans the whole visible height.
+        assert!((near * 600.0 - 4.0 * tan_half).abs() <= 1e-5);
+    }
+
+    #[test]
+    fn grid_size_uses_power_of_ten_cells() {
+        let (x, z) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let unit = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+        let (half, divisions) = grid_size(&unit, x, z);
+        assert!((half - 0.8).abs() <= 1e-5);
+        assert_eq!(divisions, 16);
+
+        let offset = Bounds::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(40.0, 1.0, 5.0));
+        let (half, divisions) = grid_size(&offset, x, z);
+        assert_eq!((half, divisions), (60.0, 12));
+
+        // With Z up the footprint is measured in XY, so a tall Z extent doesn't count.
+        let tall = Bounds::new(Vec3::new(-1.0, -2.0, 0.0), Vec3::new(1.0, 2.0, 50.0));
+        let (half, _) = grid_size(&tall, x, Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(half, 3.0);
+    }
+
+    #[test]
+    fn tick_with_zero_speed_keeps_angle() {
+        assert_eq!(advance_yaw(0.75, 0.0, 0.016), 0.75);
+        assert_eq!(advance_yaw(0.75, 0.0, 10.0), 0.75);
+    }
+
+    #[test]
+    fn tick_advances_and_wraps() {
+        let yaw = advance_yaw(0.0, 2.0, 0.5);
+        assert!((yaw - 1.0).abs() <= 1e-6);
+
+        let wrapped = advance_yaw(6.0, 1.0, 1.0);
+        assert!((wrapped - (7.0 - std::f32::consts::TAU)).abs() <= 1e-5);
+        let backwards = advance_yaw(0.25, -1.0, 0.5);
+        assert!((backwards - (std::f32::consts::TAU - 0.25)).abs() <= 1e-5);
+    }
+}